@@ -0,0 +1,47 @@
+#import bevy_pbr::{
+    prepass_io::VertexOutput,
+    mesh_functions,
+    view_transformations::position_world_to_clip,
+}
+#import "shaders/packed_vertex.wgsl"::unpack_vertex
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(8) packed: vec2<u32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    let unpacked = unpack_vertex(vertex.packed);
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+
+    var out: VertexOutput;
+    out.world_position = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(unpacked.position, 1.0));
+    out.position = position_world_to_clip(out.world_position.xyz);
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    out.unclipped_depth = out.position.z;
+    out.position.z = min(out.position.z, 1.0); // Clamp depth to avoid clipping
+#endif
+
+#ifdef NORMAL_PREPASS_OR_DEFERRED_PREPASS
+    out.world_normal = mesh_functions::mesh_normal_local_to_world(unpacked.normal, vertex.instance_index);
+#endif
+
+#ifdef MOTION_VECTOR_PREPASS
+    out.previous_world_position = mesh_functions::mesh_position_local_to_world(
+        mesh_functions::get_previous_world_from_local(vertex.instance_index),
+        vec4<f32>(unpacked.position, 1.0)
+    );
+#endif
+
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither = mesh_functions::get_visibility_range_dither_level(
+        vertex.instance_index, world_from_local[3]);
+#endif
+
+    return out;
+}
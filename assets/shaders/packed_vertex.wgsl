@@ -0,0 +1,73 @@
+// Unpacks the chunk vertices written by `pack_vertex` in packed_vertex.rs.
+
+struct PackedVertex {
+    // In voxels of the chunk, the mesh transform scales them into meters.
+    position: vec3<f32>,
+    normal: vec3<f32>,
+    uv: vec2<f32>,
+    texture_id: u32,
+    // How much the ambient occlusion and the light darken the vertex.
+    shade: f32,
+}
+
+const POSITION_STEPS: f32 = 16.0;
+const MAX_LIGHT: u32 = 15u;
+// The brightness of faces no light reaches, so caves aren't pitch black.
+const MIN_BRIGHTNESS: f32 = 0.05;
+
+fn unpack_vertex(packed: vec2<u32>) -> PackedVertex {
+    var vertex: PackedVertex;
+
+    vertex.position = vec3<f32>(
+        f32(packed.x & 0x7ffu),
+        f32(packed.y & 0xffffu),
+        f32((packed.x >> 11u) & 0x7ffu),
+    ) / POSITION_STEPS;
+
+    // Textures repeat with every voxel at full detail, starting at the
+    // corners of the voxels.
+    let multiplier = f32(1u << ((packed.x >> 27u) & 0xfu));
+    let corner = (vertex.position - vec3<f32>(0.5)) * multiplier;
+
+    switch (packed.x >> 22u) & 0x7u {
+        case 0u: {
+            vertex.normal = vec3<f32>(1.0, 0.0, 0.0);
+            vertex.uv = vec2<f32>(corner.z, -corner.y);
+        }
+        case 1u: {
+            vertex.normal = vec3<f32>(-1.0, 0.0, 0.0);
+            vertex.uv = vec2<f32>(corner.z, -corner.y);
+        }
+        case 2u: {
+            vertex.normal = vec3<f32>(0.0, 1.0, 0.0);
+            vertex.uv = corner.xz;
+        }
+        case 3u: {
+            vertex.normal = vec3<f32>(0.0, -1.0, 0.0);
+            vertex.uv = corner.xz;
+        }
+        case 4u: {
+            vertex.normal = vec3<f32>(0.0, 0.0, 1.0);
+            vertex.uv = vec2<f32>(corner.x, -corner.y);
+        }
+        case 5u: {
+            vertex.normal = vec3<f32>(0.0, 0.0, -1.0);
+            vertex.uv = vec2<f32>(corner.x, -corner.y);
+        }
+        default: {
+            // Plants are lit from above and textured like a side.
+            vertex.normal = vec3<f32>(0.0, 1.0, 0.0);
+            vertex.uv = vec2<f32>(corner.x, -corner.y);
+        }
+    }
+
+    vertex.texture_id = (packed.y >> 16u) & 0xffu;
+
+    let ambient_occlusion = f32((packed.x >> 25u) & 0x3u) / 4.0 + 0.25;
+    let light = max((packed.y >> 24u) & 0xfu, (packed.y >> 28u) & 0xfu);
+    let brightness = MIN_BRIGHTNESS
+        + (1.0 - MIN_BRIGHTNESS) * pow(0.8, f32(MAX_LIGHT - light));
+    vertex.shade = ambient_occlusion * brightness;
+
+    return vertex;
+}
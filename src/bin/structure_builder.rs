@@ -11,9 +11,8 @@ use bevy::{
         ExtendedMaterial,
         wireframe::{WireframeConfig, WireframePlugin},
     },
-    picking::pointer::PointerInteraction,
     prelude::*,
-    render::mesh::MeshAabb,
+    window::PrimaryWindow,
 };
 use bevy_inspector_egui::{
     bevy_egui::{EguiContexts, EguiPlugin, EguiPrimaryContextPass},
@@ -28,9 +27,10 @@ use opentale::{
     world_generation::{
         array_texture::ArrayTextureMaterial,
         chunk_generation::{
-            block_type::BlockType, chunk_lod::ChunkLod,
-            mesh_generation::generate_mesh,
+            block_shape::Facing, block_type::BlockType, chunk_lod::ChunkLod,
+            light_data::LightData, mesh_generation::generate_mesh,
             structures::structure_model::StructureModel, voxel_data::VoxelData,
+            world_voxels::raycast_voxels,
         },
         generation_assets::{
             GenerationAssetState, GenerationAssets, load_block_texture_assets,
@@ -40,11 +40,18 @@ use opentale::{
 };
 use ron::ser::PrettyConfig;
 
-const PLACEABLE_BLOCKS: [BlockType; 4] = [
+const PLACEABLE_BLOCKS: [BlockType; 11] = [
     BlockType::Stone,
     BlockType::Grass,
     BlockType::Log,
     BlockType::Snow,
+    BlockType::StoneSlab,
+    BlockType::StoneStairs(Facing::Right),
+    BlockType::StoneStairs(Facing::Left),
+    BlockType::StoneStairs(Facing::Front),
+    BlockType::StoneStairs(Facing::Back),
+    BlockType::LogFence,
+    BlockType::Lantern,
 ];
 
 fn main() {
@@ -97,7 +104,6 @@ fn main() {
                     ..default()
                 })
                 .set(ImagePlugin::default_nearest()),
-            MeshPickingPlugin,
             PanOrbitCameraPlugin,
             WireframePlugin::default(),
             EguiPlugin::default(),
@@ -130,6 +136,7 @@ fn main() {
             default_color: Color::srgb(1., 0., 0.),
         })
         .insert_resource(VoxelDataResource {
+            light_data: LightData::new(&voxel_data, true),
             voxel_data,
             selected_block: BlockType::Stone,
         })
@@ -141,9 +148,17 @@ fn main() {
 #[derive(Resource)]
 struct VoxelDataResource {
     voxel_data: VoxelData,
+    light_data: LightData,
     selected_block: BlockType,
 }
 
+impl VoxelDataResource {
+    fn set_block(&mut self, position: IVec3, block: BlockType) {
+        self.voxel_data.set_block(position, block);
+        self.light_data.update_block(&self.voxel_data, position);
+    }
+}
+
 #[derive(Component)]
 struct MeshEntity;
 
@@ -185,6 +200,7 @@ fn setup(
             base: StandardMaterial::from_color(Color::WHITE),
             extension: ArrayTextureMaterial {
                 array_texture: generation_assets.texture_handle.clone(),
+                wave_height: 0.,
             },
         })),
     ));
@@ -205,36 +221,54 @@ fn remesh(
     mut commands: Commands,
 ) {
     for entity in mesh_entities {
-        let mesh_result =
-            generate_mesh(&voxel_data.voxel_data, 0, ChunkLod::Full);
+        let mesh_result = generate_mesh(
+            &voxel_data.voxel_data,
+            &voxel_data.light_data,
+            0,
+            ChunkLod::Full,
+        );
 
         let Some(mesh) = mesh_result.opaque_mesh else {
             return;
         };
 
-        let Some(aabb) = mesh.compute_aabb() else {
-            return;
-        };
-
-        commands
-            .entity(entity)
-            .insert((Mesh3d(meshes.add(mesh)), aabb));
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(mesh)),
+            mesh_result.transform,
+            mesh_result.aabb,
+        ));
     }
 }
 
 fn update_blocks(
     mut voxel_data: ResMut<VoxelDataResource>,
-    pointers: Query<&PointerInteraction>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
     mouse: Res<ButtonInput<MouseButton>>,
     mut gizmos: Gizmos,
 ) {
-    for (point, normal) in pointers
-        .iter()
-        .filter_map(|interaction| interaction.get_nearest_hit())
-        .filter_map(|(_entity, hit)| hit.position.zip(hit.normal))
-    {
+    let Some(cursor) = windows
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+
+    for (camera, camera_transform) in cameras {
+        let Ok(ray) = camera.viewport_to_world(camera_transform, cursor) else {
+            continue;
+        };
+
+        let Some(hit) = raycast_voxels(ray, 200., |position| {
+            (position.min_element() >= 1 && position.max_element() <= 64)
+                .then(|| voxel_data.voxel_data.get_block(position))
+        }) else {
+            continue;
+        };
+
         if mouse.just_released(MouseButton::Left) {
-            let next_block_pos = (point + normal * 0.1).floor().as_ivec3();
+            let next_block_pos = hit.position + hit.normal;
 
             if next_block_pos.min_element() < 1
                 || next_block_pos.max_element() > 64
@@ -243,25 +277,17 @@ fn update_blocks(
             }
 
             let block = voxel_data.selected_block;
-            voxel_data.voxel_data.set_block(next_block_pos, block);
+            voxel_data.set_block(next_block_pos, block);
         }
 
         if mouse.just_released(MouseButton::Right) {
-            let current_block_pos = (point - normal * 0.1).floor().as_ivec3();
-
-            if current_block_pos.min_element() < 1
-                || current_block_pos.max_element() > 64
-            {
-                continue;
-            }
-
-            voxel_data
-                .voxel_data
-                .set_block(current_block_pos, BlockType::Air);
+            voxel_data.set_block(hit.position, BlockType::Air);
         }
 
+        let point = ray.get_point(hit.distance);
+        let normal = hit.normal.as_vec3();
         gizmos.sphere(point, 0.05, RED_500);
-        gizmos.arrow(point, point + normal.normalize() * 0.5, PINK_100);
+        gizmos.arrow(point, point + normal * 0.5, PINK_100);
     }
 }
 
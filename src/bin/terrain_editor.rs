@@ -1,47 +1,133 @@
-use bevy::prelude::*;
+use std::f32::consts::PI;
+
+use bevy::{pbr::ExtendedMaterial, prelude::*};
 use bevy_inspector_egui::{
     bevy_egui::{EguiContexts, EguiPlugin, EguiPrimaryContextPass},
     egui::{self},
 };
-use opentale::debug_tools::terrain_node_editor::terrain_graph_resource::TerrainGraphResource;
+use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
+use opentale::{
+    debug_tools::terrain_node_editor::{
+        terrain_chunk_preview::{
+            PreviewChunkTask, TerrainChunkPreview, TerrainChunkPreviewPlugin,
+        },
+        terrain_graph_resource::TerrainGraphResource,
+        terrain_preview::TerrainPreview,
+    },
+    world_generation::{
+        array_texture::ArrayTextureMaterial,
+        generation_assets::{
+            GenerationAssetState, load_block_texture_assets,
+            setup_array_texture,
+        },
+    },
+};
 
 fn main() {
     App::new()
         .add_plugins((
-            DefaultPlugins.set(WindowPlugin {
-                primary_window: Some(Window {
-                    title: "Opentale - Terrain Editor".into(),
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: "Opentale - Terrain Editor".into(),
+                        ..default()
+                    }),
                     ..default()
-                }),
-                ..default()
-            }),
+                })
+                .set(ImagePlugin::default_nearest()),
+            PanOrbitCameraPlugin,
             EguiPlugin::default(),
+            MaterialPlugin::<
+                ExtendedMaterial<StandardMaterial, ArrayTextureMaterial>,
+            >::default(),
+            TerrainChunkPreviewPlugin,
         ))
-        .add_systems(Startup, setup_camera)
+        .add_systems(Startup, setup)
+        .add_systems(
+            Update,
+            setup_array_texture.run_if(in_state(GenerationAssetState::Loading)),
+        )
+        .add_systems(
+            OnEnter(GenerationAssetState::Unloaded),
+            load_block_texture_assets,
+        )
         .add_systems(EguiPrimaryContextPass, render_terrain_editor)
+        .init_state::<GenerationAssetState>()
         .init_resource::<TerrainGraphResource>()
+        .init_resource::<TerrainPreview>()
         .run();
 }
 
-fn setup_camera(mut commands: Commands) {
-    commands.spawn(Camera2d);
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        DirectionalLight {
+            shadows_enabled: true,
+            illuminance: 1000.,
+            ..default()
+        },
+        Transform {
+            translation: Vec3::new(0.0, 2.0, 0.0),
+            rotation: Quat::from_rotation_x(-PI / 3.),
+            ..default()
+        },
+        Name::new("Light"),
+    ));
+
+    commands.insert_resource(AmbientLight {
+        color: Color::WHITE,
+        brightness: 75f32,
+        ..default()
+    });
+
+    commands.spawn((
+        Transform::default(),
+        PanOrbitCamera {
+            radius: Some(500.),
+            pitch: Some(PI / 4.),
+            ..Default::default()
+        },
+    ));
 }
 
 fn render_terrain_editor(
     mut contexts: EguiContexts,
     mut terrain_graph: ResMut<TerrainGraphResource>,
+    mut terrain_preview: ResMut<TerrainPreview>,
+    mut chunk_preview: ResMut<TerrainChunkPreview>,
+    preview_chunk_tasks: Query<(), With<PreviewChunkTask>>,
 ) -> Result {
     let ctx = contexts.ctx_mut()?;
 
-    let _graph_response = egui::CentralPanel::default()
-        .show(ctx, |ui| terrain_graph.draw(ui))
-        .inner;
-
     egui::TopBottomPanel::bottom("bottom").show(ctx, |ui| {
-        if ui.button("Save").clicked() {
-            terrain_graph.save().expect("Could not save!");
-        }
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked()
+                && let Err(error) = terrain_graph.save()
+            {
+                error!("Could not save terrain graph: {error}");
+            }
+
+            if let Some(error) = terrain_graph.error() {
+                ui.colored_label(egui::Color32::LIGHT_RED, error);
+            }
+        });
     });
 
+    egui::SidePanel::right("preview")
+        .default_width(400.)
+        .show(ctx, |ui| {
+            ui.heading("3D Preview");
+            chunk_preview.draw(ui, preview_chunk_tasks.iter().count());
+            ui.separator();
+            ui.heading("Heightmap");
+            terrain_preview.draw(ui, &terrain_graph);
+        });
+
+    // The space left of the panels shows the 3D preview.
+    let _graph_response = egui::SidePanel::left("graph")
+        .resizable(true)
+        .default_width(ctx.screen_rect().width() * 0.5)
+        .show(ctx, |ui| terrain_graph.draw(ui))
+        .inner;
+
     Ok(())
 }
@@ -10,16 +10,17 @@ use bevy_inspector_egui::bevy_egui::EguiPlugin;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
 use bevy_rapier3d::prelude::{NoUserData, RapierPhysicsPlugin};
-use fastnoise_lite::FastNoiseLite;
 use rand::{rng, RngCore};
 use spellhaven::animations::AnimationPlugin;
 use spellhaven::debug_tools::debug_resource::OpentaleDebugPlugin;
 use spellhaven::terrain_material::TerrainMaterial;
+use spellhaven::world_generation::chunk_generation::light_data::LightData;
 use spellhaven::world_generation::chunk_generation::mesh_generation::generate_mesh;
 use spellhaven::world_generation::chunk_generation::pine_structure_generator::PineStructureGenerator;
 use spellhaven::world_generation::chunk_generation::structure_generator::{
     StructureGenerator, VoxelStructureMetadata,
 };
+use spellhaven::world_generation::chunk_generation::structure_placement::PlacementSettings;
 use spellhaven::world_generation::chunk_generation::voxel_types::VoxelData;
 use spellhaven::world_generation::chunk_generation::{BlockType, CHUNK_SIZE, VOXEL_SIZE};
 use spellhaven::world_generation::voxel_world::ChunkLod;
@@ -113,7 +114,8 @@ fn spawn_mesh(
     let chunks = get_tree_voxel_data();
 
     for (chunk, chunk_pos) in chunks {
-        let mesh = generate_mesh(&chunk, 0, ChunkLod::Full);
+        let light_data = LightData::new(&chunk, true);
+        let mesh = generate_mesh(&chunk, &light_data, 0, ChunkLod::Full);
 
         let Some(mesh) = mesh else {
             continue;
@@ -172,20 +174,15 @@ fn get_tree_voxel_data() -> Vec<(Box<VoxelData>, IVec3)> {
         (Box::new(VoxelData::default()), IVec3::new(1, 2, 1)),
     ];
 
-    let seed = rng().next_u32();
-
-    let mut noise = FastNoiseLite::with_seed(seed as i32);
-    noise.set_noise_type(Some(fastnoise_lite::NoiseType::Value));
-    noise.set_frequency(Some(100.));
+    let seed = rng().next_u64();
 
     let tree_generator = PineStructureGenerator {
         fixed_structure_metadata: VoxelStructureMetadata {
             debug_rgb_multiplier: [0., 0., 0.],
             generate_debug_blocks: false,
-            generation_size: [0, 0],
-            grid_offset: [0, 0],
             model_size: [0, 0, 0],
-            noise,
+            placement: PlacementSettings::default(),
+            seed,
         },
     };
 
@@ -34,9 +34,6 @@ pub fn draw_path_gizmos(
         return;
     }
 
-    let terrain_noise =
-        Add::new(generation_options.0.get_terrain_noise(), Constant::new(5.));
-
     for player in &players {
         let player_chunk_pos =
             AbsoluteChunkPos::from_absolute(player.translation);
@@ -55,6 +52,11 @@ pub fn draw_path_gizmos(
         let GenerationState::Some(country_cache) = country_cache else {
             continue;
         };
+        let terrain_noise = Add::new(
+            country_cache.terrain_height(&generation_options.0),
+            Constant::new(5.),
+        );
+
         for path in country_cache
             .this_path_cache
             .paths
@@ -2,7 +2,10 @@ use egui_node_editor::NodeTemplateIter;
 
 use crate::debug_tools::terrain_node_editor::terrain_node_template::TerrainNodeTemplate;
 
-pub struct AllTerrainNodeTemplates;
+pub struct AllTerrainNodeTemplates {
+    pub subgraph_count: usize,
+}
+
 impl NodeTemplateIter for AllTerrainNodeTemplates {
     type Item = TerrainNodeTemplate;
 
@@ -19,6 +22,16 @@ impl NodeTemplateIter for AllTerrainNodeTemplates {
             TerrainNodeTemplate::Max,
             TerrainNodeTemplate::PowF64,
             TerrainNodeTemplate::VoxelSize,
+            TerrainNodeTemplate::Cellular,
+            TerrainNodeTemplate::Roughness,
+            TerrainNodeTemplate::FractalOpenSimplex,
+            TerrainNodeTemplate::ShiftNScale,
+            TerrainNodeTemplate::Steepness,
+            TerrainNodeTemplate::DomainWarp,
+            TerrainNodeTemplate::SplineRemap,
         ]
+        .into_iter()
+        .chain((0..self.subgraph_count).map(TerrainNodeTemplate::Subgraph))
+        .collect()
     }
 }
@@ -1,7 +1,11 @@
 mod all_terrain_node_templates;
+pub mod terrain_chunk_preview;
 mod terrain_data_type;
+pub mod terrain_graph_error;
 pub mod terrain_graph_resource;
 mod terrain_node_data;
 mod terrain_node_template;
+pub mod terrain_preview;
 mod terrain_response;
+mod terrain_subgraph;
 mod terrain_value_type;
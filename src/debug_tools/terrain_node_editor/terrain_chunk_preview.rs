@@ -0,0 +1,279 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy::tasks::Task;
+use bevy_inspector_egui::egui::{self, DragValue};
+use futures_lite::future;
+use itertools::Itertools;
+
+use crate::{
+    debug_tools::terrain_node_editor::{
+        terrain_graph_resource::TerrainGraphResource, terrain_preview::TerrainPreview,
+    },
+    world_generation::{
+        chunk_generation::{
+            CHUNK_SIZE,
+            chunk_lod::ChunkLod,
+            chunk_task::ChunkTaskPool,
+            country::{
+                country_cache::CacheStore, country_cache_position::CountryPosition,
+                country_data::CountryData, generation_cache::GenerationCacheItem,
+            },
+            light_data::LightData,
+            mesh_generation::{
+                HEIGHTMAP_LOD, MeshResult, generate_heightmap_mesh, generate_mesh,
+            },
+            structures::structure_placement::placement_reach,
+            voxel_generation::{generate_columns, generate_voxels},
+        },
+        chunk_loading::chunk_pos::AbsoluteChunkPos,
+        generation_assets::{GenerationAssetState, GenerationAssets},
+        generation_options::GenerationOptions,
+    },
+};
+
+const MAX_PREVIEW_RADIUS: i32 = 8;
+const MAX_STACKED_CHUNKS: i32 = 16;
+
+pub struct TerrainChunkPreviewPlugin;
+
+impl Plugin for TerrainChunkPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TerrainChunkPreview>()
+            .init_resource::<ChunkTaskPool>()
+            .add_systems(
+                Update,
+                (queue_preview_chunks, set_preview_chunks)
+                    .chain()
+                    .run_if(in_state(GenerationAssetState::Loaded)),
+            );
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct PreviewSettings {
+    serialized_noise: String,
+    seed: u64,
+    lod: ChunkLod,
+    radius: i32,
+}
+
+/// Generates a few chunks from the unsaved terrain graph, so they can be
+/// looked at in the editor viewport.
+#[derive(Resource)]
+pub struct TerrainChunkPreview {
+    lod: ChunkLod,
+    radius: i32,
+    generated_settings: Option<PreviewSettings>,
+}
+
+impl Default for TerrainChunkPreview {
+    fn default() -> Self {
+        Self {
+            lod: ChunkLod::Quarter,
+            radius: 2,
+            generated_settings: None,
+        }
+    }
+}
+
+impl TerrainChunkPreview {
+    pub fn draw(&mut self, ui: &mut egui::Ui, generating_chunks: usize) {
+        ui.horizontal(|ui| {
+            ui.label("LOD");
+            egui::ComboBox::from_id_salt("preview_lod")
+                .selected_text(format!("{:?}", self.lod))
+                .show_ui(ui, |ui| {
+                    for lod in (1..=9).filter_map(ChunkLod::from_u8) {
+                        ui.selectable_value(&mut self.lod, lod, format!("{:?}", lod));
+                    }
+                });
+            ui.label("Radius");
+            ui.add(DragValue::new(&mut self.radius).range(1..=MAX_PREVIEW_RADIUS));
+        });
+
+        if generating_chunks > 0 {
+            ui.label(format!("Generating {generating_chunks} chunks..."));
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct PreviewChunk {
+    chunk_pos: IVec2,
+}
+
+#[derive(Component)]
+pub struct PreviewChunkTask(Task<Vec<MeshResult>>);
+
+fn queue_preview_chunks(
+    mut commands: Commands,
+    mut chunk_preview: ResMut<TerrainChunkPreview>,
+    terrain_graph: Res<TerrainGraphResource>,
+    terrain_preview: Res<TerrainPreview>,
+    preview_chunks: Query<(Entity, &PreviewChunk)>,
+    chunk_task_pool: Res<ChunkTaskPool>,
+) {
+    let Ok(terrain_noise) = terrain_graph.to_terrain_noise() else {
+        return;
+    };
+    let Ok(serialized_noise) = ron::to_string(&terrain_noise) else {
+        return;
+    };
+
+    let settings = PreviewSettings {
+        serialized_noise,
+        seed: terrain_preview.seed(),
+        lod: chunk_preview.lod,
+        radius: chunk_preview.radius,
+    };
+    if chunk_preview.generated_settings.as_ref() == Some(&settings) {
+        return;
+    }
+
+    let Ok(generation_options) = GenerationOptions::from_terrain_noise(settings.seed, terrain_noise)
+    else {
+        return;
+    };
+    let generation_options = Arc::new(generation_options);
+    let cache_store = Arc::new(CacheStore::default());
+
+    let chunk_positions = (-settings.radius..settings.radius)
+        .cartesian_product(-settings.radius..settings.radius)
+        .map(|(x, z)| IVec2::new(x, z) * settings.lod.multiplier_i32())
+        .collect_vec();
+
+    for (entity, preview_chunk) in &preview_chunks {
+        if !chunk_positions.contains(&preview_chunk.chunk_pos) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for chunk_pos in chunk_positions {
+        let generation_options = generation_options.clone();
+        let cache_store = cache_store.clone();
+        let lod = settings.lod;
+        let task = chunk_task_pool.task_pool.spawn(async move {
+            generate_preview_chunk(chunk_pos, lod, &generation_options, cache_store)
+        });
+
+        // Replacing the task of an existing chunk drops the outdated one and
+        // keeps its old meshes visible until the new ones are done.
+        if let Some((entity, _)) = preview_chunks
+            .iter()
+            .find(|(_, preview_chunk)| preview_chunk.chunk_pos == chunk_pos)
+        {
+            commands.entity(entity).insert(PreviewChunkTask(task));
+        } else {
+            commands.spawn((
+                PreviewChunk { chunk_pos },
+                PreviewChunkTask(task),
+                Transform::from_translation(AbsoluteChunkPos::new(chunk_pos).to_absolute()),
+                Visibility::Visible,
+            ));
+        }
+    }
+
+    chunk_preview.generated_settings = Some(settings);
+}
+
+fn generate_preview_chunk(
+    chunk_pos: IVec2,
+    lod: ChunkLod,
+    generation_options: &GenerationOptions,
+    cache_store: Arc<CacheStore>,
+) -> Vec<MeshResult> {
+    let country_pos = CountryPosition::from_chunk_pos(AbsoluteChunkPos::new(chunk_pos));
+    let reach = placement_reach(&generation_options.structure_generators);
+    let area_min = chunk_pos * CHUNK_SIZE as i32;
+    let area_max = area_min + IVec2::splat(CHUNK_SIZE as i32 + 1) * lod.multiplier_i32();
+    let min_country = CountryPosition::from_column(area_min - reach);
+    let max_country = CountryPosition::from_column(area_max + reach);
+    let countries = (min_country.x..=max_country.x)
+        .cartesian_product(min_country.y..=max_country.y)
+        .map(|(x, y)| {
+            CountryData::generate(
+                CountryPosition::new(IVec2::new(x, y)),
+                generation_options,
+                cache_store.clone(),
+            )
+        })
+        .collect_vec();
+    let country_data = countries
+        .iter()
+        .find(|country| country.country_pos == country_pos)
+        .expect("The country of the chunk is within the reach of its structures!");
+
+    let columns = generate_columns(chunk_pos, generation_options, lod, country_data);
+    if lod >= HEIGHTMAP_LOD {
+        return vec![generate_heightmap_mesh(&columns, lod)];
+    }
+
+    let mut mesh_results = Vec::new();
+    for stack_height in 0..MAX_STACKED_CHUNKS {
+        let (data, min_height, more) = generate_voxels(
+            [chunk_pos.x, stack_height, chunk_pos.y],
+            &columns,
+            generation_options,
+            lod,
+            country_data,
+            &countries,
+        );
+        let light_data = LightData::new(&data, true);
+        mesh_results.push(generate_mesh(&data, &light_data, min_height, lod));
+
+        if !more {
+            break;
+        }
+    }
+
+    mesh_results
+}
+
+fn set_preview_chunks(
+    mut commands: Commands,
+    mut tasks: Query<(Entity, &mut PreviewChunkTask)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    generation_assets: Res<GenerationAssets>,
+) {
+    for (entity, mut task) in &mut tasks {
+        let Some(mesh_results) = future::block_on(future::poll_once(&mut task.0)) else {
+            continue;
+        };
+
+        commands
+            .entity(entity)
+            .remove::<PreviewChunkTask>()
+            .despawn_related::<Children>()
+            .with_children(|child_spawner| {
+                for mesh_result in mesh_results {
+                    if let Some(mesh) = mesh_result.opaque_mesh {
+                        child_spawner.spawn((
+                            Mesh3d(meshes.add(mesh)),
+                            MeshMaterial3d(generation_assets.opaque_material.clone()),
+                            mesh_result.transform,
+                            mesh_result.aabb,
+                        ));
+                    }
+
+                    if let Some(mesh) = mesh_result.transparent_mesh {
+                        child_spawner.spawn((
+                            Mesh3d(meshes.add(mesh)),
+                            MeshMaterial3d(generation_assets.transparent_material.clone()),
+                            mesh_result.transform,
+                            mesh_result.aabb,
+                        ));
+                    }
+
+                    if let Some(mesh) = mesh_result.fluid_mesh {
+                        child_spawner.spawn((
+                            Mesh3d(meshes.add(mesh)),
+                            MeshMaterial3d(generation_assets.fluid_material.clone()),
+                            mesh_result.transform,
+                            mesh_result.aabb,
+                        ));
+                    }
+                }
+            });
+    }
+}
@@ -4,19 +4,25 @@ use bevy_inspector_egui::egui;
 use egui_node_editor::DataTypeTrait;
 use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq, Serialize, Deserialize)]
+use crate::debug_tools::terrain_node_editor::terrain_subgraph::TerrainSubgraphLibrary;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TerrainDataType {
     NoiseF64x2,
     F64,
     I64,
+    CellularDistance,
+    CellularReturn,
 }
 
-impl DataTypeTrait<()> for TerrainDataType {
-    fn data_type_color(&self, _: &mut ()) -> egui::Color32 {
+impl DataTypeTrait<TerrainSubgraphLibrary> for TerrainDataType {
+    fn data_type_color(&self, _: &mut TerrainSubgraphLibrary) -> egui::Color32 {
         match self {
             TerrainDataType::NoiseF64x2 => egui::Color32::YELLOW,
             TerrainDataType::F64 => egui::Color32::BLUE,
             TerrainDataType::I64 => egui::Color32::DARK_GREEN,
+            TerrainDataType::CellularDistance => egui::Color32::LIGHT_RED,
+            TerrainDataType::CellularReturn => egui::Color32::LIGHT_BLUE,
         }
     }
 
@@ -25,6 +31,10 @@ impl DataTypeTrait<()> for TerrainDataType {
             TerrainDataType::NoiseF64x2 => Cow::Borrowed("Noise F64 x 2"),
             TerrainDataType::F64 => Cow::Borrowed("F64"),
             TerrainDataType::I64 => Cow::Borrowed("I64"),
+            TerrainDataType::CellularDistance => {
+                Cow::Borrowed("Cellular Distance")
+            }
+            TerrainDataType::CellularReturn => Cow::Borrowed("Cellular Return"),
         }
     }
 }
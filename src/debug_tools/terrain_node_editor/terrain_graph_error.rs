@@ -0,0 +1,37 @@
+use std::fmt::{self, Display};
+
+use egui_node_editor::{Node, NodeId};
+
+use crate::debug_tools::terrain_node_editor::terrain_node_data::TerrainNodeData;
+
+/// A problem found while turning the terrain graph into terrain noise,
+/// pointing at the node that caused it where there is one.
+#[derive(Debug, Clone)]
+pub struct TerrainGraphError {
+    pub node_id: Option<NodeId>,
+    message: String,
+}
+
+impl TerrainGraphError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            node_id: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn at_node(node: &Node<TerrainNodeData>, message: impl Display) -> Self {
+        Self {
+            node_id: Some(node.id),
+            message: format!("{}: {message}", node.label),
+        }
+    }
+}
+
+impl Display for TerrainGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for TerrainGraphError {}
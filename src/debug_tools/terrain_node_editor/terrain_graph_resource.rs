@@ -1,74 +1,147 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs::File,
     io::{Read, Write},
 };
 
-use anyhow::anyhow;
 use bevy::prelude::*;
-use bevy_inspector_egui::egui::Ui;
-use egui_node_editor::{GraphEditorState, GraphResponse, Node, OutputId};
+use bevy_inspector_egui::egui::{self, Ui};
+use egui_node_editor::{
+    DataTypeTrait, GraphEditorState, GraphResponse, InputId, Node, NodeId, NodeResponse,
+    NodeTemplateTrait, OutputId,
+};
 use ron::ser::PrettyConfig;
 
 use crate::{
     debug_tools::terrain_node_editor::{
         all_terrain_node_templates::AllTerrainNodeTemplates,
         terrain_data_type::TerrainDataType,
+        terrain_graph_error::TerrainGraphError,
         terrain_node_data::TerrainNodeData,
         terrain_node_template::{TerrainGraph, TerrainNodeTemplate},
         terrain_response::TerrainResponse,
+        terrain_subgraph::{TerrainSubgraph, TerrainSubgraphLibrary},
         terrain_value_type::TerrainValueType,
     },
     world_generation::chunk_generation::{
         VOXEL_SIZE,
         noise::{
+            cellular_noise::{CellularDistance, CellularReturn},
             terrain_noise::{TERRAIN_NOISE_FILE_PATH, TerrainNoise},
             terrain_noise_type::TerrainNoiseType,
         },
     },
 };
 
-type TerrainGraphState =
-    GraphEditorState<TerrainNodeData, TerrainDataType, TerrainValueType, TerrainNodeTemplate, ()>;
+type TerrainGraphState = GraphEditorState<
+    TerrainNodeData,
+    TerrainDataType,
+    TerrainValueType,
+    TerrainNodeTemplate,
+    TerrainSubgraphLibrary,
+>;
 
 #[derive(Resource, Deref, DerefMut)]
 pub struct TerrainGraphResource {
+    #[deref]
     state: TerrainGraphState,
+    library: TerrainSubgraphLibrary,
+    subgraph_name: String,
+    error: Option<String>,
 }
 
 const TERRAIN_NOISE_GRAPH_FILE_PATH: &'static str = "assets/terrain_noise_graph.ron";
 
+const MAX_OCTAVES: i64 = 32;
+
+/// Guards against subgraphs that contain themselves.
+const MAX_SUBGRAPH_DEPTH: usize = 16;
+
 impl Default for TerrainGraphResource {
     fn default() -> Self {
-        let file = File::open(TERRAIN_NOISE_GRAPH_FILE_PATH);
-        let Ok(mut file) = file else {
-            return Self {
-                state: Default::default(),
-            };
+        let (library, library_error) = match TerrainSubgraphLibrary::load() {
+            Ok(library) => (library, None),
+            Err(error) => (
+                TerrainSubgraphLibrary::default(),
+                Some(format!("Could not read the subgraph library: {error}")),
+            ),
         };
 
-        let mut output_string = String::new();
-        let read_result = file.read_to_string(&mut output_string);
-        if read_result.is_err() {
-            return Self {
-                state: Default::default(),
-            };
+        let (state, graph_error) = match load_graph_state() {
+            Ok(state) => (state.unwrap_or_default(), None),
+            Err(error) => (
+                Default::default(),
+                Some(format!(
+                    "Could not read {TERRAIN_NOISE_GRAPH_FILE_PATH}, saving will overwrite it: {error}"
+                )),
+            ),
+        };
+
+        Self {
+            state,
+            library,
+            subgraph_name: String::new(),
+            error: graph_error.or(library_error),
         }
+    }
+}
 
-        let state: TerrainGraphState =
-            ron::from_str(&output_string).expect("cannot read terrain data");
+/// Reads the saved graph, if there is one.
+fn load_graph_state() -> Result<Option<TerrainGraphState>, ron::error::SpannedError> {
+    let Ok(mut file) = File::open(TERRAIN_NOISE_GRAPH_FILE_PATH) else {
+        return Ok(None);
+    };
 
-        Self { state }
+    let mut output_string = String::new();
+    if file.read_to_string(&mut output_string).is_err() {
+        return Ok(None);
     }
+
+    ron::from_str(&output_string).map(Some)
 }
 
 impl TerrainGraphResource {
     pub fn draw(&mut self, ui: &mut Ui) -> GraphResponse<TerrainResponse, TerrainNodeData> {
-        self.state
-            .draw_graph_editor(ui, AllTerrainNodeTemplates, &mut (), Vec::default())
+        ui.horizontal(|ui| {
+            ui.label("Subgraph name");
+            ui.text_edit_singleline(&mut self.subgraph_name);
+            let collapse_button = ui.add_enabled(
+                !self.state.selected_nodes.is_empty(),
+                egui::Button::new("Collapse selection"),
+            );
+            if collapse_button.clicked() {
+                self.error = self
+                    .collapse_selection()
+                    .err()
+                    .map(|error| error.to_string());
+            }
+        });
+
+        let all_templates = AllTerrainNodeTemplates {
+            subgraph_count: self.library.count(),
+        };
+        let response =
+            self.state
+                .draw_graph_editor(ui, all_templates, &mut self.library, Vec::default());
+
+        for node_response in &response.node_responses {
+            let NodeResponse::User(TerrainResponse::SetCurvePoints { node_id, points }) =
+                node_response
+            else {
+                continue;
+            };
+
+            if let Some(node) = self.state.graph.nodes.get_mut(*node_id) {
+                node.user_data.curve_points = points.clone();
+            }
+        }
+
+        response
     }
 
-    pub fn save(&self) -> Result<(), anyhow::Error> {
+    /// Walks the graph from its output node into the noise types the
+    /// terrain generation reads.
+    pub fn to_terrain_noise(&self) -> Result<TerrainNoise, TerrainGraphError> {
         let output_nodes = self
             .graph
             .nodes
@@ -76,22 +149,107 @@ impl TerrainGraphResource {
             .filter(|node| node.1.user_data.template == TerrainNodeTemplate::Output)
             .collect::<Vec<_>>();
 
-        if output_nodes.len() != 1 {
-            return Err(anyhow!("Too many or too little number of output nodes!"));
-        }
+        let [(_, output_node)] = output_nodes.as_slice() else {
+            return Err(TerrainGraphError::new(format!(
+                "The graph needs exactly one output node, but has {}!",
+                output_nodes.len()
+            )));
+        };
 
-        let output_node = output_nodes.first();
-        let Some(output_node) = output_node else {
-            return Err(anyhow!("No output node found!"));
+        let mut walker = TerrainGraphWalker {
+            graph: &self.graph,
+            library: &self.library,
+            bound_inputs: HashMap::new(),
+            depth: 0,
+            noise_types: Vec::new(),
+            value_cache: HashMap::new(),
+            visiting: HashSet::new(),
         };
+        let start_index = walker.noise_input(output_node, "A")?;
 
-        let mut noise_array = Vec::new();
-        let mut cache = HashMap::new();
+        Ok(TerrainNoise::new(start_index, walker.noise_types))
+    }
 
-        let start_index =
-            get_terrain_noise_index(output_node.1, &mut noise_array, &self.graph, &mut cache);
+    /// The error of the last save or collapse, or of loading the graph.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn save(&mut self) -> Result<(), anyhow::Error> {
+        let result = self.write_files();
+
+        self.error = result.as_ref().err().map(|error| error.to_string());
+        if let Some(node_id) = result
+            .as_ref()
+            .err()
+            .and_then(|error| error.downcast_ref::<TerrainGraphError>())
+            .and_then(|error| error.node_id)
+        {
+            self.state.selected_nodes = vec![node_id];
+        }
 
-        let terrain_noise = TerrainNoise::new(start_index.get_noise_index(), noise_array);
+        result
+    }
+
+    /// Replaces the selected nodes with a node of a new subgraph, which is
+    /// added to the library under the entered name.
+    fn collapse_selection(&mut self) -> Result<(), anyhow::Error> {
+        let node_ids = self.state.selected_nodes.clone();
+        let position_sum = node_ids
+            .iter()
+            .filter_map(|node_id| self.state.node_positions.get(*node_id))
+            .fold(egui::Vec2::ZERO, |sum, position| sum + position.to_vec2());
+        let position = egui::Pos2::ZERO + position_sum / node_ids.len().max(1) as f32;
+
+        let collapsed = TerrainSubgraph::collapse(
+            &mut self.state.graph,
+            self.subgraph_name.clone(),
+            &node_ids,
+        )?;
+        self.state
+            .node_order
+            .retain(|node_id| !node_ids.contains(node_id));
+
+        let template = TerrainNodeTemplate::Subgraph(self.library.insert(collapsed.subgraph));
+        let label = template.node_graph_label(&mut self.library);
+        let user_data = template.user_data(&mut self.library);
+        let library = &mut self.library;
+        let node_id = self
+            .state
+            .graph
+            .add_node(label, user_data, |graph, node_id| {
+                template.build_node(graph, library, node_id)
+            });
+
+        let node = &self.state.graph[node_id];
+        let input_ids = node
+            .inputs
+            .iter()
+            .map(|(_, id)| *id)
+            .collect::<Vec<InputId>>();
+        let output_ids = node
+            .outputs
+            .iter()
+            .map(|(_, id)| *id)
+            .collect::<Vec<OutputId>>();
+        for (input_id, source) in input_ids.into_iter().zip(collapsed.input_sources) {
+            self.state.graph.add_connection(source, input_id);
+        }
+        for (output_id, targets) in output_ids.into_iter().zip(collapsed.output_targets) {
+            for target in targets {
+                self.state.graph.add_connection(output_id, target);
+            }
+        }
+
+        self.state.node_positions.insert(node_id, position);
+        self.state.node_order.push(node_id);
+        self.state.selected_nodes = vec![node_id];
+
+        self.library.save()
+    }
+
+    fn write_files(&self) -> Result<(), anyhow::Error> {
+        let terrain_noise = self.to_terrain_noise()?;
 
         let mut file = File::create(TERRAIN_NOISE_FILE_PATH)?;
         let text = ron::ser::to_string_pretty(&terrain_noise, PrettyConfig::default())?;
@@ -107,112 +265,432 @@ impl TerrainGraphResource {
     }
 }
 
-fn get_terrain_noise_index(
-    node: &Node<TerrainNodeData>,
-    noise_array: &mut Vec<TerrainNoiseType>,
-    graph: &TerrainGraph,
-    value_cache: &mut HashMap<OutputId, TerrainValueType>,
-) -> TerrainValueType {
-    let mut get_input_value = |input_name: &str| {
-        let input_id = node.get_input(input_name).expect("Input not found!");
-        let connection = graph.connection(input_id);
-
-        let Some(connection) = connection else {
-            return graph[input_id].value;
-        };
+struct TerrainGraphWalker<'a> {
+    graph: &'a TerrainGraph,
+    library: &'a TerrainSubgraphLibrary,
+    /// Values of the inputs inside a subgraph that are fed from outside.
+    bound_inputs: HashMap<InputId, TerrainValueType>,
+    depth: usize,
+    noise_types: Vec<TerrainNoiseType>,
+    value_cache: HashMap<OutputId, TerrainValueType>,
+    visiting: HashSet<NodeId>,
+}
 
-        if let Some(cached_value) = value_cache.get(&connection) {
-            return *cached_value;
-        }
+impl TerrainGraphWalker<'_> {
+    fn push(&mut self, noise_type: TerrainNoiseType) -> TerrainValueType {
+        let noise_index = self.noise_types.len();
+        self.noise_types.push(noise_type);
+        TerrainValueType::NoiseF64x2 { noise_index }
+    }
 
-        let node = &graph[graph[connection].node];
-        let value = get_terrain_noise_index(node, noise_array, graph, value_cache);
-        value_cache.insert(connection, value);
-        value
-    };
+    fn input_value(
+        &mut self,
+        node: &Node<TerrainNodeData>,
+        input_name: &str,
+    ) -> Result<TerrainValueType, TerrainGraphError> {
+        let graph = self.graph;
+        let Ok(input_id) = node.get_input(input_name) else {
+            return Err(TerrainGraphError::at_node(
+                node,
+                format!("Input \"{input_name}\" is missing!"),
+            ));
+        };
 
-    match node.user_data.template {
-        TerrainNodeTemplate::Output => get_input_value("A"),
-        TerrainNodeTemplate::SimplexNoise => {
-            let noise_index = noise_array.len();
-            noise_array.push(TerrainNoiseType::Simplex);
-            TerrainValueType::NoiseF64x2 { noise_index }
-        }
-        TerrainNodeTemplate::NoiseAdd => {
-            let a_index = get_input_value("A").get_noise_index();
-            let b_index = get_input_value("B").get_noise_index();
-            let noise_index = noise_array.len();
-            noise_array.push(TerrainNoiseType::Add { a_index, b_index });
-            TerrainValueType::NoiseF64x2 { noise_index }
+        if let Some(value) = self.bound_inputs.get(&input_id) {
+            return Ok(*value);
         }
-        TerrainNodeTemplate::PowF64 => {
-            let a_value = get_input_value("A").get_f64_value();
-            let b_value = get_input_value("B").get_f64_value();
-            TerrainValueType::F64 {
-                value: a_value.powf(b_value),
+
+        let Some(connection) = graph.connection(input_id) else {
+            if graph[input_id].typ == TerrainDataType::NoiseF64x2 {
+                return Err(TerrainGraphError::at_node(
+                    node,
+                    format!("Input \"{input_name}\" is not connected!"),
+                ));
             }
+            return Ok(graph[input_id].value);
+        };
+
+        self.output_value(connection)
+    }
+
+    fn output_value(&mut self, output_id: OutputId) -> Result<TerrainValueType, TerrainGraphError> {
+        let graph = self.graph;
+        if let Some(cached_value) = self.value_cache.get(&output_id) {
+            return Ok(*cached_value);
         }
-        TerrainNodeTemplate::Constant => {
-            let a_value = get_input_value("A").get_f64_value();
-            let noise_index = noise_array.len();
-            noise_array.push(TerrainNoiseType::Constant { value: a_value });
-            TerrainValueType::NoiseF64x2 { noise_index }
-        }
-        TerrainNodeTemplate::Multiply => {
-            let a_index = get_input_value("A").get_noise_index();
-            let b_index = get_input_value("B").get_noise_index();
-            let noise_index = noise_array.len();
-            noise_array.push(TerrainNoiseType::Multiply { a_index, b_index });
-            TerrainValueType::NoiseF64x2 { noise_index }
-        }
-        TerrainNodeTemplate::Max => {
-            let a_index = get_input_value("A").get_noise_index();
-            let b_index = get_input_value("B").get_noise_index();
-            let noise_index = noise_array.len();
-            noise_array.push(TerrainNoiseType::Max { a_index, b_index });
-            TerrainValueType::NoiseF64x2 { noise_index }
+
+        let node = &graph[graph[output_id].node];
+        if !self.visiting.insert(node.id) {
+            return Err(TerrainGraphError::at_node(node, "Node is part of a cycle!"));
         }
-        TerrainNodeTemplate::SmoothStep => {
-            let noise_index = get_input_value("noise").get_noise_index();
-            let steps = get_input_value("steps").get_f64_value();
-            let smoothness = get_input_value("smoothness").get_f64_value();
-            let index = noise_array.len();
-            noise_array.push(TerrainNoiseType::SmoothStep {
-                noise_index,
-                steps,
-                smoothness,
-            });
-            TerrainValueType::NoiseF64x2 { noise_index: index }
+        let value = self.node_value(node, output_id);
+        self.visiting.remove(&node.id);
+
+        let value = value?;
+        self.value_cache.insert(output_id, value);
+        Ok(value)
+    }
+
+    /// Walks the subgraph of a node and returns the values of all its
+    /// outputs. The noise types inside it are added to this walker, so the
+    /// subgraph is flattened into the terrain noise.
+    fn subgraph_values(
+        &mut self,
+        node: &Node<TerrainNodeData>,
+        index: usize,
+    ) -> Result<HashMap<OutputId, TerrainValueType>, TerrainGraphError> {
+        let library = self.library;
+        let Some(subgraph) = library.get(index) else {
+            return Err(TerrainGraphError::at_node(
+                node,
+                format!("Subgraph {index} is missing from the library!"),
+            ));
+        };
+        if self.depth >= MAX_SUBGRAPH_DEPTH {
+            return Err(TerrainGraphError::at_node(
+                node,
+                "Subgraphs are nested too deep, does one contain itself?",
+            ));
         }
-        TerrainNodeTemplate::ScalePoint => {
-            let noise_index = get_input_value("noise").get_noise_index();
-            let scale = get_input_value("scale").get_f64_value();
-            let index = noise_array.len();
-            noise_array.push(TerrainNoiseType::ScalePoint { noise_index, scale });
-            TerrainValueType::NoiseF64x2 { noise_index: index }
+
+        let mut bound_inputs = HashMap::new();
+        for port in &subgraph.inputs {
+            let value = self.input_value(node, &port.name)?;
+            for target in &port.targets {
+                bound_inputs.insert(*target, value);
+            }
         }
-        TerrainNodeTemplate::GFT => {
-            let noise_index = get_input_value("noise").get_noise_index();
-            let octaves = get_input_value("octaves").get_i64_value();
-            let frequency = get_input_value("frequency").get_f64_value();
-            let lacunarity = get_input_value("lacunarity").get_f64_value();
-            let persistence = get_input_value("persistence").get_f64_value();
-            let gradient = get_input_value("gradient").get_f64_value();
-            let amplitude = get_input_value("amplitude").get_f64_value();
-            let index = noise_array.len();
-            noise_array.push(TerrainNoiseType::GFT {
-                noise_index,
-                octaves: octaves as usize,
-                frequency,
-                lacunarity,
-                persistence,
-                gradient,
-                amplitude,
-            });
-            TerrainValueType::NoiseF64x2 { noise_index: index }
+
+        let mut output_ids = Vec::new();
+        for port in &subgraph.outputs {
+            let Ok(output_id) = node.get_output(&port.name) else {
+                return Err(TerrainGraphError::at_node(
+                    node,
+                    format!("Output \"{}\" is missing!", port.name),
+                ));
+            };
+            output_ids.push((output_id, port.source));
         }
-        TerrainNodeTemplate::VoxelSize => TerrainValueType::F64 {
-            value: VOXEL_SIZE as f64,
-        },
+
+        let mut subgraph_walker = TerrainGraphWalker {
+            graph: &subgraph.graph,
+            library,
+            bound_inputs,
+            depth: self.depth + 1,
+            noise_types: std::mem::take(&mut self.noise_types),
+            value_cache: HashMap::new(),
+            visiting: HashSet::new(),
+        };
+        let values = output_ids
+            .into_iter()
+            .map(|(output_id, source)| {
+                subgraph_walker
+                    .output_value(source)
+                    .map(|value| (output_id, value))
+            })
+            .collect::<Result<HashMap<_, _>, _>>();
+        self.noise_types = subgraph_walker.noise_types;
+
+        // The nodes inside the subgraph are not part of the edited graph, so
+        // errors point at the subgraph node instead.
+        values.map_err(|error| TerrainGraphError::at_node(node, error))
+    }
+
+    fn typed_input<T>(
+        &mut self,
+        node: &Node<TerrainNodeData>,
+        input_name: &str,
+        expected: TerrainDataType,
+        get_value: impl Fn(&TerrainValueType) -> Option<T>,
+    ) -> Result<T, TerrainGraphError> {
+        let value = self.input_value(node, input_name)?;
+        get_value(&value).ok_or_else(|| {
+            TerrainGraphError::at_node(
+                node,
+                format!(
+                    "Input \"{input_name}\" expects {}, but got {value:?}!",
+                    expected.name()
+                ),
+            )
+        })
+    }
+
+    fn noise_input(
+        &mut self,
+        node: &Node<TerrainNodeData>,
+        input_name: &str,
+    ) -> Result<usize, TerrainGraphError> {
+        self.typed_input(
+            node,
+            input_name,
+            TerrainDataType::NoiseF64x2,
+            TerrainValueType::get_noise_index,
+        )
+    }
+
+    fn f64_input(
+        &mut self,
+        node: &Node<TerrainNodeData>,
+        input_name: &str,
+    ) -> Result<f64, TerrainGraphError> {
+        self.typed_input(
+            node,
+            input_name,
+            TerrainDataType::F64,
+            TerrainValueType::get_f64_value,
+        )
+    }
+
+    fn i64_input(
+        &mut self,
+        node: &Node<TerrainNodeData>,
+        input_name: &str,
+    ) -> Result<i64, TerrainGraphError> {
+        self.typed_input(
+            node,
+            input_name,
+            TerrainDataType::I64,
+            TerrainValueType::get_i64_value,
+        )
+    }
+
+    fn cellular_distance_input(
+        &mut self,
+        node: &Node<TerrainNodeData>,
+        input_name: &str,
+    ) -> Result<CellularDistance, TerrainGraphError> {
+        self.typed_input(
+            node,
+            input_name,
+            TerrainDataType::CellularDistance,
+            TerrainValueType::get_cellular_distance,
+        )
+    }
+
+    fn cellular_return_input(
+        &mut self,
+        node: &Node<TerrainNodeData>,
+        input_name: &str,
+    ) -> Result<CellularReturn, TerrainGraphError> {
+        self.typed_input(
+            node,
+            input_name,
+            TerrainDataType::CellularReturn,
+            TerrainValueType::get_cellular_return,
+        )
+    }
+
+    fn node_value(
+        &mut self,
+        node: &Node<TerrainNodeData>,
+        output_id: OutputId,
+    ) -> Result<TerrainValueType, TerrainGraphError> {
+        let value = match node.user_data.template {
+            TerrainNodeTemplate::Output => self.input_value(node, "A")?,
+            TerrainNodeTemplate::SimplexNoise => self.push(TerrainNoiseType::Simplex),
+            TerrainNodeTemplate::NoiseAdd => {
+                let a_index = self.noise_input(node, "A")?;
+                let b_index = self.noise_input(node, "B")?;
+                self.push(TerrainNoiseType::Add { a_index, b_index })
+            }
+            TerrainNodeTemplate::PowF64 => {
+                let a_value = self.f64_input(node, "A")?;
+                let b_value = self.f64_input(node, "B")?;
+                TerrainValueType::F64 {
+                    value: a_value.powf(b_value),
+                }
+            }
+            TerrainNodeTemplate::Constant => {
+                let a_value = self.f64_input(node, "A")?;
+                self.push(TerrainNoiseType::Constant { value: a_value })
+            }
+            TerrainNodeTemplate::Multiply => {
+                let a_index = self.noise_input(node, "A")?;
+                let b_index = self.noise_input(node, "B")?;
+                self.push(TerrainNoiseType::Multiply { a_index, b_index })
+            }
+            TerrainNodeTemplate::Max => {
+                let a_index = self.noise_input(node, "A")?;
+                let b_index = self.noise_input(node, "B")?;
+                self.push(TerrainNoiseType::Max { a_index, b_index })
+            }
+            TerrainNodeTemplate::SmoothStep => {
+                let noise_index = self.noise_input(node, "noise")?;
+                let steps = self.f64_input(node, "steps")?;
+                ensure_positive(node, "steps", steps)?;
+                let smoothness = self.f64_input(node, "smoothness")?;
+                self.push(TerrainNoiseType::SmoothStep {
+                    noise_index,
+                    steps,
+                    smoothness,
+                })
+            }
+            TerrainNodeTemplate::ScalePoint => {
+                let noise_index = self.noise_input(node, "noise")?;
+                let scale = self.f64_input(node, "scale")?;
+                self.push(TerrainNoiseType::ScalePoint { noise_index, scale })
+            }
+            TerrainNodeTemplate::GFT => {
+                let noise_index = self.noise_input(node, "noise")?;
+                let octaves = self.i64_input(node, "octaves")?;
+                let frequency = self.f64_input(node, "frequency")?;
+                let lacunarity = self.f64_input(node, "lacunarity")?;
+                let persistence = self.f64_input(node, "persistence")?;
+                let gradient = self.f64_input(node, "gradient")?;
+                let amplitude = self.f64_input(node, "amplitude")?;
+                ensure_octaves(node, octaves)?;
+                ensure_positive(node, "frequency", frequency)?;
+                ensure_positive(node, "lacunarity", lacunarity)?;
+                self.push(TerrainNoiseType::GFT {
+                    noise_index,
+                    octaves: octaves as usize,
+                    frequency,
+                    lacunarity,
+                    persistence,
+                    gradient,
+                    amplitude,
+                })
+            }
+            TerrainNodeTemplate::VoxelSize => TerrainValueType::F64 {
+                value: VOXEL_SIZE as f64,
+            },
+            TerrainNodeTemplate::Cellular => {
+                let frequency = self.f64_input(node, "frequency")?;
+                let jitter = self.f64_input(node, "jitter")?;
+                let distance_function = self.cellular_distance_input(node, "distance function")?;
+                let return_type = self.cellular_return_input(node, "return type")?;
+                ensure_positive(node, "frequency", frequency)?;
+                ensure_range(node, "jitter", jitter, jitter >= 0., "at least 0")?;
+                self.push(TerrainNoiseType::Cellular {
+                    frequency,
+                    jitter,
+                    distance_function,
+                    return_type,
+                })
+            }
+            TerrainNodeTemplate::Roughness => {
+                let frequency = self.f64_input(node, "frequency")?;
+                let amplitude = self.f64_input(node, "amplitude")?;
+                ensure_positive(node, "frequency", frequency)?;
+                self.push(TerrainNoiseType::Roughness {
+                    frequency,
+                    amplitude,
+                })
+            }
+            TerrainNodeTemplate::FractalOpenSimplex => {
+                let roughness_index = self.noise_input(node, "roughness")?;
+                let octaves = self.i64_input(node, "octaves")?;
+                let frequency = self.f64_input(node, "frequency")?;
+                let amplitude = self.f64_input(node, "amplitude")?;
+                let lacunarity = self.f64_input(node, "lacunarity")?;
+                let persistence = self.f64_input(node, "persistence")?;
+                ensure_octaves(node, octaves)?;
+                ensure_positive(node, "frequency", frequency)?;
+                ensure_positive(node, "lacunarity", lacunarity)?;
+                self.push(TerrainNoiseType::FractalOpenSimplex {
+                    roughness_index,
+                    octaves: octaves as i32,
+                    frequency,
+                    amplitude,
+                    lacunarity,
+                    persistence,
+                })
+            }
+            TerrainNodeTemplate::ShiftNScale => {
+                let noise_index = self.noise_input(node, "noise")?;
+                let shift = self.f64_input(node, "shift")?;
+                let scale = self.f64_input(node, "scale")?;
+                ensure_non_zero(node, "scale", scale)?;
+                self.push(TerrainNoiseType::ShiftNScale {
+                    noise_index,
+                    shift,
+                    scale,
+                })
+            }
+            TerrainNodeTemplate::Steepness => {
+                let noise_index = self.noise_input(node, "noise")?;
+                let sample_offset = self.f64_input(node, "sample offset")?;
+                ensure_non_zero(node, "sample offset", sample_offset)?;
+                self.push(TerrainNoiseType::Steepness {
+                    noise_index,
+                    sample_offset,
+                })
+            }
+            TerrainNodeTemplate::DomainWarp => {
+                let noise_index = self.noise_input(node, "noise")?;
+                let x_index = self.noise_input(node, "warp x")?;
+                let z_index = self.noise_input(node, "warp z")?;
+                let strength = self.f64_input(node, "strength")?;
+                self.push(TerrainNoiseType::DomainWarp {
+                    noise_index,
+                    x_index,
+                    z_index,
+                    strength,
+                })
+            }
+            TerrainNodeTemplate::SplineRemap => {
+                let noise_index = self.noise_input(node, "noise")?;
+                self.push(TerrainNoiseType::SplineRemap {
+                    noise_index,
+                    points: node.user_data.curve_points.clone(),
+                })
+            }
+            TerrainNodeTemplate::Subgraph(index) => {
+                let values = self.subgraph_values(node, index)?;
+                let Some(value) = values.get(&output_id).copied() else {
+                    return Err(TerrainGraphError::at_node(
+                        node,
+                        "An output is not part of the subgraph anymore!",
+                    ));
+                };
+                self.value_cache.extend(values);
+                value
+            }
+        };
+
+        Ok(value)
     }
 }
+
+fn ensure_octaves(node: &Node<TerrainNodeData>, octaves: i64) -> Result<(), TerrainGraphError> {
+    ensure_range(
+        node,
+        "octaves",
+        octaves as f64,
+        (1..=MAX_OCTAVES).contains(&octaves),
+        &format!("between 1 and {MAX_OCTAVES}"),
+    )
+}
+
+fn ensure_positive(
+    node: &Node<TerrainNodeData>,
+    input_name: &str,
+    value: f64,
+) -> Result<(), TerrainGraphError> {
+    ensure_range(node, input_name, value, value > 0., "greater than 0")
+}
+
+fn ensure_non_zero(
+    node: &Node<TerrainNodeData>,
+    input_name: &str,
+    value: f64,
+) -> Result<(), TerrainGraphError> {
+    ensure_range(node, input_name, value, value != 0., "not 0")
+}
+
+fn ensure_range(
+    node: &Node<TerrainNodeData>,
+    input_name: &str,
+    value: f64,
+    valid: bool,
+    expectation: &str,
+) -> Result<(), TerrainGraphError> {
+    if valid {
+        return Ok(());
+    }
+
+    Err(TerrainGraphError::at_node(
+        node,
+        format!("Input \"{input_name}\" has to be {expectation}, but is {value}!"),
+    ))
+}
@@ -1,21 +1,25 @@
-use bevy_inspector_egui::egui;
+use bevy_inspector_egui::egui::{self, DragValue};
 use egui_node_editor::{Graph, NodeDataTrait, NodeId, NodeResponse};
 use serde::{Deserialize, Serialize};
 
 use crate::debug_tools::terrain_node_editor::{
     terrain_data_type::TerrainDataType, terrain_node_template::TerrainNodeTemplate,
-    terrain_response::TerrainResponse, terrain_value_type::TerrainValueType,
+    terrain_response::TerrainResponse, terrain_subgraph::TerrainSubgraphLibrary,
+    terrain_value_type::TerrainValueType,
 };
 
 #[derive(Serialize, Deserialize)]
 pub struct TerrainNodeData {
     pub template: TerrainNodeTemplate,
+    /// Control points of a spline remap node, as `[input, output]`.
+    #[serde(default)]
+    pub curve_points: Vec<[f64; 2]>,
 }
 
 impl NodeDataTrait for TerrainNodeData {
     type Response = TerrainResponse;
 
-    type UserState = ();
+    type UserState = TerrainSubgraphLibrary;
 
     type DataType = TerrainDataType;
 
@@ -23,14 +27,51 @@ impl NodeDataTrait for TerrainNodeData {
 
     fn bottom_ui(
         &self,
-        _ui: &mut egui::Ui,
-        _node_id: NodeId,
+        ui: &mut egui::Ui,
+        node_id: NodeId,
         _graph: &Graph<Self, Self::DataType, Self::ValueType>,
         _user_state: &mut Self::UserState,
     ) -> Vec<NodeResponse<Self::Response, Self>>
     where
         Self::Response: egui_node_editor::UserResponseTrait,
     {
-        vec![]
+        if self.template != TerrainNodeTemplate::SplineRemap {
+            return vec![];
+        }
+
+        let mut points = self.curve_points.clone();
+        let mut changed = false;
+        let mut removed_index = None;
+
+        ui.label("control points");
+        for (index, point) in points.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                changed |= ui.add(DragValue::new(&mut point[0]).speed(0.01)).changed();
+                changed |= ui.add(DragValue::new(&mut point[1]).speed(0.01)).changed();
+                if ui.button("-").clicked() {
+                    removed_index = Some(index);
+                }
+            });
+        }
+
+        if let Some(index) = removed_index {
+            points.remove(index);
+            changed = true;
+        }
+
+        if ui.button("Add point").clicked() {
+            let last = points.last().copied().unwrap_or_default();
+            points.push([last[0] + 1., last[1]]);
+            changed = true;
+        }
+
+        if !changed {
+            return vec![];
+        }
+
+        vec![NodeResponse::User(TerrainResponse::SetCurvePoints {
+            node_id,
+            points,
+        })]
     }
 }
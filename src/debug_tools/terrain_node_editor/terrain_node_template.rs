@@ -6,11 +6,19 @@ use serde::{Deserialize, Serialize};
 use crate::{
     debug_tools::terrain_node_editor::{
         terrain_data_type::TerrainDataType, terrain_node_data::TerrainNodeData,
-        terrain_value_type::TerrainValueType,
+        terrain_subgraph::TerrainSubgraphLibrary, terrain_value_type::TerrainValueType,
     },
-    world_generation::chunk_generation::noise::gradient_fractal_noise::{
-        DEFAULT_AMPLITUDE, DEFAULT_FREQUENCY, DEFAULT_GRADIENT, DEFAULT_LACUNARITY,
-        DEFAULT_OCTAVE_COUNT, DEFAULT_PERSISTENCE,
+    world_generation::chunk_generation::noise::{
+        cellular_noise::Cellular,
+        domain_warp,
+        gradient_fractal_noise::{
+            DEFAULT_AMPLITUDE, DEFAULT_FREQUENCY, DEFAULT_GRADIENT, DEFAULT_LACUNARITY,
+            DEFAULT_OCTAVE_COUNT, DEFAULT_PERSISTENCE,
+        },
+        roughness::Roughness,
+        shift_n_scale::ShiftNScale,
+        spline_remap,
+        steepness::Steepness,
     },
 };
 
@@ -27,6 +35,15 @@ pub enum TerrainNodeTemplate {
     Max,
     PowF64,
     VoxelSize,
+    Cellular,
+    Roughness,
+    FractalOpenSimplex,
+    ShiftNScale,
+    Steepness,
+    DomainWarp,
+    SplineRemap,
+    /// A subgraph from the library, by its index.
+    Subgraph(usize),
 }
 
 pub type TerrainGraph = Graph<TerrainNodeData, TerrainDataType, TerrainValueType>;
@@ -38,11 +55,11 @@ impl NodeTemplateTrait for TerrainNodeTemplate {
 
     type ValueType = TerrainValueType;
 
-    type UserState = ();
+    type UserState = TerrainSubgraphLibrary;
 
     type CategoryType = &'static str;
 
-    fn node_finder_label(&self, _: &mut Self::UserState) -> std::borrow::Cow<str> {
+    fn node_finder_label(&self, user_state: &mut Self::UserState) -> std::borrow::Cow<str> {
         Cow::Borrowed(match self {
             TerrainNodeTemplate::Output => "Output",
             TerrainNodeTemplate::SimplexNoise => "Simplex Noise",
@@ -55,23 +72,44 @@ impl NodeTemplateTrait for TerrainNodeTemplate {
             TerrainNodeTemplate::GFT => "Gradient Fractal Noise",
             TerrainNodeTemplate::Max => "Max",
             TerrainNodeTemplate::VoxelSize => "Voxel Size",
+            TerrainNodeTemplate::Cellular => "Cellular Noise",
+            TerrainNodeTemplate::Roughness => "Roughness Noise",
+            TerrainNodeTemplate::FractalOpenSimplex => "Fractal Open Simplex",
+            TerrainNodeTemplate::ShiftNScale => "Shift and Scale",
+            TerrainNodeTemplate::Steepness => "Steepness",
+            TerrainNodeTemplate::DomainWarp => "Domain Warp",
+            TerrainNodeTemplate::SplineRemap => "Spline Remap",
+            TerrainNodeTemplate::Subgraph(index) => {
+                return Cow::Owned(match user_state.get(*index) {
+                    Some(subgraph) => subgraph.name.clone(),
+                    None => format!("Missing Subgraph {index}"),
+                });
+            }
         })
     }
 
     fn node_finder_categories(&self, _user_state: &mut Self::UserState) -> Vec<&'static str> {
         match self {
             TerrainNodeTemplate::Output => vec![],
-            TerrainNodeTemplate::SimplexNoise => vec!["Noise Functions"],
+            TerrainNodeTemplate::SimplexNoise
+            | TerrainNodeTemplate::Cellular
+            | TerrainNodeTemplate::Roughness
+            | TerrainNodeTemplate::FractalOpenSimplex => vec!["Noise Functions"],
             TerrainNodeTemplate::NoiseAdd
             | TerrainNodeTemplate::Constant
             | TerrainNodeTemplate::Multiply
             | TerrainNodeTemplate::SmoothStep
             | TerrainNodeTemplate::ScalePoint
             | TerrainNodeTemplate::GFT
-            | TerrainNodeTemplate::Max => {
+            | TerrainNodeTemplate::Max
+            | TerrainNodeTemplate::ShiftNScale
+            | TerrainNodeTemplate::Steepness
+            | TerrainNodeTemplate::DomainWarp
+            | TerrainNodeTemplate::SplineRemap => {
                 vec!["Noise Calculations"]
             }
             TerrainNodeTemplate::PowF64 | TerrainNodeTemplate::VoxelSize => vec!["F64"],
+            TerrainNodeTemplate::Subgraph(_) => vec!["Subgraphs"],
         }
     }
 
@@ -80,13 +118,21 @@ impl NodeTemplateTrait for TerrainNodeTemplate {
     }
 
     fn user_data(&self, _: &mut Self::UserState) -> Self::NodeData {
-        TerrainNodeData { template: *self }
+        let curve_points = match self {
+            TerrainNodeTemplate::SplineRemap => spline_remap::DEFAULT_POINTS.to_vec(),
+            _ => Vec::new(),
+        };
+
+        TerrainNodeData {
+            template: *self,
+            curve_points,
+        }
     }
 
     fn build_node(
         &self,
         graph: &mut egui_node_editor::Graph<Self::NodeData, Self::DataType, Self::ValueType>,
-        _user_state: &mut Self::UserState,
+        user_state: &mut Self::UserState,
         node_id: egui_node_editor::NodeId,
     ) {
         let input_noise = |graph: &mut TerrainGraph, name: &str| {
@@ -123,6 +169,30 @@ impl NodeTemplateTrait for TerrainNodeTemplate {
         };
         let _input_i64 =
             |graph: &mut TerrainGraph, name: &str| input_i64_with_default(graph, name, 0);
+        let input_cellular_distance = |graph: &mut TerrainGraph, name: &str| {
+            graph.add_input_param(
+                node_id,
+                name.to_string(),
+                TerrainDataType::CellularDistance,
+                TerrainValueType::CellularDistance {
+                    value: Cellular::DEFAULT_DISTANCE_FUNCTION,
+                },
+                InputParamKind::ConstantOnly,
+                true,
+            );
+        };
+        let input_cellular_return = |graph: &mut TerrainGraph, name: &str| {
+            graph.add_input_param(
+                node_id,
+                name.to_string(),
+                TerrainDataType::CellularReturn,
+                TerrainValueType::CellularReturn {
+                    value: Cellular::DEFAULT_RETURN_TYPE,
+                },
+                InputParamKind::ConstantOnly,
+                true,
+            );
+        };
 
         let output_noise = |graph: &mut TerrainGraph, name: &str| {
             graph.add_output_param(node_id, name.to_string(), TerrainDataType::NoiseF64x2);
@@ -187,6 +257,54 @@ impl NodeTemplateTrait for TerrainNodeTemplate {
                 output_noise(graph, "out");
             }
             TerrainNodeTemplate::VoxelSize => output_f64(graph, "out"),
+            TerrainNodeTemplate::Cellular => {
+                input_f64_with_default(graph, "frequency", Cellular::DEFAULT_FREQUENCY);
+                input_f64_with_default(graph, "jitter", Cellular::DEFAULT_JITTER);
+                input_cellular_distance(graph, "distance function");
+                input_cellular_return(graph, "return type");
+                output_noise(graph, "out");
+            }
+            TerrainNodeTemplate::Roughness => {
+                input_f64_with_default(graph, "frequency", Roughness::DEFAULT_FREQUENCY);
+                input_f64_with_default(graph, "amplitude", Roughness::DEFAULT_AMPLITUDE);
+                output_noise(graph, "out");
+            }
+            TerrainNodeTemplate::FractalOpenSimplex => {
+                input_noise(graph, "roughness");
+                input_i64_with_default(graph, "octaves", DEFAULT_OCTAVE_COUNT as i64);
+                input_f64_with_default(graph, "frequency", DEFAULT_FREQUENCY);
+                input_f64_with_default(graph, "amplitude", DEFAULT_AMPLITUDE);
+                input_f64_with_default(graph, "lacunarity", DEFAULT_LACUNARITY);
+                input_f64_with_default(graph, "persistence", DEFAULT_PERSISTENCE);
+                output_noise(graph, "out");
+            }
+            TerrainNodeTemplate::ShiftNScale => {
+                input_noise(graph, "noise");
+                input_f64_with_default(graph, "shift", ShiftNScale::<()>::DEFAULT_SHIFT);
+                input_f64_with_default(graph, "scale", ShiftNScale::<()>::DEFAULT_SCALE);
+                output_noise(graph, "out");
+            }
+            TerrainNodeTemplate::Steepness => {
+                input_noise(graph, "noise");
+                input_f64_with_default(graph, "sample offset", Steepness::<()>::DEFAULT_OFFSET);
+                output_noise(graph, "out");
+            }
+            TerrainNodeTemplate::DomainWarp => {
+                input_noise(graph, "noise");
+                input_noise(graph, "warp x");
+                input_noise(graph, "warp z");
+                input_f64_with_default(graph, "strength", domain_warp::DEFAULT_STRENGTH);
+                output_noise(graph, "out");
+            }
+            TerrainNodeTemplate::SplineRemap => {
+                input_noise(graph, "noise");
+                output_noise(graph, "out");
+            }
+            TerrainNodeTemplate::Subgraph(index) => {
+                if let Some(subgraph) = user_state.get(*index) {
+                    subgraph.build_node(graph, node_id);
+                }
+            }
         }
     }
 }
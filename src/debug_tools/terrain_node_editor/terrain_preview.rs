@@ -0,0 +1,236 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::egui::{
+    self, Color32, ColorImage, DragValue, Sense, TextureHandle, TextureOptions,
+};
+use noise::NoiseFn;
+
+use crate::{
+    debug_tools::terrain_node_editor::terrain_graph_resource::TerrainGraphResource,
+    world_generation::generation_options::GenerationOptions,
+};
+
+const PREVIEW_RESOLUTION: usize = 256;
+const MIN_BLOCKS_PER_PIXEL: f64 = 0.125;
+const MAX_BLOCKS_PER_PIXEL: f64 = 512.;
+
+/// Heights mapped from the lowest to the highest sampled height.
+const COLOR_RAMP: [(f32, Color32); 5] = [
+    (0., Color32::from_rgb(40, 70, 140)),
+    (0.25, Color32::from_rgb(70, 140, 60)),
+    (0.55, Color32::from_rgb(130, 110, 70)),
+    (0.8, Color32::from_rgb(130, 130, 130)),
+    (1., Color32::from_rgb(245, 245, 250)),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+struct PreviewView {
+    center: [f64; 2],
+    blocks_per_pixel: f64,
+    seed: u64,
+}
+
+/// Renders the unsaved terrain graph as a shaded heightmap.
+#[derive(Resource)]
+pub struct TerrainPreview {
+    view: PreviewView,
+    texture: Option<TextureHandle>,
+    rendered_view: Option<PreviewView>,
+    rendered_noise: Option<String>,
+    height_range: [f64; 2],
+    error: Option<String>,
+}
+
+impl Default for TerrainPreview {
+    fn default() -> Self {
+        Self {
+            view: PreviewView {
+                center: [0., 0.],
+                blocks_per_pixel: 16.,
+                seed: 0,
+            },
+            texture: None,
+            rendered_view: None,
+            rendered_noise: None,
+            height_range: [0., 0.],
+            error: None,
+        }
+    }
+}
+
+impl TerrainPreview {
+    pub fn seed(&self) -> u64 {
+        self.view.seed
+    }
+
+    pub fn draw(&mut self, ui: &mut egui::Ui, terrain_graph: &TerrainGraphResource) {
+        ui.horizontal(|ui| {
+            ui.label("Seed");
+            ui.add(DragValue::new(&mut self.view.seed));
+            ui.label("Blocks per pixel");
+            ui.add(
+                DragValue::new(&mut self.view.blocks_per_pixel)
+                    .speed(0.1)
+                    .range(MIN_BLOCKS_PER_PIXEL..=MAX_BLOCKS_PER_PIXEL),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.label("Center");
+            ui.add(DragValue::new(&mut self.view.center[0]).prefix("x: "));
+            ui.add(DragValue::new(&mut self.view.center[1]).prefix("z: "));
+            if ui.button("Reset").clicked() {
+                self.view = Self::default().view;
+            }
+        });
+
+        self.update_texture(ui.ctx(), terrain_graph);
+
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::LIGHT_RED, error);
+        }
+
+        let Some(texture) = &self.texture else {
+            return;
+        };
+
+        ui.label(format!(
+            "Height: {:.0} to {:.0}",
+            self.height_range[0], self.height_range[1]
+        ));
+
+        let size = ui.available_width().min(ui.available_height()).max(1.);
+        let (rect, response) =
+            ui.allocate_exact_size(egui::vec2(size, size), Sense::click_and_drag());
+        ui.painter().image(
+            texture.id(),
+            rect,
+            egui::Rect::from_min_max(egui::pos2(0., 0.), egui::pos2(1., 1.)),
+            Color32::WHITE,
+        );
+
+        let blocks_per_point = self.view.blocks_per_pixel * PREVIEW_RESOLUTION as f64 / size as f64;
+
+        if response.dragged() {
+            let delta = response.drag_delta();
+            self.view.center[0] -= delta.x as f64 * blocks_per_point;
+            self.view.center[1] -= delta.y as f64 * blocks_per_point;
+        }
+
+        if response.hovered() {
+            let scroll = ui.input(|input| input.smooth_scroll_delta.y);
+            if scroll != 0. {
+                self.view.blocks_per_pixel = (self.view.blocks_per_pixel
+                    * (-scroll as f64 * 0.002).exp())
+                .clamp(MIN_BLOCKS_PER_PIXEL, MAX_BLOCKS_PER_PIXEL);
+            }
+        }
+    }
+
+    /// Re-renders the heightmap if the graph or the view changed since the
+    /// last render.
+    fn update_texture(&mut self, ctx: &egui::Context, terrain_graph: &TerrainGraphResource) {
+        let terrain_noise = match terrain_graph.to_terrain_noise() {
+            Ok(terrain_noise) => terrain_noise,
+            Err(error) => {
+                self.error = Some(error.to_string());
+                return;
+            }
+        };
+
+        let serialized_noise = ron::to_string(&terrain_noise).ok();
+        if self.rendered_view == Some(self.view)
+            && serialized_noise.is_some()
+            && self.rendered_noise == serialized_noise
+        {
+            return;
+        }
+
+        let generation_options =
+            match GenerationOptions::from_terrain_noise(self.view.seed, terrain_noise) {
+                Ok(generation_options) => generation_options,
+                Err(error) => {
+                    self.error = Some(error.to_string());
+                    return;
+                }
+            };
+        let (image, height_range) =
+            render_heightmap(&generation_options.get_terrain_noise(), self.view);
+
+        match &mut self.texture {
+            Some(texture) => texture.set(image, TextureOptions::NEAREST),
+            None => {
+                self.texture =
+                    Some(ctx.load_texture("terrain_preview", image, TextureOptions::NEAREST))
+            }
+        }
+
+        self.height_range = height_range;
+        self.rendered_view = Some(self.view);
+        self.rendered_noise = serialized_noise;
+        self.error = None;
+    }
+}
+
+fn render_heightmap(noise: &impl NoiseFn<f64, 2>, view: PreviewView) -> (ColorImage, [f64; 2]) {
+    let half_size = PREVIEW_RESOLUTION as f64 / 2.;
+
+    let mut heights = Vec::with_capacity(PREVIEW_RESOLUTION * PREVIEW_RESOLUTION);
+    for z in 0..PREVIEW_RESOLUTION {
+        for x in 0..PREVIEW_RESOLUTION {
+            heights.push(noise.get([
+                view.center[0] + (x as f64 - half_size) * view.blocks_per_pixel,
+                view.center[1] + (z as f64 - half_size) * view.blocks_per_pixel,
+            ]));
+        }
+    }
+
+    let min = heights.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = heights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = (max - min).max(f64::EPSILON);
+
+    let height_at = |x: usize, z: usize| {
+        heights[z.min(PREVIEW_RESOLUTION - 1) * PREVIEW_RESOLUTION + x.min(PREVIEW_RESOLUTION - 1)]
+    };
+
+    let light = Vec3::new(-1., 1.5, -1.).normalize();
+
+    let mut pixels = Vec::with_capacity(heights.len());
+    for z in 0..PREVIEW_RESOLUTION {
+        for x in 0..PREVIEW_RESOLUTION {
+            let height = height_at(x, z);
+
+            let slope_x = (height_at(x + 1, z) - height_at(x.saturating_sub(1), z))
+                / (2. * view.blocks_per_pixel);
+            let slope_z = (height_at(x, z + 1) - height_at(x, z.saturating_sub(1)))
+                / (2. * view.blocks_per_pixel);
+            let normal = Vec3::new(-slope_x as f32, 1., -slope_z as f32).normalize();
+            let shade = normal.dot(light).clamp(0.25, 1.);
+
+            let color = sample_color_ramp(((height - min) / range) as f32);
+            pixels.push(Color32::from_rgb(
+                (color.r() as f32 * shade) as u8,
+                (color.g() as f32 * shade) as u8,
+                (color.b() as f32 * shade) as u8,
+            ));
+        }
+    }
+
+    (
+        ColorImage {
+            size: [PREVIEW_RESOLUTION, PREVIEW_RESOLUTION],
+            pixels,
+        },
+        [min, max],
+    )
+}
+
+fn sample_color_ramp(value: f32) -> Color32 {
+    for pair in COLOR_RAMP.windows(2) {
+        let (start, start_color) = pair[0];
+        let (end, end_color) = pair[1];
+        if value <= end {
+            return start_color.lerp_to_gamma(end_color, (value - start) / (end - start));
+        }
+    }
+
+    COLOR_RAMP[COLOR_RAMP.len() - 1].1
+}
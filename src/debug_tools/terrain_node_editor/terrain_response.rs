@@ -1,6 +1,11 @@
-use egui_node_editor::UserResponseTrait;
+use egui_node_editor::{NodeId, UserResponseTrait};
 
 #[derive(Debug, Clone)]
-pub enum TerrainResponse {}
+pub enum TerrainResponse {
+    SetCurvePoints {
+        node_id: NodeId,
+        points: Vec<[f64; 2]>,
+    },
+}
 
 impl UserResponseTrait for TerrainResponse {}
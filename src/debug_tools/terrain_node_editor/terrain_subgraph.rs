@@ -0,0 +1,299 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::ErrorKind,
+};
+
+use egui_node_editor::{InputId, InputParamKind, NodeId, OutputId};
+use itertools::Itertools;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    debug_tools::terrain_node_editor::{
+        terrain_data_type::TerrainDataType,
+        terrain_graph_error::TerrainGraphError,
+        terrain_node_template::{TerrainGraph, TerrainNodeTemplate},
+        terrain_value_type::TerrainValueType,
+    },
+    world_generation::chunk_generation::noise::cellular_noise::Cellular,
+};
+
+const TERRAIN_SUBGRAPH_LIBRARY_FILE_PATH: &str = "assets/terrain_subgraphs.ron";
+
+#[derive(Serialize, Deserialize)]
+pub struct SubgraphInput {
+    pub name: String,
+    pub typ: TerrainDataType,
+    /// The inputs inside the subgraph that receive the value of this port.
+    pub targets: Vec<InputId>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SubgraphOutput {
+    pub name: String,
+    pub typ: TerrainDataType,
+    /// The output inside the subgraph this port passes on.
+    pub source: OutputId,
+}
+
+/// A named group of nodes that is used like a single node in the terrain
+/// graph. Its ports are the connections that crossed the border of the
+/// selection it was collapsed from.
+#[derive(Serialize, Deserialize)]
+pub struct TerrainSubgraph {
+    pub name: String,
+    pub graph: TerrainGraph,
+    pub inputs: Vec<SubgraphInput>,
+    pub outputs: Vec<SubgraphOutput>,
+}
+
+/// The result of collapsing nodes, with the outer connections the new
+/// subgraph node has to take over.
+pub struct CollapsedNodes {
+    pub subgraph: TerrainSubgraph,
+    /// The outer output connected to each input port.
+    pub input_sources: Vec<OutputId>,
+    /// The outer inputs connected to each output port.
+    pub output_targets: Vec<Vec<InputId>>,
+}
+
+impl TerrainSubgraph {
+    /// Moves the nodes out of the graph into a new subgraph.
+    pub fn collapse(
+        graph: &mut TerrainGraph,
+        name: String,
+        node_ids: &[NodeId],
+    ) -> Result<CollapsedNodes, TerrainGraphError> {
+        if name.trim().is_empty() {
+            return Err(TerrainGraphError::new("The subgraph needs a name!"));
+        }
+        if node_ids.is_empty() {
+            return Err(TerrainGraphError::new("No nodes are selected!"));
+        }
+        if let Some(node_id) = node_ids
+            .iter()
+            .find(|node_id| graph[**node_id].user_data.template == TerrainNodeTemplate::Output)
+        {
+            return Err(TerrainGraphError::at_node(
+                &graph[*node_id],
+                "The output node can't be part of a subgraph!",
+            ));
+        }
+
+        let selected = node_ids.iter().copied().collect::<HashSet<_>>();
+        let connections = graph
+            .iter_connections()
+            .filter(|(input, output)| {
+                selected.contains(&graph[*input].node) || selected.contains(&graph[*output].node)
+            })
+            .collect_vec();
+
+        let mut subgraph_graph = TerrainGraph::new();
+        let mut input_ids = HashMap::new();
+        let mut output_ids = HashMap::new();
+
+        for &node_id in node_ids {
+            let node = &graph[node_id];
+            let inputs = node
+                .inputs
+                .iter()
+                .map(|(name, id)| {
+                    let param = &graph[*id];
+                    (name.clone(), *id, param.typ, param.value, param.kind)
+                })
+                .collect_vec();
+            let outputs = node
+                .outputs
+                .iter()
+                .map(|(name, id)| (name.clone(), *id, graph[*id].typ))
+                .collect_vec();
+
+            let (node, _) = graph.remove_node(node_id);
+            subgraph_graph.add_node(node.label, node.user_data, |inner, node_id| {
+                for (name, outer_id, typ, value, kind) in inputs {
+                    let inner_id = inner.add_input_param(node_id, name, typ, value, kind, true);
+                    input_ids.insert(outer_id, inner_id);
+                }
+                for (name, outer_id, typ) in outputs {
+                    let inner_id = inner.add_output_param(node_id, name, typ);
+                    output_ids.insert(outer_id, inner_id);
+                }
+            });
+        }
+
+        let mut inputs: Vec<SubgraphInput> = Vec::new();
+        let mut input_sources = Vec::new();
+        let mut outputs: Vec<SubgraphOutput> = Vec::new();
+        let mut output_targets: Vec<Vec<InputId>> = Vec::new();
+
+        for (input, output) in connections {
+            match (input_ids.get(&input), output_ids.get(&output)) {
+                (Some(&inner_input), Some(&inner_output)) => {
+                    subgraph_graph.add_connection(inner_output, inner_input);
+                }
+                (Some(&inner_input), None) => {
+                    let index = match input_sources.iter().position(|source| *source == output) {
+                        Some(index) => index,
+                        None => {
+                            let base_name = input_port_name(&subgraph_graph, inner_input);
+                            inputs.push(SubgraphInput {
+                                name: unique_name(inputs.iter().map(|port| &port.name), base_name),
+                                typ: subgraph_graph[inner_input].typ,
+                                targets: Vec::new(),
+                            });
+                            input_sources.push(output);
+                            inputs.len() - 1
+                        }
+                    };
+                    inputs[index].targets.push(inner_input);
+                }
+                (None, Some(&inner_output)) => {
+                    let index = match outputs.iter().position(|port| port.source == inner_output) {
+                        Some(index) => index,
+                        None => {
+                            let base_name = output_port_name(&subgraph_graph, inner_output);
+                            outputs.push(SubgraphOutput {
+                                name: unique_name(outputs.iter().map(|port| &port.name), base_name),
+                                typ: subgraph_graph[inner_output].typ,
+                                source: inner_output,
+                            });
+                            output_targets.push(Vec::new());
+                            outputs.len() - 1
+                        }
+                    };
+                    output_targets[index].push(input);
+                }
+                (None, None) => {}
+            }
+        }
+
+        Ok(CollapsedNodes {
+            subgraph: TerrainSubgraph {
+                name: name.trim().to_string(),
+                graph: subgraph_graph,
+                inputs,
+                outputs,
+            },
+            input_sources,
+            output_targets,
+        })
+    }
+
+    /// Adds the ports of the subgraph to a node of another graph.
+    pub fn build_node(&self, graph: &mut TerrainGraph, node_id: NodeId) {
+        for port in &self.inputs {
+            graph.add_input_param(
+                node_id,
+                port.name.clone(),
+                port.typ,
+                default_value(port.typ),
+                InputParamKind::ConnectionOrConstant,
+                true,
+            );
+        }
+
+        for port in &self.outputs {
+            graph.add_output_param(node_id, port.name.clone(), port.typ);
+        }
+    }
+}
+
+fn input_port_name(graph: &TerrainGraph, input_id: InputId) -> String {
+    let node = &graph[graph[input_id].node];
+    let param_name = node
+        .inputs
+        .iter()
+        .find(|(_, id)| *id == input_id)
+        .map_or("in", |(name, _)| name.as_str());
+    format!("{} {param_name}", node.label)
+}
+
+fn output_port_name(graph: &TerrainGraph, output_id: OutputId) -> String {
+    let node = &graph[graph[output_id].node];
+    let param_name = node
+        .outputs
+        .iter()
+        .find(|(_, id)| *id == output_id)
+        .map_or("out", |(name, _)| name.as_str());
+    format!("{} {param_name}", node.label)
+}
+
+fn unique_name<'a>(
+    existing: impl Iterator<Item = &'a String> + Clone,
+    base_name: String,
+) -> String {
+    let is_taken = |name: &str| existing.clone().any(|existing| existing == name);
+    if !is_taken(&base_name) {
+        return base_name;
+    }
+
+    (2..)
+        .map(|number| format!("{base_name} {number}"))
+        .find(|name| !is_taken(name))
+        .unwrap()
+}
+
+fn default_value(typ: TerrainDataType) -> TerrainValueType {
+    match typ {
+        TerrainDataType::NoiseF64x2 => TerrainValueType::NoiseF64x2 { noise_index: 0 },
+        TerrainDataType::F64 => TerrainValueType::F64 { value: 0. },
+        TerrainDataType::I64 => TerrainValueType::I64 { value: 0 },
+        TerrainDataType::CellularDistance => TerrainValueType::CellularDistance {
+            value: Cellular::DEFAULT_DISTANCE_FUNCTION,
+        },
+        TerrainDataType::CellularReturn => TerrainValueType::CellularReturn {
+            value: Cellular::DEFAULT_RETURN_TYPE,
+        },
+    }
+}
+
+/// The subgraphs that can be placed from the node finder, stored next to
+/// the terrain graph so they can be shared between graphs.
+#[derive(Default, Serialize, Deserialize)]
+pub struct TerrainSubgraphLibrary {
+    subgraphs: Vec<TerrainSubgraph>,
+}
+
+impl TerrainSubgraphLibrary {
+    pub fn load() -> Result<Self, anyhow::Error> {
+        match fs::read_to_string(TERRAIN_SUBGRAPH_LIBRARY_FILE_PATH) {
+            Ok(text) => Ok(ron::from_str(&text)?),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        let text = ron::ser::to_string_pretty(self, PrettyConfig::default())?;
+        fs::write(TERRAIN_SUBGRAPH_LIBRARY_FILE_PATH, text)?;
+        Ok(())
+    }
+
+    pub fn get(&self, index: usize) -> Option<&TerrainSubgraph> {
+        self.subgraphs.get(index)
+    }
+
+    pub fn count(&self) -> usize {
+        self.subgraphs.len()
+    }
+
+    /// Adds the subgraph and returns its index. A subgraph with the same
+    /// name is replaced, so nodes placed from it pick up the new version.
+    pub fn insert(&mut self, subgraph: TerrainSubgraph) -> usize {
+        match self
+            .subgraphs
+            .iter()
+            .position(|existing| existing.name == subgraph.name)
+        {
+            Some(index) => {
+                self.subgraphs[index] = subgraph;
+                index
+            }
+            None => {
+                self.subgraphs.push(subgraph);
+                self.subgraphs.len() - 1
+            }
+        }
+    }
+}
@@ -1,11 +1,17 @@
 use std::fmt::Debug;
 
-use bevy_inspector_egui::egui::{self, DragValue};
+use bevy_inspector_egui::egui::{self, ComboBox, DragValue};
 use egui_node_editor::{NodeId, WidgetValueTrait};
 use serde::{Deserialize, Serialize};
 
-use crate::debug_tools::terrain_node_editor::{
-    terrain_node_data::TerrainNodeData, terrain_response::TerrainResponse,
+use crate::{
+    debug_tools::terrain_node_editor::{
+        terrain_node_data::TerrainNodeData, terrain_response::TerrainResponse,
+        terrain_subgraph::TerrainSubgraphLibrary,
+    },
+    world_generation::chunk_generation::noise::cellular_noise::{
+        CellularDistance, CellularReturn,
+    },
 };
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
@@ -13,27 +19,43 @@ pub enum TerrainValueType {
     NoiseF64x2 { noise_index: usize },
     F64 { value: f64 },
     I64 { value: i64 },
+    CellularDistance { value: CellularDistance },
+    CellularReturn { value: CellularReturn },
 }
 
 impl TerrainValueType {
-    pub fn get_noise_index(&self) -> usize {
+    pub fn get_noise_index(&self) -> Option<usize> {
         match self {
-            TerrainValueType::NoiseF64x2 { noise_index } => *noise_index,
-            _ => 0,
+            TerrainValueType::NoiseF64x2 { noise_index } => Some(*noise_index),
+            _ => None,
         }
     }
 
-    pub fn get_f64_value(&self) -> f64 {
+    pub fn get_f64_value(&self) -> Option<f64> {
         match self {
-            TerrainValueType::F64 { value } => *value,
-            _ => 0.0,
+            TerrainValueType::F64 { value } => Some(*value),
+            _ => None,
         }
     }
 
-    pub fn get_i64_value(&self) -> i64 {
+    pub fn get_i64_value(&self) -> Option<i64> {
         match self {
-            TerrainValueType::I64 { value } => *value,
-            _ => 0,
+            TerrainValueType::I64 { value } => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_cellular_distance(&self) -> Option<CellularDistance> {
+        match self {
+            TerrainValueType::CellularDistance { value } => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_cellular_return(&self) -> Option<CellularReturn> {
+        match self {
+            TerrainValueType::CellularReturn { value } => Some(*value),
+            _ => None,
         }
     }
 }
@@ -46,14 +68,14 @@ impl Default for TerrainValueType {
 
 impl WidgetValueTrait for TerrainValueType {
     type Response = TerrainResponse;
-    type UserState = ();
+    type UserState = TerrainSubgraphLibrary;
     type NodeData = TerrainNodeData;
     fn value_widget(
         &mut self,
         param_name: &str,
-        _node_id: NodeId,
+        node_id: NodeId,
         ui: &mut egui::Ui,
-        _user_state: &mut (),
+        _user_state: &mut TerrainSubgraphLibrary,
         _node_data: &TerrainNodeData,
     ) -> Vec<Self::Response> {
         match self {
@@ -68,6 +90,34 @@ impl WidgetValueTrait for TerrainValueType {
                 ui.label(param_name);
                 ui.add(DragValue::new(value));
             }
+            TerrainValueType::CellularDistance { value } => {
+                ui.label(param_name);
+                ComboBox::from_id_salt((node_id, param_name))
+                    .selected_text(format!("{value:?}"))
+                    .show_ui(ui, |ui| {
+                        for option in CellularDistance::ALL {
+                            ui.selectable_value(
+                                value,
+                                option,
+                                format!("{option:?}"),
+                            );
+                        }
+                    });
+            }
+            TerrainValueType::CellularReturn { value } => {
+                ui.label(param_name);
+                ComboBox::from_id_salt((node_id, param_name))
+                    .selected_text(format!("{value:?}"))
+                    .show_ui(ui, |ui| {
+                        for option in CellularReturn::ALL {
+                            ui.selectable_value(
+                                value,
+                                option,
+                                format!("{option:?}"),
+                            );
+                        }
+                    });
+            }
         }
         Vec::new()
     }
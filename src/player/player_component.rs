@@ -19,6 +19,8 @@ pub struct Player {
     pub velocity: Vec3,
     pub jumped: bool,
     pub fly: bool,
+    /// Whether the player is under water and swims.
+    pub submerged: bool,
 }
 
 #[derive(Component)]
@@ -54,6 +56,7 @@ pub(super) fn spawn_player(
             velocity: Vec3::ZERO,
             jumped: false,
             fly: true,
+            submerged: false,
         },
         ChunkLoader::default(),
         Name::new("Player"),
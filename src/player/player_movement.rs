@@ -4,7 +4,19 @@ use bevy::prelude::*;
 use bevy_panorbit_camera::PanOrbitCamera;
 use bevy_rapier3d::prelude::*;
 
-use crate::player::player_component::{Player, PlayerBody, PlayerCamera};
+use crate::{
+    player::player_component::{Player, PlayerBody, PlayerCamera},
+    world_generation::{
+        chunk_generation::{
+            VOXEL_SIZE,
+            country::{
+                country_cache::CountryCache,
+                country_cache_position::CountryPosition,
+            },
+        },
+        generation_options::GenerationOptionsResource,
+    },
+};
 
 pub(super) fn movement(
     keyboard_input: Res<ButtonInput<KeyCode>>,
@@ -26,6 +38,7 @@ pub(super) fn movement(
 
         let mut move_direction = Vec3::ZERO;
         let mut last_movement = player.velocity;
+        let swimming = player.submerged && !player.fly;
 
         if let Some(controller_output) = controller_output {
             if player.jumped && controller_output.grounded {
@@ -34,7 +47,13 @@ pub(super) fn movement(
         }
 
         last_movement.x *= 0.8;
-        last_movement.y *= if player.fly { 0.8 } else { 0.98 };
+        last_movement.y *= if player.fly {
+            0.8
+        } else if swimming {
+            0.9
+        } else {
+            0.98
+        };
         last_movement.z *= 0.8;
 
         // Directional movement
@@ -75,6 +94,8 @@ pub(super) fn movement(
 
         if player.fly {
             movement_speed *= 10.;
+        } else if swimming {
+            movement_speed *= 0.6;
         }
 
         if let Ok(player_camera) = player_camera.single() {
@@ -85,7 +106,15 @@ pub(super) fn movement(
                 .mul_vec3(move_direction.normalize_or_zero() * movement_speed);
         }
 
-        if !player.fly
+        if swimming {
+            // The water holds the player up, so they only sink slowly and
+            // swim up while space is held.
+            move_direction.y += if keyboard_input.pressed(KeyCode::Space) {
+                0.3
+            } else {
+                -0.1
+            };
+        } else if !player.fly
             && controller_output.is_some()
             && !controller_output.unwrap().grounded
         {
@@ -119,6 +148,33 @@ pub(super) fn movement(
     }
 }
 
+/// Marks players whose center lies below the sea or lake over their column.
+pub(super) fn update_submersion(
+    mut players: Query<(&mut Player, &Transform)>,
+    country_cache: Res<CountryCache>,
+    generation_options: Option<Res<GenerationOptionsResource>>,
+) {
+    let Some(generation_options) = generation_options else {
+        return;
+    };
+    let water_settings = &generation_options.0.water_settings;
+
+    for (mut player, transform) in &mut players {
+        let column =
+            (transform.translation.xz() / VOXEL_SIZE).floor().as_ivec2();
+        let water_level = country_cache
+            .cache_store
+            .lake_cache
+            .try_get_entry_no_lock(CountryPosition::from_column(column))
+            .map_or(water_settings.sea_level / VOXEL_SIZE, |lakes| {
+                lakes.water_level(column, water_settings)
+            });
+
+        player.submerged =
+            transform.translation.y < water_level.floor() * VOXEL_SIZE;
+    }
+}
+
 pub(super) fn move_body(
     player: Query<&Transform, (With<Player>, Without<PlayerBody>)>,
     mut player_body: Query<&mut Transform, (With<PlayerBody>, Without<Player>)>,
@@ -1,14 +1,22 @@
 use bevy::prelude::*;
 
+use crate::world_generation::world_preset::WorldPreset;
+
 #[derive(Resource)]
 pub struct MainMenuData {
     pub seed: String,
+    pub presets: Vec<WorldPreset>,
+    pub selected_preset: usize,
+    pub error: Option<String>,
 }
 
 impl Default for MainMenuData {
     fn default() -> Self {
         Self {
             seed: "Seed".into(),
+            presets: WorldPreset::load_all(),
+            selected_preset: 0,
+            error: None,
         }
     }
 }
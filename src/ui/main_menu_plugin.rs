@@ -1,5 +1,3 @@
-use std::hash::{DefaultHasher, Hash, Hasher};
-
 use bevy::prelude::*;
 use bevy_inspector_egui::{
     bevy_egui::{EguiContexts, EguiPrimaryContextPass},
@@ -16,7 +14,10 @@ use crate::{
     },
     world_generation::{
         generation_options::GenerationOptionsResource,
+        seed_derivation::seed_from_text,
+        terrain_noise_watcher::TerrainNoiseWatcher,
         world_generation_state::WorldGenerationState,
+        world_preset::WORLD_PRESETS_DIRECTORY,
     },
 };
 
@@ -71,22 +72,62 @@ fn render_main_menu(
     mut commands: Commands,
     mut contexts: EguiContexts,
 ) -> Result {
+    let menu_data = &mut *menu_data;
+
     egui::CentralPanel::default().show(contexts.ctx_mut()?, |ui| {
         ui.vertical_centered(|ui| {
             ui.heading("SpellHaven");
 
             ui.text_edit_singleline(&mut menu_data.seed);
-            if ui.button("Start").clicked() {
-                let mut hasher = DefaultHasher::new();
-                menu_data.seed.hash(&mut hasher);
-                let seed = hasher.finish();
-
-                info!("Seed to use: {}", seed);
-                commands.insert_resource(GenerationOptionsResource::from_seed(
-                    seed,
+
+            if let Some(selected_preset) =
+                menu_data.presets.get(menu_data.selected_preset)
+            {
+                egui::ComboBox::from_label("World Preset")
+                    .selected_text(&selected_preset.name)
+                    .show_ui(ui, |ui| {
+                        for (index, preset) in
+                            menu_data.presets.iter().enumerate()
+                        {
+                            ui.selectable_value(
+                                &mut menu_data.selected_preset,
+                                index,
+                                &preset.name,
+                            );
+                        }
+                    });
+            } else {
+                ui.label(format!(
+                    "No world presets found in {WORLD_PRESETS_DIRECTORY}."
                 ));
+            }
+
+            if let Some(error) = &menu_data.error {
+                ui.colored_label(egui::Color32::LIGHT_RED, error);
+            }
+
+            if ui.button("Start").clicked()
+                && let Some(preset) =
+                    menu_data.presets.get(menu_data.selected_preset)
+            {
+                let seed = seed_from_text(&menu_data.seed);
 
-                menu_state.set(MainMenuState::LoadingWorldGen);
+                info!("Seed to use: {}, preset: {}", seed, preset.name);
+                match GenerationOptionsResource::from_preset(seed, preset) {
+                    Ok(generation_options) => {
+                        commands.insert_resource(generation_options);
+                        commands.insert_resource(TerrainNoiseWatcher::new(
+                            preset.clone(),
+                        ));
+                        menu_state.set(MainMenuState::LoadingWorldGen);
+                    }
+                    Err(error) => {
+                        menu_data.error = Some(format!(
+                            "Could not load preset {}: {error}",
+                            preset.name
+                        ));
+                    }
+                }
             }
         });
     });
@@ -10,14 +10,20 @@ use bevy::{
     },
 };
 
-pub const ATTRIBUTE_TEXTURE_ID: MeshVertexAttribute =
-    MeshVertexAttribute::new("TextureId", 988543481, VertexFormat::Uint32);
+/// The vertices of chunk meshes, packed by `pack_vertex`. Chunk meshes
+/// have no other attributes.
+pub const ATTRIBUTE_PACKED_VERTEX: MeshVertexAttribute =
+    MeshVertexAttribute::new("PackedVertex", 988543482, VertexFormat::Uint32x2);
 
 #[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
 pub struct ArrayTextureMaterial {
     #[texture(100, dimension = "2d_array")]
     #[sampler(101)]
     pub array_texture: Handle<Image>,
+    /// How far in meters the surface is moved up and down by waves, zero
+    /// for everything but fluids.
+    #[uniform(102)]
+    pub wave_height: f32,
 }
 
 impl MaterialExtension for ArrayTextureMaterial {
@@ -29,6 +35,10 @@ impl MaterialExtension for ArrayTextureMaterial {
         "shaders/array_texture.wgsl".into()
     }
 
+    fn prepass_vertex_shader() -> ShaderRef {
+        "shaders/array_texture_prepass.wgsl".into()
+    }
+
     fn specialize(
         _pipeline: &MaterialExtensionPipeline,
         descriptor: &mut RenderPipelineDescriptor,
@@ -39,7 +49,7 @@ impl MaterialExtension for ArrayTextureMaterial {
             .0
             .attribute_ids()
             .iter()
-            .position(|id| *id == ATTRIBUTE_TEXTURE_ID.id)
+            .position(|id| *id == ATTRIBUTE_PACKED_VERTEX.id)
         {
             let layout_attribute = &layout.0.layout().attributes[index];
             descriptor.vertex.buffers[0]
@@ -7,18 +7,9 @@ pub struct AmbiantOcclusion {
 }
 
 impl AmbiantOcclusion {
-    pub fn get_colors(&self) -> [[f32; 4]; 4] {
-        let corner_1 = (self.corner_1 as f32 / 4.) + 0.25;
-        let corner_2 = (self.corner_2 as f32 / 4.) + 0.25;
-        let corner_3 = (self.corner_3 as f32 / 4.) + 0.25;
-        let corner_4 = (self.corner_4 as f32 / 4.) + 0.25;
-
-        [
-            [corner_1, corner_1, corner_1, 1.],
-            [corner_2, corner_2, corner_2, 1.],
-            [corner_3, corner_3, corner_3, 1.],
-            [corner_4, corner_4, corner_4, 1.],
-        ]
+    /// The occlusion of the corners from 0, darkest, to 3, open.
+    pub fn corners(&self) -> [u8; 4] {
+        [self.corner_1, self.corner_2, self.corner_3, self.corner_4]
     }
 
     pub fn turn_quad(&self) -> bool {
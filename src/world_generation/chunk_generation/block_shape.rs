@@ -0,0 +1,166 @@
+use bevy::math::{IVec3, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// The horizontal side a shaped block is turned towards.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum Facing {
+    Right,
+    Left,
+    Front,
+    Back,
+}
+
+impl Facing {
+    pub fn direction(self) -> IVec3 {
+        match self {
+            Facing::Right => IVec3::X,
+            Facing::Left => IVec3::NEG_X,
+            Facing::Front => IVec3::Z,
+            Facing::Back => IVec3::NEG_Z,
+        }
+    }
+}
+
+pub const HORIZONTAL_DIRECTIONS: [IVec3; 4] =
+    [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z];
+
+/// The geometry of a block inside its voxel.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BlockShape {
+    Empty,
+    /// A full cube, the only shape whose faces are merged by the mesher.
+    Cube,
+    /// The lower half of a cube.
+    Slab,
+    /// A slab with its upper half filled on the side it faces.
+    Stairs(Facing),
+    /// Two crossing quads, for plants.
+    Cross,
+    /// A post with rails to the neighbours it connects to.
+    Fence,
+    /// A thin wall to the neighbours it connects to.
+    Pane,
+    /// A liquid filling its voxel, drawn in its own pass.
+    Fluid,
+}
+
+/// An axis aligned box inside a voxel, in sixteenths of the voxel from 0
+/// to 16 on every axis, so its sides can be compared exactly.
+#[derive(Copy, Clone, Debug)]
+pub struct ShapeBox {
+    pub min: IVec3,
+    pub max: IVec3,
+}
+
+impl ShapeBox {
+    pub const SIXTEENTHS: i32 = 16;
+
+    pub fn from_sixteenths(min: [i32; 3], max: [i32; 3]) -> Self {
+        Self {
+            min: IVec3::from_array(min),
+            max: IVec3::from_array(max),
+        }
+    }
+
+    /// A box from the middle of the voxel to its side in `direction`, with
+    /// `thickness` across it and `height` up.
+    fn arm(direction: IVec3, thickness: [i32; 2], height: [i32; 2]) -> Self {
+        let along = |direction: i32| match direction.signum() {
+            1 => [8, 16],
+            -1 => [0, 8],
+            _ => thickness,
+        };
+        let [min_x, max_x] = along(direction.x);
+        let [min_z, max_z] = along(direction.z);
+
+        Self::from_sixteenths(
+            [min_x, height[0], min_z],
+            [max_x, height[1], max_z],
+        )
+    }
+
+    /// The lowest corner in voxels.
+    pub fn min_corner(&self) -> Vec3 {
+        self.min.as_vec3() / Self::SIXTEENTHS as f32
+    }
+
+    /// The size in voxels.
+    pub fn size(&self) -> Vec3 {
+        (self.max - self.min).as_vec3() / Self::SIXTEENTHS as f32
+    }
+
+    /// Whether the face in `direction` lies on the side of the voxel.
+    pub fn reaches_side(&self, direction: IVec3) -> bool {
+        if direction.max_element() > 0 {
+            self.max.dot(direction) == Self::SIXTEENTHS
+        } else {
+            self.min.dot(-direction) == 0
+        }
+    }
+}
+
+impl BlockShape {
+    /// Whether the side of the voxel in `direction` is completely covered.
+    pub fn is_full_face(self, direction: IVec3) -> bool {
+        match self {
+            BlockShape::Cube | BlockShape::Fluid => true,
+            BlockShape::Slab => direction == IVec3::NEG_Y,
+            BlockShape::Stairs(facing) => {
+                direction == IVec3::NEG_Y || direction == facing.direction()
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the shape darkens the corners of the faces next to it.
+    pub fn casts_ambient_occlusion(self) -> bool {
+        matches!(
+            self,
+            BlockShape::Cube | BlockShape::Slab | BlockShape::Stairs(_)
+        )
+    }
+
+    /// The boxes the shape is made of. `connections` are the horizontal
+    /// directions a fence or pane reaches out to.
+    pub fn boxes(self, connections: &[IVec3]) -> Vec<ShapeBox> {
+        match self {
+            BlockShape::Empty | BlockShape::Cross | BlockShape::Fluid => {
+                vec![]
+            }
+            BlockShape::Cube => {
+                vec![ShapeBox::from_sixteenths([0, 0, 0], [16, 16, 16])]
+            }
+            BlockShape::Slab => {
+                vec![ShapeBox::from_sixteenths([0, 0, 0], [16, 8, 16])]
+            }
+            BlockShape::Stairs(facing) => vec![
+                ShapeBox::from_sixteenths([0, 0, 0], [16, 8, 16]),
+                ShapeBox::arm(facing.direction(), [0, 16], [8, 16]),
+            ],
+            BlockShape::Fence => {
+                let mut boxes =
+                    vec![ShapeBox::from_sixteenths([6, 0, 6], [10, 16, 10])];
+                for direction in connections {
+                    boxes.push(ShapeBox::arm(*direction, [7, 9], [6, 9]));
+                    boxes.push(ShapeBox::arm(*direction, [7, 9], [12, 15]));
+                }
+                boxes
+            }
+            BlockShape::Pane => {
+                // A pane on its own is drawn as a cross, so it can be seen
+                // from every side.
+                let connections = if connections.is_empty() {
+                    &HORIZONTAL_DIRECTIONS[..]
+                } else {
+                    connections
+                };
+                let mut boxes =
+                    vec![ShapeBox::from_sixteenths([7, 0, 7], [9, 16, 9])];
+                for direction in connections {
+                    boxes.push(ShapeBox::arm(*direction, [7, 9], [0, 16]));
+                }
+                boxes
+            }
+        }
+    }
+}
@@ -1,5 +1,10 @@
+use bevy::math::IVec3;
 use serde::{Deserialize, Serialize};
 
+use crate::world_generation::chunk_generation::block_shape::{
+    BlockShape, Facing,
+};
+
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
 pub enum BlockType {
     Air,
@@ -8,6 +13,20 @@ pub enum BlockType {
     Log,
     Snow,
     Leaf,
+    TallGrass,
+    Flower,
+    StoneSlab,
+    StoneStairs(Facing),
+    LogFence,
+    GlassPane,
+    Lantern,
+    Water,
+    Dirt,
+    Slate,
+    Sandstone,
+    CoalOre,
+    IronOre,
+    GoldOre,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
@@ -32,21 +51,89 @@ impl BlockType {
                 BlockFace::Bottom => 7,
                 _ => 6,
             },
-            BlockType::Stone => 1,
+            BlockType::LogFence => match block_face {
+                BlockFace::Top | BlockFace::Bottom => 4,
+                _ => 3,
+            },
+            BlockType::Stone
+            | BlockType::StoneSlab
+            | BlockType::StoneStairs(_) => 1,
             BlockType::Snow => 2,
             BlockType::Leaf => 5,
+            BlockType::TallGrass => 8,
+            BlockType::Flower => 9,
+            BlockType::GlassPane => 10,
+            BlockType::Lantern => 11,
+            BlockType::Water => 12,
+            BlockType::Dirt => 7,
+            BlockType::Slate => 13,
+            BlockType::Sandstone => 14,
+            BlockType::CoalOre => 15,
+            BlockType::IronOre => 16,
+            BlockType::GoldOre => 17,
             _ => 0,
         }
     }
 
-    pub fn is_covering_for(&self, other: &BlockType) -> bool {
-        if self == other {
-            return true;
+    pub fn shape(&self) -> BlockShape {
+        match self {
+            BlockType::Air => BlockShape::Empty,
+            BlockType::TallGrass | BlockType::Flower => BlockShape::Cross,
+            BlockType::StoneSlab => BlockShape::Slab,
+            BlockType::StoneStairs(facing) => BlockShape::Stairs(*facing),
+            BlockType::LogFence => BlockShape::Fence,
+            BlockType::GlassPane => BlockShape::Pane,
+            BlockType::Water => BlockShape::Fluid,
+            _ => BlockShape::Cube,
         }
+    }
+
+    /// Whether blocks behind this one can't be seen through it.
+    pub fn is_opaque(&self) -> bool {
+        !matches!(
+            self,
+            BlockType::Air
+                | BlockType::Leaf
+                | BlockType::TallGrass
+                | BlockType::Flower
+                | BlockType::GlassPane
+                | BlockType::Water
+        )
+    }
 
+    /// Whether this block hides the face of `other` that lies against its
+    /// side in `face`.
+    pub fn is_covering_for(&self, other: &BlockType, face: IVec3) -> bool {
+        self.shape().is_full_face(face) && (self == other || self.is_opaque())
+    }
+
+    /// How many light levels this block takes away on top of the one light
+    /// loses per voxel, `None` if it stops light entirely.
+    pub fn light_filter(&self) -> Option<u8> {
+        match self {
+            BlockType::Leaf => Some(2),
+            BlockType::Water => Some(1),
+            block
+                if block.is_opaque()
+                    && block.shape().casts_ambient_occlusion() =>
+            {
+                None
+            }
+            _ => Some(0),
+        }
+    }
+
+    /// The block light level this block shines with.
+    pub fn emitted_light(&self) -> u8 {
         match self {
-            BlockType::Air | BlockType::Leaf => false,
-            _ => true,
+            BlockType::Lantern => 14,
+            _ => 0,
         }
     }
+
+    /// Whether a fence or pane reaches out to this neighbour.
+    pub fn connects_to(&self, neighbour: &BlockType) -> bool {
+        self == neighbour
+            || (neighbour.shape() == BlockShape::Cube && neighbour.is_opaque())
+    }
 }
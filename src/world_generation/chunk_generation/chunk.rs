@@ -1,7 +1,10 @@
+use std::sync::Arc;
+
 use bevy::prelude::*;
 
-use crate::world_generation::chunk_loading::{
-    chunk_tree::ChunkTreePos, lod_position::LodPosition,
+use crate::world_generation::{
+    chunk_generation::voxel_generation::ChunkColumns,
+    chunk_loading::{chunk_tree::ChunkTreePos, lod_position::LodPosition},
 };
 
 #[derive(Component)]
@@ -11,3 +14,8 @@ pub struct Chunk {
     pub generate_above: bool,
     pub chunk_height: i32,
 }
+
+/// The columns of a chunk that has more chunks to be stacked on it, handed
+/// on to the next chunk of the stack so they are only generated once.
+#[derive(Component)]
+pub struct StackColumns(pub Arc<ChunkColumns>);
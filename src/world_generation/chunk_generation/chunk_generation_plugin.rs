@@ -2,13 +2,19 @@ use bevy::prelude::*;
 
 use crate::world_generation::{
     chunk_generation::{
+        chunk_light::{
+            mark_outdated_light, queue_chunk_relight, set_relit_chunks,
+        },
         chunk_start::queue_chunk_tasks,
-        chunk_task::{ChunkTaskPool, set_generated_chunks},
+        chunk_task::{ChunkFinishBudget, ChunkTaskPool, set_generated_chunks},
         chunk_triangles::ChunkTriangles,
         country::{
             cache_generation_task::{CacheTaskPool, set_generated_caches},
             country_cache::CountryCache,
         },
+        world_voxels::{
+            WorldVoxels, forget_chunk_voxels, remember_chunk_voxels,
+        },
     },
     chunk_loading::chunk_loader_plugin::ChunkLoaderPlugin,
     generation_assets::GenerationAssets,
@@ -22,9 +28,14 @@ impl Plugin for ChunkGenerationPlugin {
         app.add_plugins(ChunkLoaderPlugin)
             .init_resource::<ChunkTriangles>()
             .init_resource::<ChunkTaskPool>()
+            .init_resource::<ChunkFinishBudget>()
             .init_resource::<CacheTaskPool>()
             .init_resource::<CountryCache>()
+            .init_resource::<WorldVoxels>()
             .register_type::<ChunkTriangles>()
+            .register_type::<ChunkFinishBudget>()
+            .add_observer(remember_chunk_voxels)
+            .add_observer(forget_chunk_voxels)
             .add_systems(
                 Update,
                 (
@@ -33,6 +44,14 @@ impl Plugin for ChunkGenerationPlugin {
                     set_generated_chunks
                         .run_if(resource_exists::<GenerationAssets>),
                     set_generated_caches,
+                    (
+                        mark_outdated_light,
+                        queue_chunk_relight,
+                        set_relit_chunks,
+                    )
+                        .chain()
+                        .after(set_generated_chunks)
+                        .run_if(resource_exists::<GenerationAssets>),
                 ),
             );
     }
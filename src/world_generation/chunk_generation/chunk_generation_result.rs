@@ -1,5 +1,10 @@
+use std::sync::Arc;
+
 use crate::world_generation::{
-    chunk_generation::mesh_generation::MeshResult,
+    chunk_generation::{
+        chunk_light::ChunkLight, mesh_generation::MeshResult,
+        voxel_data::VoxelData, voxel_generation::ChunkColumns,
+    },
     chunk_loading::{chunk_tree::ChunkTreePos, lod_position::LodPosition},
 };
 
@@ -9,4 +14,10 @@ pub struct ChunkGenerationResult {
     pub chunk_pos: LodPosition,
     pub chunk_tree_position: ChunkTreePos,
     pub chunk_stack_offset: i32,
+    /// The voxels of the chunk, only kept at full detail.
+    pub voxel_data: Option<VoxelData>,
+    /// The columns of the chunk, only kept if more chunks are stacked on it.
+    pub columns: Option<Arc<ChunkColumns>>,
+    /// What the chunk needs to be lit again, `None` for height maps.
+    pub light: Option<ChunkLight>,
 }
@@ -0,0 +1,189 @@
+use std::{collections::HashMap, sync::Arc};
+
+use bevy::{prelude::*, tasks::Task};
+use futures_lite::future;
+use itertools::Itertools;
+
+use crate::world_generation::{
+    chunk_generation::{
+        CHUNK_SIZE,
+        chunk_lod::ChunkLod,
+        chunk_task::{ChunkTaskPool, spawn_chunk_meshes},
+        light_data::{BorderLight, LightData},
+        mesh_generation::{MeshResult, generate_mesh},
+        voxel_data::VoxelData,
+    },
+    generation_assets::GenerationAssets,
+};
+
+/// What a chunk needs to be lit again once its neighbours have light for
+/// it.
+#[derive(Component)]
+pub struct ChunkLight {
+    pub voxel_data: Arc<VoxelData>,
+    pub min_height: i32,
+    /// Whether no chunk is stacked on this one, so its top is open sky.
+    pub open_sky: bool,
+    pub border: Arc<BorderLight>,
+}
+
+/// Marks a chunk whose neighbours have light it hasn't taken in yet.
+#[derive(Component)]
+pub struct LightOutdated;
+
+#[derive(Component)]
+pub struct ChunkLightTask(Task<(MeshResult, BorderLight)>);
+
+/// The lit chunks by their lod and the world column at their corner.
+type LitChunks = HashMap<(ChunkLod, IVec2), Vec<Entity>>;
+
+/// Chunks that should be lit again and aren't being lit already.
+type OutdatedLight = (With<LightOutdated>, Without<ChunkLightTask>);
+
+/// Marks the chunks whose border the light of a new or relit chunk
+/// brightens, and a new chunk whose border its neighbours brighten.
+pub fn mark_outdated_light(
+    mut commands: Commands,
+    chunks: Query<(Entity, Ref<ChunkLight>)>,
+) {
+    if !chunks.iter().any(|(_, light)| light.is_changed()) {
+        return;
+    }
+
+    let lit_chunks = lit_chunks(
+        chunks
+            .iter()
+            .map(|(entity, light)| (entity, light.into_inner())),
+    );
+    for (entity, light) in chunks.iter().filter(|(_, light)| light.is_changed())
+    {
+        for neighbour in neighbours(&lit_chunks, entity, &light) {
+            let Ok((_, neighbour_light)) = chunks.get(neighbour) else {
+                continue;
+            };
+
+            if light.border.brightens(&neighbour_light.border) {
+                commands.entity(neighbour).insert(LightOutdated);
+            }
+            if light.is_added()
+                && neighbour_light.border.brightens(&light.border)
+            {
+                commands.entity(entity).insert(LightOutdated);
+            }
+        }
+    }
+}
+
+/// Lights the outdated chunks again with the border light of all their
+/// neighbours, and meshes them with the new light.
+pub fn queue_chunk_relight(
+    mut commands: Commands,
+    outdated_chunks: Query<(Entity, &ChunkLight), OutdatedLight>,
+    chunks: Query<(Entity, &ChunkLight)>,
+    chunk_task_pool: Res<ChunkTaskPool>,
+) {
+    if outdated_chunks.is_empty() {
+        return;
+    }
+
+    let lit_chunks = lit_chunks(chunks.iter());
+    for (entity, light) in &outdated_chunks {
+        let neighbour_borders = neighbours(&lit_chunks, entity, light)
+            .filter_map(|neighbour| chunks.get(neighbour).ok())
+            .map(|(_, neighbour_light)| neighbour_light.border.clone())
+            .collect_vec();
+        let voxel_data = light.voxel_data.clone();
+        let min_height = light.min_height;
+        let open_sky = light.open_sky;
+
+        let task = chunk_task_pool.task_pool.spawn(async move {
+            let mut light_data = LightData::new(&voxel_data, open_sky);
+            for border in &neighbour_borders {
+                light_data.take_border(&voxel_data, border);
+            }
+
+            (
+                generate_mesh(
+                    &voxel_data,
+                    &light_data,
+                    min_height,
+                    voxel_data.lod(),
+                ),
+                light_data.border(&voxel_data),
+            )
+        });
+
+        commands
+            .entity(entity)
+            .remove::<LightOutdated>()
+            .insert(ChunkLightTask(task));
+    }
+}
+
+/// Swaps in the meshes of the relit chunks. A chunk whose border light
+/// changed is marked as changed, so its neighbours take it in too.
+pub fn set_relit_chunks(
+    mut commands: Commands,
+    mut chunks: Query<(Entity, &mut ChunkLightTask, &mut ChunkLight)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    generation_assets: Res<GenerationAssets>,
+) {
+    for (entity, mut task, mut light) in &mut chunks {
+        let Some((mesh_result, border)) =
+            future::block_on(future::poll_once(&mut task.0))
+        else {
+            continue;
+        };
+
+        let mut chunk = commands.entity(entity);
+        chunk
+            .remove::<ChunkLightTask>()
+            .despawn_related::<Children>();
+        spawn_chunk_meshes(
+            &mut chunk,
+            mesh_result,
+            &mut meshes,
+            &generation_assets,
+        );
+
+        if *light.border != border {
+            light.border = Arc::new(border);
+        }
+    }
+}
+
+fn lit_chunks<'a>(
+    chunks: impl Iterator<Item = (Entity, &'a ChunkLight)>,
+) -> LitChunks {
+    chunks
+        .map(|(entity, light)| (corner_key(light), entity))
+        .into_group_map()
+}
+
+fn corner_key(light: &ChunkLight) -> (ChunkLod, IVec2) {
+    let voxel_data = &light.voxel_data;
+    (
+        voxel_data.lod(),
+        voxel_data.local_to_world(IVec3::ZERO).xz(),
+    )
+}
+
+/// The other chunks of the same lod in the column of a chunk and in the
+/// eight columns around it.
+fn neighbours<'a>(
+    lit_chunks: &'a LitChunks,
+    entity: Entity,
+    light: &ChunkLight,
+) -> impl Iterator<Item = Entity> + 'a {
+    let (lod, corner) = corner_key(light);
+    let step = CHUNK_SIZE as i32 * lod.multiplier_i32();
+
+    (-1..=1)
+        .cartesian_product(-1..=1)
+        .filter_map(move |(x, z)| {
+            lit_chunks.get(&(lod, corner + IVec2::new(x, z) * step))
+        })
+        .flatten()
+        .copied()
+        .filter(move |neighbour| *neighbour != entity)
+}
@@ -1,6 +1,6 @@
 pub const MAX_LOD: ChunkLod = ChunkLod::OneTwentyEight;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub enum ChunkLod {
     #[default]
     Full = 1,
@@ -1,16 +1,25 @@
+use std::sync::Arc;
+
 use bevy::prelude::*;
 use itertools::Itertools;
 
 use crate::world_generation::{
     chunk_generation::{
+        CHUNK_SIZE,
         chunk_generation_result::ChunkGenerationResult,
+        chunk_light::ChunkLight,
+        chunk_lod::ChunkLod,
         chunk_task::{ChunkTask, ChunkTaskPool},
         country::{
             cache_generation_task::CacheTaskPool, country_cache::CountryCache,
             country_cache_position::CountryPosition, country_data::CountryData,
         },
-        mesh_generation::generate_mesh,
-        voxel_generation::generate_voxels,
+        light_data::LightData,
+        mesh_generation::{
+            HEIGHTMAP_LOD, generate_heightmap_mesh, generate_mesh,
+        },
+        structures::structure_placement::placement_reach,
+        voxel_generation::{ChunkColumns, generate_columns, generate_voxels},
     },
     chunk_loading::{chunk_tree::ChunkTreePos, lod_position::LodPosition},
     generation_options::{GenerationOptions, GenerationOptionsResource},
@@ -21,6 +30,8 @@ pub struct ChunkStart {
     pub chunk_lod_pos: LodPosition,
     pub chunk_tree_pos: ChunkTreePos,
     pub chunk_stack_offset: i32,
+    /// The columns of the chunk below, `None` at the bottom of a stack.
+    pub columns: Option<Arc<ChunkColumns>>,
 }
 
 pub fn queue_chunk_tasks(
@@ -39,6 +50,7 @@ pub fn queue_chunk_tasks(
     }
 
     let mut currently_added_tasks = 0;
+    let reach = placement_reach(&generation_options.0.structure_generators);
 
     for (chunk_start, chunk_entity) in chunk_starts
         .iter()
@@ -53,12 +65,34 @@ pub fn queue_chunk_tasks(
             .get_absolute_chunk_pos(chunk_start.chunk_tree_pos);
         let country_pos = CountryPosition::from_chunk_pos(chunk_pos);
 
-        let Some(country_data) = country_cache.get_or_queue(
-            &mut commands,
-            country_pos,
-            &cache_task_pool,
-            &generation_options,
-        ) else {
+        // Structures near a border are checked against the country they
+        // stand in, so the chunk waits for every country they can reach.
+        let area_min = *chunk_pos * CHUNK_SIZE as i32;
+        let area_max = area_min
+            + IVec2::splat(CHUNK_SIZE as i32 + 1)
+                * chunk_start.chunk_lod_pos.lod.multiplier_i32();
+        let min_country = CountryPosition::from_column(area_min - reach);
+        let max_country = CountryPosition::from_column(area_max + reach);
+        let countries = (min_country.x..=max_country.x)
+            .cartesian_product(min_country.y..=max_country.y)
+            .map(|(x, y)| {
+                country_cache.get_or_queue(
+                    &mut commands,
+                    CountryPosition::new(IVec2::new(x, y)),
+                    &cache_task_pool,
+                    &generation_options,
+                )
+            })
+            .collect_vec();
+        let Some(countries) = countries.into_iter().collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+        let Some(country_data) = countries
+            .iter()
+            .find(|country| country.country_pos == country_pos)
+            .cloned()
+        else {
             continue;
         };
 
@@ -67,13 +101,16 @@ pub fn queue_chunk_tasks(
         let lod_pos = chunk_start.chunk_lod_pos;
         let tree_pos = chunk_start.chunk_tree_pos;
         let stack_height = chunk_start.chunk_stack_offset;
+        let columns = chunk_start.columns.clone();
         let task = chunk_task_pool.task_pool.spawn(async move {
             generate_chunk(
                 lod_pos,
                 tree_pos,
                 stack_height,
+                columns,
                 &generation_options,
                 &country_data,
+                &countries,
             )
         });
 
@@ -88,18 +125,50 @@ fn generate_chunk(
     chunk_pos: LodPosition,
     tree_pos: ChunkTreePos,
     stack_height: i32,
+    columns: Option<Arc<ChunkColumns>>,
     generation_options: &GenerationOptions,
     country_data: &CountryData,
+    countries: &[CountryData],
 ) -> ChunkGenerationResult {
     let absolute_chunk_pos = chunk_pos.get_absolute_chunk_pos(tree_pos);
+    let columns = columns.unwrap_or_else(|| {
+        Arc::new(generate_columns(
+            *absolute_chunk_pos,
+            generation_options,
+            chunk_pos.lod,
+            country_data,
+        ))
+    });
+
+    if chunk_pos.lod >= HEIGHTMAP_LOD {
+        return ChunkGenerationResult {
+            mesh_result: generate_heightmap_mesh(&columns, chunk_pos.lod),
+            generate_above: false,
+            chunk_pos,
+            chunk_tree_position: tree_pos,
+            chunk_stack_offset: stack_height,
+            voxel_data: None,
+            columns: None,
+            light: None,
+        };
+    }
+
     let (data, min_height, more) = generate_voxels(
         [absolute_chunk_pos.x, stack_height, absolute_chunk_pos.y],
+        &columns,
         generation_options,
         chunk_pos.lod,
         country_data,
+        countries,
     );
 
-    let mesh_result = generate_mesh(&data, min_height, chunk_pos.lod);
+    // The top of the chunk is only open sky if no chunk is stacked on it,
+    // otherwise the chunk above lights it once it is generated.
+    let open_sky = !more;
+    let light_data = LightData::new(&data, open_sky);
+    let mesh_result =
+        generate_mesh(&data, &light_data, min_height, chunk_pos.lod);
+    let border = Arc::new(light_data.border(&data));
 
     ChunkGenerationResult {
         mesh_result,
@@ -107,5 +176,13 @@ fn generate_chunk(
         chunk_pos,
         chunk_tree_position: tree_pos,
         chunk_stack_offset: stack_height,
+        voxel_data: (chunk_pos.lod == ChunkLod::Full).then(|| data.clone()),
+        columns: more.then_some(columns),
+        light: Some(ChunkLight {
+            voxel_data: Arc::new(data),
+            min_height,
+            open_sky,
+            border,
+        }),
     }
 }
@@ -1,20 +1,39 @@
+use std::time::{Duration, Instant};
+
 use bevy::{
     prelude::*,
     tasks::{Task, TaskPool, TaskPoolBuilder},
 };
 use futures_lite::future;
+use itertools::Itertools;
 
 use crate::world_generation::{
     chunk_generation::{
-        chunk::Chunk, chunk_generation_result::ChunkGenerationResult,
+        chunk::{Chunk, StackColumns},
+        chunk_generation_result::ChunkGenerationResult,
         chunk_triangles::ChunkTriangles,
+        mesh_generation::MeshResult,
+        world_voxels::GeneratedVoxels,
     },
+    chunk_loading::chunk_node::ChunkNode,
     generation_assets::GenerationAssets,
 };
 
 #[derive(Component)]
 pub struct ChunkTask(pub Task<ChunkGenerationResult>);
 
+/// How long finishing generated chunks may take every frame. Once it is
+/// used up, the other finished chunks wait for the next frame.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct ChunkFinishBudget(pub Duration);
+
+impl Default for ChunkFinishBudget {
+    fn default() -> Self {
+        Self(Duration::from_millis(4))
+    }
+}
+
 #[derive(Resource)]
 pub struct ChunkTaskPool {
     pub task_pool: TaskPool,
@@ -33,59 +52,131 @@ impl Default for ChunkTaskPool {
 
 pub fn set_generated_chunks(
     mut commands: Commands,
-    mut chunks: Query<(Entity, &mut ChunkTask)>,
+    mut chunks: Query<(Entity, &mut ChunkTask, &ChildOf)>,
+    chunk_nodes: Query<&ChunkNode>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut _chunk_triangles: ResMut<ChunkTriangles>,
     generation_assets: Res<GenerationAssets>,
+    finish_budget: Res<ChunkFinishBudget>,
 ) {
-    for (entity, mut task) in &mut chunks {
-        let Some(chunk_generation_result) =
-            future::block_on(future::poll_once(&mut task.0))
-        else {
-            continue;
-        };
-
-        let mut current_entity = commands.entity(entity);
-
-        let chunk_pos =
-            chunk_generation_result.chunk_pos.get_absolute_chunk_pos(
-                chunk_generation_result.chunk_tree_position,
-            );
+    let start = Instant::now();
+
+    // The chunks of the four children of a dividing node are finished
+    // together and stay hidden until the node swaps them in, so the budget
+    // is only checked between groups and never splits them up. Regenerated
+    // chunks stay hidden until they replace the outdated ones. Close chunks
+    // come first.
+    let groups = chunks
+        .iter()
+        .filter(|(_, task, _)| task.0.is_finished())
+        .map(|(entity, _, ChildOf(chunk_node))| {
+            let node = chunk_nodes.get(*chunk_node).ok();
+            let dividing_parent =
+                node.and_then(ChunkNode::parent).filter(|parent| {
+                    chunk_nodes.get(*parent).is_ok_and(ChunkNode::is_dividing)
+                });
+            let hidden = dividing_parent.is_some()
+                || node.is_some_and(ChunkNode::is_regenerating);
+            (dividing_parent.unwrap_or(*chunk_node), (entity, hidden))
+        })
+        .into_group_map()
+        .into_iter()
+        .sorted_by_key(|(group_node, _)| {
+            chunk_nodes
+                .get(*group_node)
+                .map(|chunk_node| chunk_node.position().lod)
+                .ok()
+        })
+        .collect_vec();
+
+    for (_, group) in groups {
+        if start.elapsed() >= finish_budget.0 {
+            break;
+        }
+
+        for (entity, hidden) in group {
+            let Ok((_, mut task, _)) = chunks.get_mut(entity) else {
+                continue;
+            };
+
+            let Some(chunk_generation_result) =
+                future::block_on(future::poll_once(&mut task.0))
+            else {
+                continue;
+            };
+
+            let mut current_entity = commands.entity(entity);
 
-        current_entity.remove::<ChunkTask>().insert((
-            Chunk {
-                tree_position: chunk_generation_result.chunk_tree_position,
-                chunk_height: chunk_generation_result.chunk_stack_offset,
-                generate_above: chunk_generation_result.generate_above,
-                lod_position: chunk_generation_result.chunk_pos,
-            },
-            Transform::from_translation(chunk_pos.to_absolute()),
-        ));
-
-        // let triangle_count = mesh.indices().unwrap().len() / 3;
-        // let result_lod = chunk_generation_result.chunk_pos.lod.usize();
-        // chunk_triangles.0[result_lod - 1] += triangle_count as u64;
-
-        let opaque_mesh = chunk_generation_result.mesh_result.opaque_mesh;
-        let transparent_mesh =
-            chunk_generation_result.mesh_result.transparent_mesh;
-
-        current_entity.with_children(|child_spawner| {
-            if let Some(mesh) = opaque_mesh {
-                child_spawner.spawn((
-                    Mesh3d(meshes.add(mesh)),
-                    MeshMaterial3d(generation_assets.opaque_material.clone()),
-                ));
+            let chunk_pos =
+                chunk_generation_result.chunk_pos.get_absolute_chunk_pos(
+                    chunk_generation_result.chunk_tree_position,
+                );
+
+            if hidden {
+                current_entity.insert(Visibility::Hidden);
             }
 
-            if let Some(mesh) = transparent_mesh {
-                child_spawner.spawn((
-                    Mesh3d(meshes.add(mesh)),
-                    MeshMaterial3d(
-                        generation_assets.transparent_material.clone(),
-                    ),
-                ));
+            current_entity.remove::<ChunkTask>().insert((
+                Chunk {
+                    tree_position: chunk_generation_result.chunk_tree_position,
+                    chunk_height: chunk_generation_result.chunk_stack_offset,
+                    generate_above: chunk_generation_result.generate_above,
+                    lod_position: chunk_generation_result.chunk_pos,
+                },
+                GeneratedVoxels(chunk_generation_result.voxel_data),
+                Transform::from_translation(chunk_pos.to_absolute()),
+            ));
+
+            if let Some(columns) = chunk_generation_result.columns {
+                current_entity.insert(StackColumns(columns));
             }
-        });
+
+            // let triangle_count = mesh.indices().unwrap().len() / 3;
+            // let result_lod = chunk_generation_result.chunk_pos.lod.usize();
+            // chunk_triangles.0[result_lod - 1] += triangle_count as u64;
+
+            if let Some(light) = chunk_generation_result.light {
+                current_entity.insert(light);
+            }
+
+            spawn_chunk_meshes(
+                &mut current_entity,
+                chunk_generation_result.mesh_result,
+                &mut meshes,
+                &generation_assets,
+            );
+        }
     }
 }
+
+/// Spawns the meshes of a chunk as its children.
+pub fn spawn_chunk_meshes(
+    chunk: &mut EntityCommands,
+    mesh_result: MeshResult,
+    meshes: &mut Assets<Mesh>,
+    generation_assets: &GenerationAssets,
+) {
+    let transform = mesh_result.transform;
+    let aabb = mesh_result.aabb;
+
+    chunk.with_children(|child_spawner| {
+        for (mesh, material) in [
+            (mesh_result.opaque_mesh, &generation_assets.opaque_material),
+            (
+                mesh_result.transparent_mesh,
+                &generation_assets.transparent_material,
+            ),
+            (mesh_result.fluid_mesh, &generation_assets.fluid_material),
+        ] {
+            let Some(mesh) = mesh else {
+                continue;
+            };
+            child_spawner.spawn((
+                Mesh3d(meshes.add(mesh)),
+                MeshMaterial3d(material.clone()),
+                transform,
+                aabb,
+            ));
+        }
+    });
+}
@@ -6,8 +6,11 @@ use crate::world_generation::{
         cache_generation_task::{CacheGenerationTask, CacheTaskPool},
         country_cache_position::CountryPosition,
         country_data::CountryData,
+        erosion_data::ErosionData,
         generation_cache::{GenerationCache, GenerationCacheItem},
+        lake_data::LakeData,
         path_data::PathData,
+        river_data::RiverData,
         structure_data::StructureData,
     },
     generation_options::GenerationOptionsResource,
@@ -25,6 +28,9 @@ pub struct CountryCache {
 pub struct CacheStore {
     pub path_cache: GenerationCache<CountryPosition, PathData>,
     pub structure_cache: GenerationCache<CountryPosition, StructureData>,
+    pub lake_cache: GenerationCache<CountryPosition, LakeData>,
+    pub river_cache: GenerationCache<CountryPosition, RiverData>,
+    pub erosion_cache: GenerationCache<CountryPosition, ErosionData>,
 }
 
 pub enum GenerationState<T> {
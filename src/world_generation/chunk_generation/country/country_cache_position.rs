@@ -13,6 +13,11 @@ impl CountryPosition {
         Self(vec)
     }
 
+    /// The country a column of full sized voxels lies in.
+    pub fn from_column(column: IVec2) -> Self {
+        Self(column.div_euclid(IVec2::splat(COUNTRY_SIZE as i32)))
+    }
+
     pub fn from_chunk_pos(chunk_pos: AbsoluteChunkPos) -> Self {
         let country_chunk_size = COUNTRY_SIZE / CHUNK_SIZE;
         Self(
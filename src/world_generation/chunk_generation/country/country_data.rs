@@ -1,12 +1,21 @@
 use std::sync::Arc;
 
 use bevy::prelude::*;
+use itertools::Itertools;
 
 use crate::world_generation::{
-    chunk_generation::country::{
-        country_cache::CacheStore, country_cache_position::CountryPosition,
-        generation_cache::GenerationCacheItem, path_data::PathData,
-        structure_data::StructureData,
+    chunk_generation::{
+        country::{
+            country_cache::CacheStore,
+            country_cache_position::CountryPosition,
+            eroded_terrain::{ErodedTerrain, ErosionNeighbourhood},
+            generation_cache::GenerationCacheItem,
+            lake_data::LakeData,
+            path_data::PathData,
+            river_data::RiverData,
+            structure_data::StructureData,
+        },
+        noise::noise_grid::GridNoiseFn,
     },
     generation_options::GenerationOptions,
 };
@@ -18,6 +27,11 @@ pub struct CountryData {
     pub this_path_cache: Arc<PathData>,
     pub bottom_path_cache: Arc<PathData>,
     pub left_path_cache: Arc<PathData>,
+    pub lake_cache: Arc<LakeData>,
+    /// The rivers springing in this country and in the eight around it,
+    /// which can flow over the border into this one.
+    pub river_caches: Vec<Arc<RiverData>>,
+    pub erosion: ErosionNeighbourhood,
 }
 
 impl GenerationCacheItem<CountryPosition> for CountryData {
@@ -47,6 +61,39 @@ impl GenerationCacheItem<CountryPosition> for CountryData {
                 generation_options,
                 cache_store.clone(),
             ),
+            lake_cache: cache_store.clone().lake_cache.get_cache_entry(
+                key,
+                generation_options,
+                cache_store.clone(),
+            ),
+            river_caches: (-1..=1)
+                .cartesian_product(-1..=1)
+                .map(|(x, y)| {
+                    cache_store.clone().river_cache.get_cache_entry(
+                        CountryPosition::new(*key + IVec2::new(x, y)),
+                        generation_options,
+                        cache_store.clone(),
+                    )
+                })
+                .collect(),
+            erosion: ErosionNeighbourhood::new(
+                key,
+                generation_options,
+                cache_store.clone(),
+            ),
         }
     }
 }
+
+impl CountryData {
+    /// The height of the eroded terrain in and around the country.
+    pub fn terrain_height(
+        &self,
+        generation_options: &GenerationOptions,
+    ) -> ErodedTerrain<impl GridNoiseFn> {
+        ErodedTerrain::new(
+            generation_options.get_terrain_noise(),
+            self.erosion.clone(),
+        )
+    }
+}
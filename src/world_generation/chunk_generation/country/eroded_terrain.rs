@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use itertools::Itertools;
+use noise::NoiseFn;
+
+use crate::world_generation::{
+    chunk_generation::{
+        country::{
+            country_cache::CacheStore, country_cache_position::CountryPosition,
+            erosion_data::ErosionData,
+        },
+        noise::noise_grid::{GridNoiseFn, NoiseGrid, NoiseGridArea},
+    },
+    generation_options::GenerationOptions,
+};
+
+/// The erosion of a country and of the eight countries around it, so the
+/// eroded terrain is known a little past the border too.
+#[derive(Clone)]
+pub struct ErosionNeighbourhood {
+    /// The erosion of every country, x-major: column by column from the
+    /// lowest corner.
+    countries: Vec<Arc<ErosionData>>,
+}
+
+impl ErosionNeighbourhood {
+    pub fn new(
+        center: CountryPosition,
+        generation_options: &GenerationOptions,
+        cache_store: Arc<CacheStore>,
+    ) -> Self {
+        Self {
+            countries: (-1..=1)
+                .cartesian_product(-1..=1)
+                .map(|(x, y)| {
+                    cache_store.clone().erosion_cache.get_cache_entry(
+                        CountryPosition::new(*center + IVec2::new(x, y)),
+                        generation_options,
+                        cache_store.clone(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// How many voxels erosion raises or lowers a column, blended from the
+    /// countries whose simulations reach it. Near the outer edge of the
+    /// neighbourhood only the countries it holds are blended.
+    pub fn height_delta(&self, column: IVec2) -> f32 {
+        let (delta, total_weight) = self
+            .countries
+            .iter()
+            .map(|erosion| (erosion, erosion.blend_weight(column)))
+            .filter(|(_, weight)| *weight > 0.)
+            .fold((0., 0.), |(delta, total_weight), (erosion, weight)| {
+                (
+                    delta + erosion.height_delta(column) * weight,
+                    total_weight + weight,
+                )
+            });
+        if total_weight == 0. {
+            return 0.;
+        }
+
+        delta / total_weight
+    }
+}
+
+/// The terrain noise with erosion added. Everything that builds on the
+/// height of the terrain samples it through this, so lakes, rivers, paths
+/// and structures sit on the same ground the voxels are made of.
+pub struct ErodedTerrain<T> {
+    terrain_noise: T,
+    erosion: ErosionNeighbourhood,
+}
+
+impl<T> ErodedTerrain<T> {
+    pub fn new(terrain_noise: T, erosion: ErosionNeighbourhood) -> Self {
+        Self {
+            terrain_noise,
+            erosion,
+        }
+    }
+
+    fn height_delta(&self, point: [f64; 2]) -> f64 {
+        self.erosion.height_delta(IVec2::new(
+            point[0].floor() as i32,
+            point[1].floor() as i32,
+        )) as f64
+    }
+}
+
+impl<T: NoiseFn<f64, 2>> NoiseFn<f64, 2> for ErodedTerrain<T> {
+    fn get(&self, point: [f64; 2]) -> f64 {
+        self.terrain_noise.get(point) + self.height_delta(point)
+    }
+}
+
+impl<T: GridNoiseFn> GridNoiseFn for ErodedTerrain<T> {
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        self.terrain_noise.get_grid(area).zip(
+            &NoiseGrid::from_fn(area, |point| self.height_delta(point)),
+            |height, delta| height + delta,
+        )
+    }
+}
@@ -0,0 +1,307 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use noise::NoiseFn;
+use rand::Rng;
+
+use crate::world_generation::{
+    chunk_generation::country::{
+        country_cache::{COUNTRY_SIZE, CacheStore},
+        country_cache_position::CountryPosition,
+        generation_cache::GenerationCacheItem,
+    },
+    generation_options::{ErosionSettings, GenerationOptions},
+    seed_derivation::{EROSION_SALT, derive_seed, positional_rng},
+};
+
+/// The distance in voxels between two nodes of the erosion grid.
+const EROSION_GRID_STEP: i32 = 64;
+const EROSION_GRID_NODES: i32 = COUNTRY_SIZE as i32 / EROSION_GRID_STEP + 1;
+/// How many nodes the simulated terrain reaches past the country on every
+/// side, so droplets can run in from outside. Neighbouring countries are
+/// blended over the overlap of their simulations.
+const MARGIN_NODES: i32 = 16;
+const SIMULATION_NODES: i32 = EROSION_GRID_NODES + 2 * MARGIN_NODES;
+
+/// The corners of a grid cell, in the order of `corner_weights`.
+const CORNERS: [IVec2; 4] = [IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE];
+
+const MAX_DROPLET_STEPS: usize = 64;
+const MIN_SLOPE: f32 = 0.01;
+const GRAVITY: f32 = 4.;
+
+/// How the terrain of a country changes through erosion.
+///
+/// The terrain is sampled on a coarse grid that reaches a little past the
+/// country and eroded there. Around a border the simulations of both
+/// countries overlap, and their height changes are blended with
+/// `blend_weight`, so the terrain is seamless without either knowing of the
+/// other.
+#[derive(Default)]
+pub struct ErosionData {
+    origin: IVec2,
+    /// The height change of every simulated node in voxels, empty if erosion
+    /// is turned off.
+    deltas: Vec<f32>,
+}
+
+impl GenerationCacheItem<CountryPosition> for ErosionData {
+    fn generate(
+        key: CountryPosition,
+        generation_options: &GenerationOptions,
+        _cache_store: Arc<CacheStore>,
+    ) -> Self {
+        let origin = *key * COUNTRY_SIZE as i32;
+        let erosion_settings = &generation_options.erosion_settings;
+        if !erosion_settings.enabled {
+            return Self {
+                origin,
+                deltas: vec![],
+            };
+        }
+
+        // Heights are kept in grid steps, so slopes are the same as in the
+        // terrain.
+        let terrain_noise = generation_options.get_terrain_noise();
+        let simulation_origin =
+            origin - IVec2::splat(MARGIN_NODES * EROSION_GRID_STEP);
+        let original = HeightGrid {
+            heights: (0..SIMULATION_NODES * SIMULATION_NODES)
+                .map(|index| {
+                    let node = IVec2::new(
+                        index % SIMULATION_NODES,
+                        index / SIMULATION_NODES,
+                    );
+                    let column = simulation_origin + node * EROSION_GRID_STEP;
+                    (terrain_noise.get(column.as_dvec2().to_array())
+                        / EROSION_GRID_STEP as f64) as f32
+                })
+                .collect(),
+        };
+
+        let mut eroded = original.clone();
+        let mut rng = positional_rng(
+            derive_seed(generation_options.seed, EROSION_SALT),
+            *key,
+        );
+        let droplets = ((SIMULATION_NODES * SIMULATION_NODES) as f32
+            * erosion_settings.droplets_per_node)
+            as usize;
+        for _ in 0..droplets {
+            let start = Vec2::new(
+                rng.random_range(0. ..(SIMULATION_NODES - 1) as f32),
+                rng.random_range(0. ..(SIMULATION_NODES - 1) as f32),
+            );
+            eroded.run_droplet(start, erosion_settings);
+        }
+        for _ in 0..erosion_settings.thermal_iterations {
+            eroded.crumble(erosion_settings.talus);
+        }
+
+        let deltas = eroded
+            .heights
+            .iter()
+            .zip(&original.heights)
+            .map(|(eroded, original)| {
+                (eroded - original) * EROSION_GRID_STEP as f32
+            })
+            .collect();
+
+        Self { origin, deltas }
+    }
+}
+
+impl ErosionData {
+    /// How many voxels the simulation of this country raises or lowers a
+    /// column. Columns it doesn't reach aren't eroded.
+    pub fn height_delta(&self, column: IVec2) -> f32 {
+        if self.deltas.is_empty() {
+            return 0.;
+        }
+
+        let position = (column - self.origin).as_vec2()
+            / EROSION_GRID_STEP as f32
+            + MARGIN_NODES as f32;
+        let cell = position.floor().as_ivec2();
+        if cell.min_element() < 0 || cell.max_element() >= SIMULATION_NODES - 1
+        {
+            return 0.;
+        }
+
+        bilinear(
+            CORNERS.map(|corner| self.deltas[HeightGrid::index(cell + corner)]),
+            position - cell.as_vec2(),
+        )
+    }
+
+    /// How much the height change of this country counts for a column. It
+    /// is one inside of the country and ramps down over the overlap with
+    /// the simulation of each neighbour, so the weights of all countries
+    /// add up to one everywhere.
+    pub fn blend_weight(&self, column: IVec2) -> f32 {
+        let margin = (MARGIN_NODES * EROSION_GRID_STEP) as f32;
+        let ramp = |border_distance: f32| {
+            let t = ((border_distance + margin) / (2. * margin)).clamp(0., 1.);
+            t * t * (3. - 2. * t)
+        };
+
+        let position = (column - self.origin).as_vec2();
+        let far_distance = Vec2::splat(COUNTRY_SIZE as f32) - position;
+        ramp(position.x)
+            * ramp(far_distance.x)
+            * ramp(position.y)
+            * ramp(far_distance.y)
+    }
+}
+
+/// The heights of the simulated terrain in grid steps.
+#[derive(Clone)]
+struct HeightGrid {
+    heights: Vec<f32>,
+}
+
+impl HeightGrid {
+    fn get(&self, node: IVec2) -> f32 {
+        self.heights[Self::index(node)]
+    }
+
+    fn index(node: IVec2) -> usize {
+        (node.x + node.y * SIMULATION_NODES) as usize
+    }
+
+    fn contains(position: Vec2) -> bool {
+        position.min_element() >= 0.
+            && position.max_element() < (SIMULATION_NODES - 1) as f32
+    }
+
+    /// The interpolated height and slope at a position between the nodes.
+    fn height_and_gradient(&self, position: Vec2) -> (f32, Vec2) {
+        let cell = position.floor().as_ivec2();
+        let offset = position - cell.as_vec2();
+
+        let bottom_left = self.get(cell);
+        let bottom_right = self.get(cell + IVec2::X);
+        let top_left = self.get(cell + IVec2::Y);
+        let top_right = self.get(cell + IVec2::ONE);
+
+        let gradient = Vec2::new(
+            (bottom_right - bottom_left) * (1. - offset.y)
+                + (top_right - top_left) * offset.y,
+            (top_left - bottom_left) * (1. - offset.x)
+                + (top_right - bottom_right) * offset.x,
+        );
+        let height =
+            bilinear([bottom_left, bottom_right, top_left, top_right], offset);
+
+        (height, gradient)
+    }
+
+    /// Spreads `amount` over the four nodes around `position`.
+    fn add(&mut self, position: Vec2, amount: f32) {
+        let cell = position.floor().as_ivec2();
+        let offset = position - cell.as_vec2();
+
+        for (corner, weight) in CORNERS.into_iter().zip(corner_weights(offset))
+        {
+            self.heights[Self::index(cell + corner)] += amount * weight;
+        }
+    }
+
+    /// Lets a droplet of water run downhill from `position`. It picks up
+    /// sediment where it is fast and drops it where it slows down.
+    fn run_droplet(&mut self, mut position: Vec2, settings: &ErosionSettings) {
+        let mut direction = Vec2::ZERO;
+        let mut speed = 1f32;
+        let mut water = 1f32;
+        let mut sediment = 0f32;
+
+        for _ in 0..MAX_DROPLET_STEPS {
+            let (height, gradient) = self.height_and_gradient(position);
+            direction = (direction * settings.inertia
+                - gradient * (1. - settings.inertia))
+                .normalize_or_zero();
+            let next_position = position + direction;
+            if direction == Vec2::ZERO || !Self::contains(next_position) {
+                break;
+            }
+
+            let (next_height, _) = self.height_and_gradient(next_position);
+            let height_difference = next_height - height;
+            let capacity = (-height_difference).max(MIN_SLOPE)
+                * speed
+                * water
+                * settings.sediment_capacity;
+
+            if height_difference > 0. || sediment > capacity {
+                // Uphill the droplet fills the pit it is in, otherwise it
+                // drops what it can't carry.
+                let deposit = if height_difference > 0. {
+                    height_difference.min(sediment)
+                } else {
+                    (sediment - capacity) * settings.deposition_rate
+                };
+                sediment -= deposit;
+                self.add(position, deposit);
+            } else {
+                let erosion = ((capacity - sediment) * settings.erosion_rate)
+                    .min(-height_difference);
+                sediment += erosion;
+                self.add(position, -erosion);
+            }
+
+            speed =
+                (speed * speed - height_difference * GRAVITY).max(0.).sqrt();
+            water *= 1. - settings.evaporation_rate;
+            position = next_position;
+        }
+    }
+
+    /// Moves material from every node down to its lower neighbours where
+    /// the slope between them is steeper than `talus`.
+    fn crumble(&mut self, talus: f32) {
+        for y in 0..SIMULATION_NODES {
+            for x in 0..SIMULATION_NODES {
+                let node = IVec2::new(x, y);
+                for direction in
+                    [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+                {
+                    let neighbour = node + direction;
+                    if neighbour.min_element() < 0
+                        || neighbour.max_element() >= SIMULATION_NODES
+                    {
+                        continue;
+                    }
+
+                    let difference = self.get(node) - self.get(neighbour);
+                    if difference <= talus {
+                        continue;
+                    }
+
+                    // Every neighbour takes away a quarter of the excess,
+                    // so a node can't crumble below its neighbours.
+                    let amount = (difference - talus) / 8.;
+                    self.heights[Self::index(node)] -= amount;
+                    self.heights[Self::index(neighbour)] += amount;
+                }
+            }
+        }
+    }
+}
+
+/// How much each corner of a cell counts at `offset` inside of it.
+fn corner_weights(offset: Vec2) -> [f32; 4] {
+    [
+        (1. - offset.x) * (1. - offset.y),
+        offset.x * (1. - offset.y),
+        (1. - offset.x) * offset.y,
+        offset.x * offset.y,
+    ]
+}
+
+fn bilinear(corners: [f32; 4], offset: Vec2) -> f32 {
+    corners
+        .into_iter()
+        .zip(corner_weights(offset))
+        .map(|(value, weight)| value * weight)
+        .sum()
+}
@@ -0,0 +1,23 @@
+use std::cmp::Ordering;
+
+/// A node of a country grid on the shore of a priority flood, ordered so
+/// the lowest one is taken first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FloodCandidate {
+    pub height: f64,
+    pub index: usize,
+}
+
+impl Eq for FloodCandidate {}
+
+impl PartialOrd for FloodCandidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FloodCandidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.height.total_cmp(&self.height)
+    }
+}
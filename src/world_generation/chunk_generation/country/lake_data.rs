@@ -0,0 +1,214 @@
+use std::{collections::BinaryHeap, sync::Arc};
+
+use bevy::prelude::*;
+use noise::NoiseFn;
+
+use crate::world_generation::{
+    chunk_generation::{
+        VOXEL_SIZE,
+        country::{
+            country_cache::{COUNTRY_SIZE, CacheStore},
+            country_cache_position::CountryPosition,
+            eroded_terrain::{ErodedTerrain, ErosionNeighbourhood},
+            flood_candidate::FloodCandidate,
+            generation_cache::GenerationCacheItem,
+        },
+    },
+    generation_options::{GenerationOptions, WaterSettings},
+};
+
+/// The distance in voxels between two samples of the lake grid.
+const LAKE_GRID_STEP: i32 = 64;
+const LAKE_GRID_NODES: i32 = COUNTRY_SIZE as i32 / LAKE_GRID_STEP + 1;
+/// How many grid nodes past every side of the country are flooded too, so
+/// a basin cut by the border is filled the same from both countries.
+const FLOOD_MARGIN: i32 = 32;
+const FLOOD_GRID_NODES: i32 = LAKE_GRID_NODES + 2 * FLOOD_MARGIN;
+
+/// The lakes of a country.
+///
+/// The eroded terrain is sampled on a coarse grid, whose basins are filled
+/// up to the height they overflow at, with the border of the flooded grid
+/// as the outlet. The grid reaches `FLOOD_MARGIN` nodes past the country,
+/// so only basins wider than that drain over the border and end there.
+/// Where the water is deep enough and above the sea, it is kept as a lake.
+#[derive(Default)]
+pub struct LakeData {
+    origin: IVec2,
+    /// The terrain height of every grid node in voxels, empty if lakes are
+    /// turned off.
+    heights: Vec<f32>,
+    /// The lake surface of every grid node in voxels, empty if lakes are
+    /// turned off.
+    levels: Vec<Option<f32>>,
+}
+
+impl GenerationCacheItem<CountryPosition> for LakeData {
+    fn generate(
+        key: CountryPosition,
+        generation_options: &GenerationOptions,
+        cache_store: Arc<CacheStore>,
+    ) -> Self {
+        let origin = *key * COUNTRY_SIZE as i32;
+        let water_settings = &generation_options.water_settings;
+        if !water_settings.lakes {
+            return Self {
+                origin,
+                heights: vec![],
+                levels: vec![],
+            };
+        }
+
+        let terrain_height = ErodedTerrain::new(
+            generation_options.get_terrain_noise(),
+            ErosionNeighbourhood::new(key, generation_options, cache_store),
+        );
+        let flood_origin = origin - FLOOD_MARGIN * LAKE_GRID_STEP;
+        let node_count = (FLOOD_GRID_NODES * FLOOD_GRID_NODES) as usize;
+        let flood_heights = (0..node_count)
+            .map(|index| {
+                let node = node_position(index, FLOOD_GRID_NODES);
+                let column = flood_origin + node * LAKE_GRID_STEP;
+                terrain_height.get(column.as_dvec2().to_array())
+            })
+            .collect::<Vec<_>>();
+
+        // Priority flood: the lowest node of the shore is taken next, so
+        // every node is reached over the lowest possible pass.
+        let mut filled = vec![None; node_count];
+        let mut shore = BinaryHeap::new();
+        for (index, height) in flood_heights.iter().enumerate() {
+            let node = node_position(index, FLOOD_GRID_NODES);
+            if node.min_element() == 0
+                || node.max_element() == FLOOD_GRID_NODES - 1
+            {
+                filled[index] = Some(*height);
+                shore.push(FloodCandidate {
+                    height: *height,
+                    index,
+                });
+            }
+        }
+
+        while let Some(candidate) = shore.pop() {
+            let node = node_position(candidate.index, FLOOD_GRID_NODES);
+            for direction in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+                let Some(index) =
+                    node_index(node + direction, FLOOD_GRID_NODES)
+                else {
+                    continue;
+                };
+                if filled[index].is_some() {
+                    continue;
+                }
+
+                let height = flood_heights[index].max(candidate.height);
+                filled[index] = Some(height);
+                shore.push(FloodCandidate { height, index });
+            }
+        }
+
+        let sea_level = water_settings.sea_level / VOXEL_SIZE;
+        let min_depth = water_settings.min_lake_depth / VOXEL_SIZE;
+        let country_node_count = (LAKE_GRID_NODES * LAKE_GRID_NODES) as usize;
+        let (heights, levels) = (0..country_node_count)
+            .map(|index| {
+                let node = node_position(index, LAKE_GRID_NODES)
+                    + IVec2::splat(FLOOD_MARGIN);
+                let flood_index = node_index(node, FLOOD_GRID_NODES)
+                    .expect("The country lies inside of the flooded grid!");
+                let height = flood_heights[flood_index] as f32;
+                let level =
+                    filled[flood_index].map_or(height, |level| level as f32);
+                let lake = (level - height >= min_depth && level > sea_level)
+                    .then_some(level);
+                (height, lake)
+            })
+            .unzip();
+
+        Self {
+            origin,
+            heights,
+            levels,
+        }
+    }
+}
+
+impl LakeData {
+    /// The height in voxels of the lake over a column. A column takes the
+    /// highest lake at the corners of its grid cell it connects to, which
+    /// is one whose interpolated terrain stays below the lake all the way
+    /// from the corner to the column.
+    pub fn lake_level(&self, column: IVec2) -> Option<f32> {
+        if self.levels.is_empty() {
+            return None;
+        }
+
+        let relative = column - self.origin;
+        let cell = relative.div_euclid(IVec2::splat(LAKE_GRID_STEP));
+        let fraction = (relative - cell * LAKE_GRID_STEP).as_vec2()
+            / LAKE_GRID_STEP as f32;
+        let corner_heights =
+            [IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE].map(|corner| {
+                node_index(cell + corner, LAKE_GRID_NODES)
+                    .map(|index| self.heights[index])
+            });
+        let [Some(h00), Some(h10), Some(h01), Some(h11)] = corner_heights
+        else {
+            return None;
+        };
+        let terrain = |position: Vec2| {
+            h00.lerp(h10, position.x)
+                .lerp(h01.lerp(h11, position.x), position.y)
+        };
+
+        [IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE]
+            .into_iter()
+            .filter_map(|corner| {
+                let level =
+                    self.levels[node_index(cell + corner, LAKE_GRID_NODES)?]?;
+                (highest_between(terrain, corner.as_vec2(), fraction) < level)
+                    .then_some(level)
+            })
+            .reduce(f32::max)
+    }
+
+    /// The height in voxels water fills a column up to, from the sea or a
+    /// lake.
+    pub fn water_level(
+        &self,
+        column: IVec2,
+        water_settings: &WaterSettings,
+    ) -> f32 {
+        let sea_level = water_settings.sea_level / VOXEL_SIZE;
+        self.lake_level(column)
+            .map_or(sea_level, |lake_level| lake_level.max(sea_level))
+    }
+}
+
+/// The highest point of the bilinear `terrain` on the line from `from` to
+/// `to`. Along a line it is a quadratic, so it peaks at an end or at the
+/// vertex.
+fn highest_between(terrain: impl Fn(Vec2) -> f32, from: Vec2, to: Vec2) -> f32 {
+    let start = terrain(from);
+    let middle = terrain(from.lerp(to, 0.5));
+    let end = terrain(to);
+
+    let a = 2. * (start + end) - 4. * middle;
+    let b = end - start - a;
+    let vertex = -b / (2. * a);
+    if a < 0. && vertex > 0. && vertex < 1. {
+        start.max(end).max(terrain(from.lerp(to, vertex)))
+    } else {
+        start.max(end)
+    }
+}
+
+fn node_position(index: usize, grid_nodes: i32) -> IVec2 {
+    IVec2::new(index as i32 % grid_nodes, index as i32 / grid_nodes)
+}
+
+fn node_index(node: IVec2, grid_nodes: i32) -> Option<usize> {
+    (node.min_element() >= 0 && node.max_element() < grid_nodes)
+        .then(|| (node.x + node.y * grid_nodes) as usize)
+}
@@ -3,6 +3,11 @@ pub mod cache_generation_task;
 pub mod country_cache;
 pub mod country_cache_position;
 pub mod country_data;
+pub mod eroded_terrain;
+pub mod erosion_data;
+pub mod flood_candidate;
 pub mod generation_cache;
+pub mod lake_data;
 pub mod path_data;
+pub mod river_data;
 pub mod structure_data;
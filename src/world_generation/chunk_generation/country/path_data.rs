@@ -14,6 +14,7 @@ use crate::world_generation::{
             a_star_candidate::AStarCandidate,
             country_cache::{COUNTRY_SIZE, CacheStore},
             country_cache_position::CountryPosition,
+            eroded_terrain::{ErodedTerrain, ErosionNeighbourhood},
             generation_cache::GenerationCacheItem,
         },
         noise::{
@@ -57,7 +58,7 @@ pub struct PathLine {
 }
 
 impl PathLine {
-    fn new(start: IVec2, end: IVec2, before: IVec2, after: IVec2) -> Self {
+    pub fn new(start: IVec2, end: IVec2, before: IVec2, after: IVec2) -> Self {
         let spline_one = start.as_vec2() + (end - before).as_vec2() / 2. / 3.;
         let spline_two = end.as_vec2() - (after - start).as_vec2() / 2. / 3.;
 
@@ -207,7 +208,7 @@ impl GenerationCacheItem<CountryPosition> for PathData {
         generation_options: &GenerationOptions,
         cache_store: Arc<CacheStore>,
     ) -> Self {
-        if !generation_options.generate_paths {
+        if !generation_options.path_settings.enabled {
             return Self { paths: vec![] };
         }
 
@@ -232,6 +233,11 @@ impl GenerationCacheItem<CountryPosition> for PathData {
             );
 
         let path_finding_lod = ChunkLod::Sixteenth;
+        let erosion = ErosionNeighbourhood::new(
+            key,
+            generation_options,
+            cache_store.clone(),
+        );
 
         Self {
             paths: vec![
@@ -241,6 +247,7 @@ impl GenerationCacheItem<CountryPosition> for PathData {
                     [*key, *top_country_pos],
                     path_finding_lod,
                     generation_options,
+                    &erosion,
                 ),
                 PathData::generate_path(
                     current_structure_cache.city_location,
@@ -248,6 +255,7 @@ impl GenerationCacheItem<CountryPosition> for PathData {
                     [*key, *right_country_pos],
                     path_finding_lod,
                     generation_options,
+                    &erosion,
                 ),
             ],
         }
@@ -261,12 +269,17 @@ impl PathData {
         country_positions: [IVec2; 2],
         path_finding_lod: ChunkLod,
         generation_options: &GenerationOptions,
+        erosion: &ErosionNeighbourhood,
     ) -> Path {
+        let path_settings = &generation_options.path_settings;
         start_pos /= path_finding_lod.multiplier_i32();
         end_pos /= path_finding_lod.multiplier_i32();
 
         let terrain_noise = FullCache::new(LodHeightAdjuster::new(
-            generation_options.get_terrain_noise(),
+            ErodedTerrain::new(
+                generation_options.get_terrain_noise(),
+                erosion.clone(),
+            ),
             path_finding_lod,
         ));
 
@@ -369,7 +382,7 @@ impl PathData {
 
                 let height_difference = (current_height - next_height).abs()
                     / path_finding_lod.multiplier_i32() as f64;
-                if height_difference > 0.65 {
+                if height_difference > path_settings.max_height_difference {
                     continue;
                 }
 
@@ -380,8 +393,9 @@ impl PathData {
 
                 let real_weight = real_weight
                     + weight
-                    + (height_difference * 30.) as i32
-                    + (steepness * 20.) as i32; //((total_steepness * 0.6).max(0.) * 10.0) as i32;
+                    + (height_difference * path_settings.height_difference_cost)
+                        as i32
+                    + (steepness * path_settings.steepness_cost) as i32; //((total_steepness * 0.6).max(0.) * 10.0) as i32;
                 if weights
                     .get(&next)
                     .map(|&weight| real_weight < weight)
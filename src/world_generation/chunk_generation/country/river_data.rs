@@ -0,0 +1,308 @@
+use std::{collections::BinaryHeap, sync::Arc};
+
+use bevy::prelude::*;
+use noise::NoiseFn;
+use rand::Rng;
+
+use crate::world_generation::{
+    chunk_generation::{
+        VOXEL_SIZE,
+        country::{
+            country_cache::{COUNTRY_SIZE, CacheStore},
+            country_cache_position::CountryPosition,
+            eroded_terrain::{ErodedTerrain, ErosionNeighbourhood},
+            flood_candidate::FloodCandidate,
+            generation_cache::GenerationCacheItem,
+            path_data::{Path, PathLine},
+        },
+    },
+    generation_options::{GenerationOptions, RiverSettings},
+    seed_derivation::{RIVER_SOURCE_SALT, derive_seed, positional_rng},
+};
+
+/// The distance in voxels between two nodes of the river grid.
+const RIVER_GRID_STEP: i32 = 128;
+/// The grid nodes along a side of a country, which owns the springs on them.
+const RIVER_GRID_NODES: i32 = COUNTRY_SIZE as i32 / RIVER_GRID_STEP;
+/// How many grid nodes past every side of the country the water is
+/// followed, so rivers flow on over the border.
+const FLOOD_MARGIN: i32 = 32;
+const FLOOD_GRID_NODES: i32 = RIVER_GRID_NODES + 2 * FLOOD_MARGIN + 1;
+
+const NEIGHBOURS: [IVec2; 8] = [
+    IVec2::new(1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(-1, 0),
+    IVec2::new(0, -1),
+    IVec2::new(1, 1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+    IVec2::new(1, -1),
+];
+
+pub struct River {
+    pub path: Path,
+    /// Half the width of the channel in voxels.
+    pub half_width: f32,
+}
+
+/// The rivers springing in a country.
+///
+/// Springs are scattered over the high ground of a coarse grid of the
+/// eroded terrain. From there the water runs downhill until it reaches the
+/// sea or a lake, and rivers that meet flow on together as a wider one.
+/// The water is followed up to `FLOOD_MARGIN` nodes past the border, so
+/// rivers run on into the countries around, and springs whose water
+/// doesn't reach the sea or a lake within that are left dry. Every river
+/// is split where another one flows into it, so its width stays the same
+/// along its path.
+#[derive(Default)]
+pub struct RiverData {
+    pub rivers: Vec<River>,
+}
+
+impl GenerationCacheItem<CountryPosition> for RiverData {
+    fn generate(
+        key: CountryPosition,
+        generation_options: &GenerationOptions,
+        cache_store: Arc<CacheStore>,
+    ) -> Self {
+        let river_settings = &generation_options.river_settings;
+        if !river_settings.enabled {
+            return Self::default();
+        }
+
+        let origin = *key * COUNTRY_SIZE as i32;
+        let flood_origin = origin - FLOOD_MARGIN * RIVER_GRID_STEP;
+        let column = |index: usize| {
+            flood_origin + node_position(index) * RIVER_GRID_STEP
+        };
+
+        let terrain_height = ErodedTerrain::new(
+            generation_options.get_terrain_noise(),
+            ErosionNeighbourhood::new(key, generation_options, cache_store),
+        );
+        let node_count = (FLOOD_GRID_NODES * FLOOD_GRID_NODES) as usize;
+        let heights = (0..node_count)
+            .map(|index| {
+                terrain_height.get(column(index).as_dvec2().to_array())
+            })
+            .collect::<Vec<_>>();
+
+        let water_settings = &generation_options.water_settings;
+        let sea_level = water_settings.sea_level as f64 / VOXEL_SIZE as f64;
+        let min_lake_depth =
+            water_settings.min_lake_depth as f64 / VOXEL_SIZE as f64;
+        let flood = flow_directions(&heights, sea_level);
+
+        // The sea, or a basin deep enough to hold a lake.
+        let is_water = |index: usize| {
+            let depth = flood.levels[index] - heights[index];
+            heights[index] < sea_level
+                || (water_settings.lakes
+                    && depth >= min_lake_depth
+                    && flood.levels[index] > sea_level)
+        };
+        let downstream = |index: usize| {
+            if is_water(index) {
+                None
+            } else {
+                flood.downstream[index]
+            }
+        };
+
+        // Every node drains to one reached before it, so walking them in
+        // that order tells which reach water before the edge of the grid.
+        let mut reaches_water = vec![false; node_count];
+        for index in flood.order.iter().copied() {
+            reaches_water[index] = is_water(index)
+                || downstream(index).is_some_and(|next| reaches_water[next]);
+        }
+
+        // Every spring adds to the flow of all nodes below it.
+        let source_seed =
+            derive_seed(generation_options.seed, RIVER_SOURCE_SALT);
+        let source_height = (river_settings.source_height / VOXEL_SIZE) as f64;
+        let mut flow = vec![0u32; node_count];
+        let sources = (0..node_count)
+            .filter(|index| {
+                let country_node =
+                    node_position(*index) - IVec2::splat(FLOOD_MARGIN);
+                country_node.min_element() >= 0
+                    && country_node.max_element() < RIVER_GRID_NODES
+                    && reaches_water[*index]
+                    && heights[*index] >= source_height
+                    && positional_rng(source_seed, column(*index))
+                        .random::<f64>()
+                        < river_settings.source_chance
+            })
+            .collect::<Vec<_>>();
+        for source in &sources {
+            let mut current = Some(*source);
+            while let Some(index) = current {
+                flow[index] += 1;
+                current = downstream(index);
+            }
+        }
+
+        let mut rivers = Vec::new();
+        let mut visited = vec![false; node_count];
+        for source in sources {
+            if visited[source] {
+                continue;
+            }
+            visited[source] = true;
+
+            let mut points = vec![column(source)];
+            let mut current = source;
+            while let Some(next) = downstream(current) {
+                points.push(column(next));
+
+                let joins = visited[next];
+                if joins || flow[next] != flow[current] {
+                    rivers.extend(River::new(
+                        std::mem::take(&mut points),
+                        flow[current],
+                        river_settings,
+                    ));
+                    if joins {
+                        break;
+                    }
+                    points.push(column(next));
+                }
+
+                visited[next] = true;
+                current = next;
+            }
+            rivers.extend(River::new(points, flow[current], river_settings));
+        }
+
+        Self { rivers }
+    }
+}
+
+impl River {
+    /// A river along `points`, if they make up at least one line.
+    fn new(
+        points: Vec<IVec2>,
+        flow: u32,
+        river_settings: &RiverSettings,
+    ) -> Option<Self> {
+        if points.len() < 2 {
+            return None;
+        }
+
+        let last = points.len() - 1;
+        let lines = (0..last)
+            .map(|index| {
+                let before = if index == 0 {
+                    points[0] * 2 - points[1]
+                } else {
+                    points[index - 1]
+                };
+                let after = if index + 1 == last {
+                    points[last] * 2 - points[last - 1]
+                } else {
+                    points[index + 2]
+                };
+                PathLine::new(points[index], points[index + 1], before, after)
+            })
+            .collect::<Vec<_>>();
+
+        let box_pos_start = lines
+            .iter()
+            .map(|line| line.box_pos_start)
+            .reduce(IVec2::min)?;
+        let box_pos_end = lines
+            .iter()
+            .map(|line| line.box_pos_end)
+            .reduce(IVec2::max)?;
+
+        let width = (river_settings.width
+            + river_settings.width_per_source * (flow.max(1) - 1) as f32)
+            .min(river_settings.max_width);
+
+        Some(Self {
+            path: Path {
+                lines,
+                box_pos_start,
+                box_pos_end,
+            },
+            half_width: width / 2. / VOXEL_SIZE,
+        })
+    }
+}
+
+/// How the water drains over the river grid.
+struct Flood {
+    /// The node every node drains to. Nodes below the sea drain nowhere.
+    downstream: Vec<Option<usize>>,
+    /// The height every node is flooded up to, its own height outside of
+    /// basins.
+    levels: Vec<f64>,
+    /// The nodes in the order they were reached, so every node comes after
+    /// the one it drains to.
+    order: Vec<usize>,
+}
+
+/// Drains the grid with a priority flood from its border. Each node drains
+/// to the node it was reached from, which leads over the lowest pass out
+/// of every basin.
+fn flow_directions(heights: &[f64], sea_level: f64) -> Flood {
+    let mut flood = Flood {
+        downstream: vec![None; heights.len()],
+        levels: heights.to_vec(),
+        order: Vec::with_capacity(heights.len()),
+    };
+    let mut reached = vec![false; heights.len()];
+    let mut shore = BinaryHeap::new();
+
+    for (index, height) in heights.iter().enumerate() {
+        let node = node_position(index);
+        if node.min_element() == 0 || node.max_element() == FLOOD_GRID_NODES - 1
+        {
+            reached[index] = true;
+            shore.push(FloodCandidate {
+                height: *height,
+                index,
+            });
+        }
+    }
+
+    while let Some(candidate) = shore.pop() {
+        flood.order.push(candidate.index);
+        let node = node_position(candidate.index);
+        for direction in NEIGHBOURS {
+            let Some(index) = node_index(node + direction) else {
+                continue;
+            };
+            if reached[index] {
+                continue;
+            }
+
+            reached[index] = true;
+            if heights[index] >= sea_level {
+                flood.downstream[index] = Some(candidate.index);
+            }
+            flood.levels[index] = heights[index].max(candidate.height);
+            shore.push(FloodCandidate {
+                height: flood.levels[index],
+                index,
+            });
+        }
+    }
+
+    flood
+}
+
+fn node_position(index: usize) -> IVec2 {
+    IVec2::new(
+        index as i32 % FLOOD_GRID_NODES,
+        index as i32 / FLOOD_GRID_NODES,
+    )
+}
+
+fn node_index(node: IVec2) -> Option<usize> {
+    (node.min_element() >= 0 && node.max_element() < FLOOD_GRID_NODES)
+        .then(|| (node.x + node.y * FLOOD_GRID_NODES) as usize)
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use bevy::prelude::*;
-use rand::{Rng, SeedableRng, rngs::StdRng};
+use rand::Rng;
 
 use crate::world_generation::{
     chunk_generation::country::{
@@ -10,6 +10,7 @@ use crate::world_generation::{
         generation_cache::GenerationCacheItem,
     },
     generation_options::GenerationOptions,
+    seed_derivation::{CITY_LOCATION_SALT, derive_seed, positional_rng},
 };
 
 #[derive(Default)]
@@ -23,16 +24,10 @@ impl GenerationCacheItem<CountryPosition> for StructureData {
         generation_options: &GenerationOptions,
         _country_cache: Arc<CacheStore>,
     ) -> Self {
-        let mut rng = StdRng::seed_from_u64(if key.x < 0 {
-            generation_options.seed.wrapping_sub(key.x.abs() as u64)
-        } else {
-            generation_options.seed.wrapping_add(key.x.abs() as u64)
-        });
-        let mut rng = StdRng::seed_from_u64(if key.x < 0 {
-            rng.random::<u64>().wrapping_sub(key.y.abs() as u64)
-        } else {
-            rng.random::<u64>().wrapping_add(key.y.abs() as u64)
-        });
+        let mut rng = positional_rng(
+            derive_seed(generation_options.seed, CITY_LOCATION_SALT),
+            *key,
+        );
 
         let min_offset = 100i32;
 
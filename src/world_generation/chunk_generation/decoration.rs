@@ -0,0 +1,197 @@
+use bevy::math::{IVec2, IVec3};
+
+use crate::world_generation::{
+    chunk_generation::{
+        CHUNK_SIZE, block_type::BlockType, chunk_lod::ChunkLod,
+        voxel_data::VoxelData,
+    },
+    generation_options::{Biome, DecorationSettings},
+    seed_derivation::{DECORATION_SALT, derive_seed, positional_seed},
+};
+
+/// How many columns the largest decoration reaches past the column it
+/// stands on. Decorations are decided this far around the chunk, so the
+/// ones standing in a neighbouring chunk still reach into this one.
+pub const DECORATION_REACH: i32 = 1;
+/// Width of the surfaces given to `decorate`.
+const SURFACES_SIZE: i32 = CHUNK_SIZE as i32 + 2 + 2 * DECORATION_REACH;
+
+/// The ground of a column, as left by the terrain fill.
+#[derive(Clone, Copy)]
+pub struct Surface {
+    /// The first block above the ground, in chunk coordinates. It can lie
+    /// outside of the chunk, decorations reaching into it are still placed.
+    pub y: i32,
+    pub biome: Biome,
+    pub steepness: f64,
+    pub is_path: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Decoration {
+    GrassTuft,
+    Flower,
+    Bush,
+    Boulder,
+}
+
+/// Scatters decorations over the surfaces of a chunk, one column of
+/// `surfaces` per column of the chunk including its border and
+/// `DECORATION_REACH` columns around it. Only air is replaced, so terrain
+/// and structures stay intact.
+///
+/// Small plants are only placed at full detail, bushes and boulders shrink
+/// to a single block at half detail and further chunks are left bare.
+/// Returns whether a decoration reaches above the chunk.
+pub fn decorate(
+    blocks: &mut VoxelData,
+    surfaces: &[Surface],
+    position: [i32; 3],
+    chunk_lod: ChunkLod,
+    settings: &DecorationSettings,
+    seed: u64,
+) -> bool {
+    if chunk_lod > ChunkLod::Half {
+        return false;
+    }
+
+    let seed = derive_seed(seed, DECORATION_SALT);
+    let mut reaches_above = false;
+
+    for x in -DECORATION_REACH..SURFACES_SIZE - DECORATION_REACH {
+        for z in -DECORATION_REACH..SURFACES_SIZE - DECORATION_REACH {
+            let surface = &surfaces[surface_index(x, z)];
+            let total = IVec2::new(
+                position[0] * CHUNK_SIZE as i32
+                    + x * chunk_lod.multiplier_i32(),
+                position[2] * CHUNK_SIZE as i32
+                    + z * chunk_lod.multiplier_i32(),
+            );
+            let hash = positional_seed(seed, total);
+
+            let Some(decoration) =
+                choose_decoration(surface, settings, unit(hash))
+            else {
+                continue;
+            };
+
+            let ground = IVec3::new(x, surface.y, z);
+            let shape = derive_seed(hash, 1);
+            let parts = match (decoration, chunk_lod) {
+                (Decoration::GrassTuft, ChunkLod::Full) => {
+                    vec![(IVec3::ZERO, BlockType::TallGrass)]
+                }
+                (Decoration::Flower, ChunkLod::Full) => {
+                    vec![(IVec3::ZERO, BlockType::Flower)]
+                }
+                (Decoration::Bush, ChunkLod::Full) => bush_parts(shape),
+                (Decoration::Boulder, ChunkLod::Full) => boulder_parts(shape),
+                (Decoration::Bush, _) => vec![(IVec3::ZERO, BlockType::Leaf)],
+                (Decoration::Boulder, _) => {
+                    vec![(IVec3::ZERO, BlockType::Stone)]
+                }
+                _ => continue,
+            };
+
+            for (offset, block) in parts {
+                reaches_above |= set_if_air(blocks, ground + offset, block);
+            }
+        }
+    }
+
+    reaches_above
+}
+
+fn choose_decoration(
+    surface: &Surface,
+    settings: &DecorationSettings,
+    mut roll: f64,
+) -> Option<Decoration> {
+    if surface.is_path {
+        return None;
+    }
+
+    let plants_grow = surface.biome == Biome::Grass
+        && surface.steepness <= settings.max_plant_steepness;
+    let plant_chance = |chance: f64| if plants_grow { chance } else { 0. };
+
+    [
+        (Decoration::Boulder, settings.boulder_chance),
+        (Decoration::Bush, plant_chance(settings.bush_chance)),
+        (Decoration::Flower, plant_chance(settings.flower_chance)),
+        (
+            Decoration::GrassTuft,
+            plant_chance(settings.grass_tuft_chance),
+        ),
+    ]
+    .into_iter()
+    .find(|(_, chance)| {
+        roll -= chance;
+        roll < 0.
+    })
+    .map(|(decoration, _)| decoration)
+}
+
+/// A leaf trunk of two blocks with some of its sides filled in.
+fn bush_parts(shape: u64) -> Vec<(IVec3, BlockType)> {
+    let mut parts =
+        vec![(IVec3::ZERO, BlockType::Leaf), (IVec3::Y, BlockType::Leaf)];
+    let sides = [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z];
+    for (index, side) in sides.into_iter().enumerate() {
+        if (shape >> index) & 1 == 1 {
+            parts.push((side, BlockType::Leaf));
+        }
+        if (shape >> (index + 4)) & 3 == 0 {
+            parts.push((side + IVec3::Y, BlockType::Leaf));
+        }
+    }
+    parts
+}
+
+/// A rock of two by two blocks that is sunk into the ground by one block,
+/// with some of its top blocks left out.
+fn boulder_parts(shape: u64) -> Vec<(IVec3, BlockType)> {
+    let mut parts = Vec::new();
+    for (index, (x, z)) in
+        [(0, 0), (1, 0), (0, 1), (1, 1)].into_iter().enumerate()
+    {
+        parts.push((IVec3::new(x, -1, z), BlockType::Stone));
+        parts.push((IVec3::new(x, 0, z), BlockType::Stone));
+        if (shape >> index) & 1 == 1 {
+            parts.push((IVec3::new(x, 1, z), BlockType::Stone));
+        }
+    }
+    parts
+}
+
+/// Returns whether the block lies above the chunk.
+fn set_if_air(
+    blocks: &mut VoxelData,
+    position: IVec3,
+    block: BlockType,
+) -> bool {
+    let size = CHUNK_SIZE as i32 + 2;
+    if position.y >= size {
+        return true;
+    }
+    if position.cmplt(IVec3::ZERO).any()
+        || position.x >= size
+        || position.z >= size
+        || blocks.get_block(position) != BlockType::Air
+    {
+        return false;
+    }
+
+    blocks.set_block(position, block);
+    false
+}
+
+/// The index in the surfaces of a column relative to the chunk.
+fn surface_index(x: i32, z: i32) -> usize {
+    ((x + DECORATION_REACH) * SURFACES_SIZE + z + DECORATION_REACH) as usize
+}
+
+/// Maps a hash to a value between 0 and 1.
+fn unit(hash: u64) -> f64 {
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
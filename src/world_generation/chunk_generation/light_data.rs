@@ -0,0 +1,396 @@
+use std::collections::VecDeque;
+
+use bevy::math::IVec3;
+
+use crate::{
+    utils::cartesian_product::cube_cartesian_product,
+    world_generation::chunk_generation::{
+        CHUNK_SIZE, chunk_lod::ChunkLod, voxel_data::VoxelData,
+    },
+};
+
+pub const MAX_LIGHT: u8 = 15;
+
+const SIZE: i32 = CHUNK_SIZE as i32 + 2;
+
+const DIRECTIONS: [IVec3; 6] = [
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Y,
+    IVec3::NEG_Y,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
+
+/// The light of a voxel, from the sky and from emissive blocks.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct LightLevel {
+    pub sky: u8,
+    pub block: u8,
+}
+
+impl LightLevel {
+    pub fn max(self, other: LightLevel) -> LightLevel {
+        LightLevel {
+            sky: self.sky.max(other.sky),
+            block: self.block.max(other.block),
+        }
+    }
+
+    fn get(self, channel: LightChannel) -> u8 {
+        match channel {
+            LightChannel::Sky => self.sky,
+            LightChannel::Block => self.block,
+        }
+    }
+
+    fn set(&mut self, channel: LightChannel, value: u8) {
+        match channel {
+            LightChannel::Sky => self.sky = value,
+            LightChannel::Block => self.block = value,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum LightChannel {
+    Sky,
+    Block,
+}
+
+const CHANNELS: [LightChannel; 2] = [LightChannel::Sky, LightChannel::Block];
+
+/// The light levels of a chunk including its border.
+///
+/// Sky light falls without dimming until something stops it, block light
+/// starts at emissive blocks. Both lose a level for every voxel they spread
+/// sideways. The terrain is a height map, so the top of a chunk with no
+/// chunk stacked on it is open sky. Light from the chunks around is taken
+/// in with `take_border`, which is also how a stacked chunk gets the sky
+/// light of the chunk above it.
+pub struct LightData {
+    levels: Vec<LightLevel>,
+    /// Whether sky light enters through the top of the chunk.
+    open_sky: bool,
+}
+
+impl LightData {
+    pub fn new(voxel_data: &VoxelData, open_sky: bool) -> Self {
+        let mut light_data = Self {
+            levels: vec![LightLevel::default(); (SIZE * SIZE * SIZE) as usize],
+            open_sky,
+        };
+
+        for channel in CHANNELS {
+            let mut queue = VecDeque::new();
+            for (x, y, z) in cube_cartesian_product(0..SIZE) {
+                let position = IVec3::new(x, y, z);
+                let source = light_data.source(voxel_data, position, channel);
+                if source > 0 {
+                    light_data.set_level(position, channel, source);
+                    queue.push_back(position);
+                }
+            }
+            light_data.spread(voxel_data, channel, queue);
+        }
+
+        light_data
+    }
+
+    pub fn get<T: Into<IVec3>>(&self, position: T) -> LightLevel {
+        self.levels[VoxelData::position_to_indexes(position)]
+    }
+
+    /// Relights the chunk after the block at `position` of `voxel_data` was
+    /// changed. Only the voxels the change can reach are touched.
+    pub fn update_block(&mut self, voxel_data: &VoxelData, position: IVec3) {
+        for channel in CHANNELS {
+            let mut queue = self.darken(voxel_data, channel, position);
+            let source = self.source(voxel_data, position, channel);
+            if source > self.level(position, channel) {
+                self.set_level(position, channel, source);
+                queue.push_back(position);
+            }
+            self.spread(voxel_data, channel, queue);
+        }
+    }
+
+    /// Takes in the light a neighbouring chunk of the same lod has at the
+    /// border of this chunk and spreads it into the chunk.
+    pub fn take_border(
+        &mut self,
+        voxel_data: &VoxelData,
+        neighbour: &BorderLight,
+    ) {
+        let mut queues = CHANNELS.map(|_| VecDeque::new());
+
+        for position in border_positions() {
+            let Some(light) =
+                neighbour.inner_light(voxel_data.local_to_world(position))
+            else {
+                continue;
+            };
+
+            for (channel, queue) in CHANNELS.into_iter().zip(&mut queues) {
+                if light.get(channel) > self.level(position, channel) {
+                    self.set_level(position, channel, light.get(channel));
+                    queue.push_back(position);
+                }
+            }
+        }
+
+        for (channel, queue) in CHANNELS.into_iter().zip(queues) {
+            self.spread(voxel_data, channel, queue);
+        }
+    }
+
+    /// The light of the outermost layers of the chunk, which is all its
+    /// neighbours need to take in its light.
+    pub fn border(&self, voxel_data: &VoxelData) -> BorderLight {
+        BorderLight {
+            origin: voxel_data.local_to_world(IVec3::ZERO),
+            lod: voxel_data.lod(),
+            sides: std::array::from_fn(|side| {
+                (0..BORDER_DEPTH)
+                    .flat_map(|depth| {
+                        (0..SIZE).flat_map(move |u| {
+                            (0..SIZE)
+                                .map(move |v| side_position(side, depth, u, v))
+                        })
+                    })
+                    .map(|position| self.get(position))
+                    .collect()
+            }),
+        }
+    }
+
+    /// The light a voxel gives off by itself.
+    fn source(
+        &self,
+        voxel_data: &VoxelData,
+        position: IVec3,
+        channel: LightChannel,
+    ) -> u8 {
+        let block = voxel_data.get_block(position);
+        match channel {
+            LightChannel::Sky if self.open_sky && position.y == SIZE - 1 => {
+                block
+                    .light_filter()
+                    .map_or(0, |filter| MAX_LIGHT.saturating_sub(filter))
+            }
+            LightChannel::Sky => 0,
+            LightChannel::Block => block.emitted_light(),
+        }
+    }
+
+    /// Floods light outwards from the voxels in `queue`.
+    fn spread(
+        &mut self,
+        voxel_data: &VoxelData,
+        channel: LightChannel,
+        mut queue: VecDeque<IVec3>,
+    ) {
+        while let Some(position) = queue.pop_front() {
+            let level = self.level(position, channel);
+            for direction in DIRECTIONS {
+                let neighbour = position + direction;
+                if !Self::contains(neighbour) {
+                    continue;
+                }
+                let Some(filter) =
+                    voxel_data.get_block(neighbour).light_filter()
+                else {
+                    continue;
+                };
+
+                let loss = if Self::is_falling_sky(channel, direction, level) {
+                    0
+                } else {
+                    1
+                };
+                let neighbour_level = level.saturating_sub(loss + filter);
+                if neighbour_level > self.level(neighbour, channel) {
+                    self.set_level(neighbour, channel, neighbour_level);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+    }
+
+    /// Removes the light that spread from `start`. Returns the lit voxels
+    /// around the darkened area, which have to spread into it again.
+    fn darken(
+        &mut self,
+        voxel_data: &VoxelData,
+        channel: LightChannel,
+        start: IVec3,
+    ) -> VecDeque<IVec3> {
+        let mut relight = VecDeque::new();
+        let mut queue = VecDeque::from([(start, self.level(start, channel))]);
+        self.set_level(start, channel, 0);
+
+        while let Some((position, level)) = queue.pop_front() {
+            for direction in DIRECTIONS {
+                let neighbour = position + direction;
+                if !Self::contains(neighbour) {
+                    continue;
+                }
+                let neighbour_level = self.level(neighbour, channel);
+                if neighbour_level == 0 {
+                    continue;
+                }
+
+                if neighbour_level < level
+                    || Self::is_falling_sky(channel, direction, level)
+                {
+                    self.set_level(neighbour, channel, 0);
+                    queue.push_back((neighbour, neighbour_level));
+
+                    let source = self.source(voxel_data, neighbour, channel);
+                    if source > 0 {
+                        self.set_level(neighbour, channel, source);
+                        relight.push_back(neighbour);
+                    }
+                } else {
+                    relight.push_back(neighbour);
+                }
+            }
+        }
+
+        relight
+    }
+
+    /// Whether full sky light goes straight down, which it does undimmed.
+    fn is_falling_sky(
+        channel: LightChannel,
+        direction: IVec3,
+        level: u8,
+    ) -> bool {
+        channel == LightChannel::Sky
+            && direction == IVec3::NEG_Y
+            && level == MAX_LIGHT
+    }
+
+    fn contains(position: IVec3) -> bool {
+        position.min_element() >= 0 && position.max_element() < SIZE
+    }
+
+    fn level(&self, position: IVec3, channel: LightChannel) -> u8 {
+        self.get(position).get(channel)
+    }
+
+    fn set_level(&mut self, position: IVec3, channel: LightChannel, value: u8) {
+        self.levels[VoxelData::position_to_indexes(position)]
+            .set(channel, value);
+    }
+}
+
+/// How many layers of a chunk `BorderLight` keeps on every side: the
+/// border, which neighbours light, and the layer inside of it, which lights
+/// the border of neighbours.
+const BORDER_DEPTH: i32 = 2;
+
+/// The light of the outermost layers of a chunk. Neighbouring chunks take
+/// it in with `LightData::take_border`.
+#[derive(PartialEq)]
+pub struct BorderLight {
+    /// The world voxel at the corner of voxel `(0, 0, 0)`.
+    origin: IVec3,
+    lod: ChunkLod,
+    /// The layers of every side, low and high along x, then along y and
+    /// z, from the outermost one inwards.
+    sides: [Vec<LightLevel>; 6],
+}
+
+impl BorderLight {
+    /// Whether the layer inside of the border of this chunk is brighter
+    /// than the border of `other` anywhere, so `other` has to take in the
+    /// light of this chunk.
+    pub fn brightens(&self, other: &BorderLight) -> bool {
+        other.lod == self.lod
+            && self.touches(other)
+            && border_positions().any(|position| {
+                let Some(inner) = self
+                    .inner_light(other.origin + position * other.multiplier())
+                else {
+                    return false;
+                };
+                let border = other.get(position);
+                inner.sky > border.sky || inner.block > border.block
+            })
+    }
+
+    /// The light of the world voxel `world_position` if it lies in the
+    /// layer inside of the border of this chunk.
+    fn inner_light(&self, world_position: IVec3) -> Option<LightLevel> {
+        let position = (world_position - self.origin)
+            .div_euclid(IVec3::splat(self.multiplier()));
+        (position.min_element() >= 1
+            && position.max_element() <= CHUNK_SIZE as i32
+            && (position.min_element() == 1
+                || position.max_element() == CHUNK_SIZE as i32))
+            .then(|| self.get(position))
+    }
+
+    /// Whether the chunks, including their borders, overlap.
+    fn touches(&self, other: &BorderLight) -> bool {
+        let end = |border: &BorderLight| {
+            border.origin + IVec3::splat(SIZE * border.multiplier())
+        };
+        self.origin.cmplt(end(other)).all()
+            && other.origin.cmplt(end(self)).all()
+    }
+
+    /// The light of a voxel in the outermost layers of the chunk.
+    fn get(&self, position: IVec3) -> LightLevel {
+        for side in 0..6 {
+            let axis = side / 2;
+            let depth = if side % 2 == 0 {
+                position[axis]
+            } else {
+                SIZE - 1 - position[axis]
+            };
+            if depth < BORDER_DEPTH {
+                let [u, v] = other_axes(axis).map(|axis| position[axis]);
+                return self.sides[side]
+                    [((depth * SIZE + u) * SIZE + v) as usize];
+            }
+        }
+
+        panic!("{position} isn't in the outermost layers of the chunk");
+    }
+
+    fn multiplier(&self) -> i32 {
+        self.lod.multiplier_i32()
+    }
+}
+
+/// The voxels of the border of a chunk. Voxels on an edge come up more
+/// than once.
+fn border_positions() -> impl Iterator<Item = IVec3> {
+    (0..6).flat_map(|side| {
+        (0..SIZE).flat_map(move |u| {
+            (0..SIZE).map(move |v| side_position(side, 0, u, v))
+        })
+    })
+}
+
+/// The voxel `depth` layers inside of a side of the chunk, at `u` and `v`
+/// along the other two axes.
+fn side_position(side: usize, depth: i32, u: i32, v: i32) -> IVec3 {
+    let axis = side / 2;
+    let mut position = IVec3::ZERO;
+    position[axis] = if side % 2 == 1 {
+        SIZE - 1 - depth
+    } else {
+        depth
+    };
+    let [u_axis, v_axis] = other_axes(axis);
+    position[u_axis] = u;
+    position[v_axis] = v;
+    position
+}
+
+fn other_axes(axis: usize) -> [usize; 2] {
+    [(axis + 1) % 3, (axis + 2) % 3]
+}
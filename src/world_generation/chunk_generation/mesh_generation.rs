@@ -1,21 +1,61 @@
-use crate::world_generation::array_texture::ATTRIBUTE_TEXTURE_ID;
+use crate::utils::cartesian_product::cube_cartesian_product;
+use crate::world_generation::array_texture::ATTRIBUTE_PACKED_VERTEX;
+use crate::world_generation::chunk_generation::block_shape::{
+    BlockShape, Facing, HORIZONTAL_DIRECTIONS, ShapeBox,
+};
 use crate::world_generation::chunk_generation::block_type::{
     BlockFace, BlockType,
 };
 use crate::world_generation::chunk_generation::chunk_lod::ChunkLod;
+use crate::world_generation::chunk_generation::light_data::{
+    LightData, LightLevel, MAX_LIGHT,
+};
+use crate::world_generation::chunk_generation::packed_vertex::{
+    VertexFace, pack_vertex,
+};
 use crate::world_generation::chunk_generation::voxel_data::VoxelData;
+use crate::world_generation::chunk_generation::voxel_generation::ChunkColumns;
 use crate::world_generation::chunk_generation::{CHUNK_SIZE, VOXEL_SIZE};
 use bevy::prelude::*;
 use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::primitives::Aabb;
 use bevy::render::render_asset::RenderAssetUsages;
 
+/// How many sixteenths of its voxel the surface of a fluid reaches up.
+const FLUID_SURFACE_SIXTEENTHS: i32 = 14;
+const FLUID_SURFACE_HEIGHT: f32 =
+    FLUID_SURFACE_SIXTEENTHS as f32 / ShapeBox::SIXTEENTHS as f32;
+/// The occlusion of a quad no neighbour darkens.
+const NO_OCCLUSION: [u8; 4] = [3; 4];
+
+/// From this lod on chunks are meshed straight from their column heights
+/// instead of from voxels.
+pub const HEIGHTMAP_LOD: ChunkLod = ChunkLod::Sixteenth;
+
+const FACES: [(IVec3, BlockFace); 6] = [
+    (IVec3::X, BlockFace::Right),
+    (IVec3::NEG_X, BlockFace::Left),
+    (IVec3::Z, BlockFace::Front),
+    (IVec3::NEG_Z, BlockFace::Back),
+    (IVec3::Y, BlockFace::Top),
+    (IVec3::NEG_Y, BlockFace::Bottom),
+];
+
 pub struct MeshResult {
     pub opaque_mesh: Option<Mesh>,
     pub transparent_mesh: Option<Mesh>,
+    pub fluid_mesh: Option<Mesh>,
+    /// Places the meshes in their chunk. Their vertices are packed in
+    /// voxels of the chunk, so this scales them into world units.
+    pub transform: Transform,
+    /// The bounds of the meshes, which Bevy can't read from packed
+    /// vertices.
+    pub aabb: Aabb,
 }
 
 pub fn generate_mesh(
     voxel_data: &VoxelData,
+    light_data: &LightData,
     min_height: i32,
     chunk_lod: ChunkLod,
 ) -> MeshResult {
@@ -25,40 +65,63 @@ pub fn generate_mesh(
             BlockType::Grass,
             BlockType::Log,
             BlockType::Snow,
+            BlockType::StoneSlab,
+            BlockType::StoneStairs(Facing::Right),
+            BlockType::StoneStairs(Facing::Left),
+            BlockType::StoneStairs(Facing::Front),
+            BlockType::StoneStairs(Facing::Back),
+            BlockType::LogFence,
+            BlockType::Lantern,
+            BlockType::Dirt,
+            BlockType::Slate,
+            BlockType::Sandstone,
+            BlockType::CoalOre,
+            BlockType::IronOre,
+            BlockType::GoldOre,
         ],
         voxel_data,
-        min_height,
+        light_data,
         chunk_lod,
     );
 
     let transparent_mesh = get_mesh_for_blocks(
-        &[BlockType::Leaf],
+        &[
+            BlockType::Leaf,
+            BlockType::TallGrass,
+            BlockType::Flower,
+            BlockType::GlassPane,
+        ],
         voxel_data,
-        min_height,
+        light_data,
+        chunk_lod,
+    );
+
+    let fluid_mesh = get_mesh_for_blocks(
+        &[BlockType::Water],
+        voxel_data,
+        light_data,
         chunk_lod,
     );
 
     MeshResult {
         opaque_mesh,
         transparent_mesh,
+        fluid_mesh,
+        transform: chunk_transform(min_height, chunk_lod),
+        aabb: Aabb::from_min_max(
+            Vec3::ZERO,
+            Vec3::splat(CHUNK_SIZE as f32 + 1.),
+        ),
     }
 }
 
 fn get_mesh_for_blocks(
     blocks: &[BlockType],
     voxel_data: &VoxelData,
-    min_height: i32,
+    light_data: &LightData,
     chunk_lod: ChunkLod,
 ) -> Option<Mesh> {
-    let mut mesh =
-        Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::all());
-
-    let mut positions: Vec<[f32; 3]> = Vec::new();
-    let mut normals: Vec<[f32; 3]> = Vec::new();
-    let mut triangles: Vec<[u32; 3]> = Vec::new();
-    let mut uvs: Vec<[f32; 2]> = Vec::new();
-    let mut texture_ids: Vec<u32> = Vec::new();
-    let mut colors: Vec<[f32; 4]> = Vec::new();
+    let mut mesh_data = MeshData::default();
 
     let mut generate_sides = |direction: IVec3, block_face: BlockFace| {
         for i in 1..CHUNK_SIZE + 1 {
@@ -82,15 +145,17 @@ fn get_mesh_for_blocks(
                         [width_pos as usize - 1, height_pos as usize - 1];
                     if done_faces[face_x][face_y]
                         || !blocks.contains(&current_block)
+                        || current_block.shape() != BlockShape::Cube
                         || voxel_data
                             .get_block(current_pos + direction)
-                            .is_covering_for(&current_block)
+                            .is_covering_for(&current_block, -direction)
                     {
                         continue;
                     }
 
                     let ambient_occlusion = voxel_data
                         .get_ambiant_occlusion(current_pos, direction);
+                    let light = light_data.get(current_pos + direction);
 
                     let mut height = 1;
                     let mut width = 1;
@@ -105,11 +170,14 @@ fn get_mesh_for_blocks(
                             .get_block(
                                 current_pos + (height_dir * height) + direction,
                             )
-                            .is_covering_for(&current_block)
+                            .is_covering_for(&current_block, -direction)
                         && voxel_data.get_ambiant_occlusion(
                             current_pos + (height_dir * height),
                             direction,
                         ) == ambient_occlusion
+                        && light_data.get(
+                            current_pos + (height_dir * height) + direction,
+                        ) == light
                     {
                         height += 1;
                     }
@@ -133,13 +201,19 @@ fn get_mesh_for_blocks(
                                             + (height_dir * height as i32)
                                             + direction,
                                     )
-                                    .is_covering_for(&current_block)
+                                    .is_covering_for(&current_block, -direction)
                                 && voxel_data.get_ambiant_occlusion(
                                     current_pos
                                         + (width_dir * width as i32)
                                         + (height_dir * height as i32),
                                     direction,
                                 ) == ambient_occlusion
+                                && light_data.get(
+                                    current_pos
+                                        + (width_dir * width as i32)
+                                        + (height_dir * height as i32)
+                                        + direction,
+                                ) == light
                         })
                     {
                         width += 1;
@@ -151,58 +225,42 @@ fn get_mesh_for_blocks(
                         }
                     }
 
-                    let uv_start = Vec2::ZERO;
-                    let uv_end = Vec2::new(width as f32, height as f32)
-                        * chunk_lod.multiplier_f32();
-
-                    uvs.extend_from_slice(&[
-                        [uv_end.x, uv_end.y],
-                        [uv_start.x, uv_end.y],
-                        [uv_start.x, uv_start.y],
-                        [uv_end.x, uv_start.y],
-                    ]);
-
                     let height = height as f32 - 1.;
                     let width = width as f32 - 1.;
 
-                    let positions_count = positions.len() as u32;
+                    let positions_count = mesh_data.vertices.len() as u32;
 
                     let vertex_pos = current_pos.as_vec3();
 
                     let direction_adder =
                         direction * direction.min_element().abs();
 
-                    let vecs = &[
+                    let vecs = [
                         Vec3::new(0.5, -0.5, -0.5),
                         Vec3::new(0.5, -0.5, 0.5 + width),
                         Vec3::new(0.5, 0.5 + height, 0.5 + width),
                         Vec3::new(0.5, 0.5 + height, -0.5),
                     ];
 
-                    positions.extend_from_slice(
-                        vecs.into_iter()
-                            .map(|e| {
-                                (vertex_pos
-                                    + rotate_into_direction(*e, direction)
-                                    + direction_adder.as_vec3())
-                                .to_array()
-                            })
-                            .collect::<Vec<_>>()
-                            .as_slice(),
-                    );
-
-                    normals.extend_from_slice(&[
-                        direction.as_vec3().to_array(),
-                        direction.as_vec3().to_array(),
-                        direction.as_vec3().to_array(),
-                        direction.as_vec3().to_array(),
-                    ]);
-
+                    let face = VertexFace::from_direction(direction);
                     let texture_id = current_block.get_texture_id(block_face);
 
-                    texture_ids.extend_from_slice(&[
-                        texture_id, texture_id, texture_id, texture_id,
-                    ]);
+                    mesh_data.vertices.extend(
+                        vecs.into_iter().zip(ambient_occlusion.corners()).map(
+                            |(e, occlusion)| {
+                                pack_vertex(
+                                    vertex_pos
+                                        + rotate_into_direction(e, direction)
+                                        + direction_adder.as_vec3(),
+                                    face,
+                                    occlusion,
+                                    texture_id,
+                                    light,
+                                    chunk_lod,
+                                )
+                            },
+                        ),
+                    );
 
                     let mut invert = !direction.min_element() < 0;
 
@@ -211,12 +269,8 @@ fn get_mesh_for_blocks(
                         invert = !invert;
                     }
 
-                    colors.extend_from_slice(
-                        ambient_occlusion.get_colors().as_slice(),
-                    );
-
                     if ambient_occlusion.turn_quad() {
-                        triangles.extend_from_slice(&[
+                        mesh_data.triangles.extend_from_slice(&[
                             [
                                 positions_count + 0,
                                 positions_count + if invert { 1 } else { 2 },
@@ -229,7 +283,7 @@ fn get_mesh_for_blocks(
                             ],
                         ]);
                     } else {
-                        triangles.extend_from_slice(&[
+                        mesh_data.triangles.extend_from_slice(&[
                             [
                                 positions_count + 0,
                                 positions_count + if invert { 1 } else { 3 },
@@ -254,40 +308,421 @@ fn get_mesh_for_blocks(
     generate_sides(IVec3::Y, BlockFace::Top);
     generate_sides(IVec3::NEG_Y, BlockFace::Bottom);
 
-    if triangles.is_empty() {
-        return None;
+    for (x, y, z) in cube_cartesian_product(1..CHUNK_SIZE as i32 + 1) {
+        let position = IVec3::new(x, y, z);
+        let block = voxel_data.get_block(position);
+        if !blocks.contains(&block) {
+            continue;
+        }
+
+        match block.shape() {
+            BlockShape::Empty | BlockShape::Cube => {}
+            BlockShape::Cross => mesh_data.add_cross(
+                position,
+                block,
+                light_data.get(position),
+                chunk_lod,
+            ),
+            BlockShape::Fluid => {
+                let is_surface =
+                    voxel_data.get_block(position + IVec3::Y) != block;
+                let shape_box = ShapeBox::from_sixteenths(
+                    [0, 0, 0],
+                    [
+                        ShapeBox::SIXTEENTHS,
+                        if is_surface {
+                            FLUID_SURFACE_SIXTEENTHS
+                        } else {
+                            ShapeBox::SIXTEENTHS
+                        },
+                        ShapeBox::SIXTEENTHS,
+                    ],
+                );
+                mesh_data.add_box(
+                    voxel_data, light_data, position, block, shape_box,
+                    chunk_lod,
+                );
+            }
+            shape => {
+                let connections = HORIZONTAL_DIRECTIONS
+                    .into_iter()
+                    .filter(|direction| {
+                        block.connects_to(
+                            &voxel_data.get_block(position + *direction),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                for shape_box in shape.boxes(&connections) {
+                    mesh_data.add_box(
+                        voxel_data, light_data, position, block, shape_box,
+                        chunk_lod,
+                    );
+                }
+            }
+        }
+    }
+
+    mesh_data.into_mesh()
+}
+
+/// Meshes a chunk as a heightfield: a top face for every column, walls
+/// down to lower neighbours and the surface of the water above it. The
+/// chunk reaches over the whole height of its columns, so nothing is
+/// stacked on it. Structures and decorations are too small to be seen
+/// this far away and are left out.
+pub fn generate_heightmap_mesh(
+    columns: &ChunkColumns,
+    chunk_lod: ChunkLod,
+) -> MeshResult {
+    let light = LightLevel {
+        sky: MAX_LIGHT,
+        block: 0,
+    };
+
+    // Heights are kept above the lowest column, where the mesh starts.
+    let min_height = (0..CHUNK_SIZE + 2)
+        .flat_map(|x| (0..CHUNK_SIZE + 2).map(move |z| (x, z)))
+        .map(|(x, z)| columns.get(x, z).height as i32)
+        .min()
+        .unwrap_or_default()
+        - 1;
+    let top = |x: usize, z: usize| columns.get(x, z).height as i32 - min_height;
+    let mut max_top = 0;
+
+    let mut opaque_data = MeshData::default();
+    let mut fluid_data = MeshData::default();
+
+    for x in 1..CHUNK_SIZE + 1 {
+        // Columns in a row with the same top are merged into one face.
+        let mut z = 1;
+        while z < CHUNK_SIZE + 1 {
+            let column = columns.get(x, z);
+            let texture_id =
+                column.surface_block().get_texture_id(BlockFace::Top);
+            let mut length = 1;
+            while z + length < CHUNK_SIZE + 1
+                && top(x, z + length) == top(x, z)
+                && columns
+                    .get(x, z + length)
+                    .surface_block()
+                    .get_texture_id(BlockFace::Top)
+                    == texture_id
+            {
+                length += 1;
+            }
+
+            let min = Vec3::new(
+                x as f32 - 0.5,
+                top(x, z) as f32 - 0.5,
+                z as f32 - 0.5,
+            );
+            let size = Vec3::new(1., 0., length as f32);
+            opaque_data.add_quad(
+                face_corners(size, IVec3::Y).map(|corner| min + corner),
+                NO_OCCLUSION,
+                VertexFace::Top,
+                texture_id,
+                light,
+                chunk_lod,
+            );
+
+            z += length;
+        }
+
+        for z in 1..CHUNK_SIZE + 1 {
+            let column = columns.get(x, z);
+            let column_top = top(x, z);
+
+            for direction in HORIZONTAL_DIRECTIONS {
+                let neighbour_top = top(
+                    (x as i32 + direction.x) as usize,
+                    (z as i32 + direction.z) as usize,
+                );
+                if neighbour_top >= column_top {
+                    continue;
+                }
+
+                // The top voxel shows the side of the surface block, the
+                // ones below are stone.
+                let walls = [
+                    (
+                        neighbour_top,
+                        column_top - 1,
+                        BlockType::Stone.get_texture_id(BlockFace::Front),
+                    ),
+                    (
+                        (column_top - 1).max(neighbour_top),
+                        column_top,
+                        column.surface_block().get_texture_id(BlockFace::Front),
+                    ),
+                ];
+                for (bottom, wall_top, texture_id) in walls {
+                    if bottom >= wall_top {
+                        continue;
+                    }
+
+                    let min = Vec3::new(
+                        x as f32 - 0.5,
+                        bottom as f32 - 0.5,
+                        z as f32 - 0.5,
+                    );
+                    let size = Vec3::new(1., (wall_top - bottom) as f32, 1.);
+                    opaque_data.add_quad(
+                        face_corners(size, direction)
+                            .map(|corner| min + corner),
+                        NO_OCCLUSION,
+                        VertexFace::from_direction(direction),
+                        texture_id,
+                        light,
+                        chunk_lod,
+                    );
+                }
+            }
+
+            max_top = max_top.max(column_top);
+
+            let water_top = column.water_level as i32 - min_height;
+            if water_top > column_top {
+                max_top = max_top.max(water_top);
+
+                let min = Vec3::new(
+                    x as f32 - 0.5,
+                    water_top as f32 - 1.5 + FLUID_SURFACE_HEIGHT,
+                    z as f32 - 0.5,
+                );
+                fluid_data.add_quad(
+                    face_corners(Vec3::new(1., 0., 1.), IVec3::Y)
+                        .map(|corner| min + corner),
+                    NO_OCCLUSION,
+                    VertexFace::Top,
+                    BlockType::Water.get_texture_id(BlockFace::Top),
+                    light,
+                    chunk_lod,
+                );
+            }
+        }
     }
 
-    for position in positions.iter_mut() {
-        position[0] =
-            (position[0] - 0.5) * VOXEL_SIZE * chunk_lod.multiplier_f32()
-                + VOXEL_SIZE;
-        position[1] = (position[1] + min_height as f32 - 0.5)
-            * VOXEL_SIZE
-            * chunk_lod.multiplier_f32()
-            + VOXEL_SIZE;
-        position[2] =
-            (position[2] - 0.5) * VOXEL_SIZE * chunk_lod.multiplier_f32()
-                + VOXEL_SIZE;
+    MeshResult {
+        opaque_mesh: opaque_data.into_mesh(),
+        transparent_mesh: None,
+        fluid_mesh: fluid_data.into_mesh(),
+        transform: chunk_transform(min_height, chunk_lod),
+        aabb: Aabb::from_min_max(
+            Vec3::ZERO,
+            Vec3::new(
+                CHUNK_SIZE as f32 + 1.,
+                max_top as f32 + 1.,
+                CHUNK_SIZE as f32 + 1.,
+            ),
+        ),
     }
+}
+
+/// Scales the voxels of a chunk into world units, with its lowest voxel
+/// at `min_height`.
+fn chunk_transform(min_height: i32, chunk_lod: ChunkLod) -> Transform {
+    let voxel_size = VOXEL_SIZE * chunk_lod.multiplier_f32();
+    Transform::from_translation(Vec3::new(
+        VOXEL_SIZE - voxel_size / 2.,
+        (min_height as f32 - 0.5) * voxel_size + VOXEL_SIZE,
+        VOXEL_SIZE - voxel_size / 2.,
+    ))
+    .with_scale(Vec3::splat(voxel_size))
+}
+
+#[derive(Default)]
+struct MeshData {
+    vertices: Vec<[u32; 2]>,
+    triangles: Vec<[u32; 3]>,
+}
+
+impl MeshData {
+    /// Builds the mesh, `None` if there is nothing to draw.
+    fn into_mesh(self) -> Option<Mesh> {
+        if self.triangles.is_empty() {
+            return None;
+        }
+
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::all(),
+        );
 
-    let mut mesh_triangles: Vec<u32> = Vec::new();
+        let mut mesh_triangles: Vec<u32> = Vec::new();
 
-    for triangle in &triangles {
-        mesh_triangles.push(triangle[0]);
-        mesh_triangles.push(triangle[1]);
-        mesh_triangles.push(triangle[2]);
+        for triangle in &self.triangles {
+            mesh_triangles.push(triangle[0]);
+            mesh_triangles.push(triangle[1]);
+            mesh_triangles.push(triangle[2]);
+        }
+
+        mesh.insert_attribute(ATTRIBUTE_PACKED_VERTEX, self.vertices);
+
+        mesh.insert_indices(Indices::U32(mesh_triangles));
+
+        Some(mesh)
+    }
+
+    /// Adds a quad whose corners are counter-clockwise seen from its front,
+    /// with the ambient occlusion of every corner. Like the faces of cubes,
+    /// it is split along its darker diagonal so the shading stays even.
+    fn add_quad(
+        &mut self,
+        corners: [Vec3; 4],
+        occlusion: [u8; 4],
+        face: VertexFace,
+        texture_id: u32,
+        light: LightLevel,
+        chunk_lod: ChunkLod,
+    ) {
+        let positions_count = self.vertices.len() as u32;
+
+        self.vertices.extend(corners.into_iter().zip(occlusion).map(
+            |(corner, occlusion)| {
+                pack_vertex(
+                    corner, face, occlusion, texture_id, light, chunk_lod,
+                )
+            },
+        ));
+        let [a, b, c, d] = [0, 1, 2, 3].map(|index| positions_count + index);
+        if occlusion[0] + occlusion[2] > occlusion[1] + occlusion[3] {
+            self.triangles.extend_from_slice(&[[a, b, d], [b, c, d]]);
+        } else {
+            self.triangles.extend_from_slice(&[[a, b, c], [a, c, d]]);
+        }
+    }
+
+    /// Adds the faces of a box of a shaped block, leaving out the ones the
+    /// neighbouring blocks cover. Faces are lit by the brighter of the
+    /// block's voxel and the one they look into. Faces on the side of the
+    /// voxel are darkened like the faces of cubes, with the occlusion at
+    /// the corners of the voxel's face blended over the box's face.
+    fn add_box(
+        &mut self,
+        voxel_data: &VoxelData,
+        light_data: &LightData,
+        position: IVec3,
+        block: BlockType,
+        shape_box: ShapeBox,
+        chunk_lod: ChunkLod,
+    ) {
+        let voxel_min = position.as_vec3() - Vec3::splat(0.5);
+        let box_min = shape_box.min_corner();
+        let size = shape_box.size();
+
+        for (direction, block_face) in FACES {
+            let is_on_side = shape_box.reaches_side(direction);
+            if is_on_side
+                && voxel_data
+                    .get_block(position + direction)
+                    .is_covering_for(&block, -direction)
+            {
+                continue;
+            }
+
+            let corners =
+                face_corners(size, direction).map(|corner| box_min + corner);
+            let occlusion = if is_on_side {
+                let voxel_occlusion = voxel_data
+                    .get_ambiant_occlusion(position, direction)
+                    .corners()
+                    .map(|occlusion| occlusion as f32);
+                let right = rotate_into_direction(Vec3::Y, direction);
+                let front = rotate_into_direction(Vec3::Z, direction);
+                corners.map(|corner| {
+                    let [r, f] = [corner.dot(right), corner.dot(front)];
+                    let [c1, c2, c3, c4] = voxel_occlusion;
+                    (c1 * (1. - r) * (1. - f)
+                        + c2 * (1. - r) * f
+                        + c3 * r * f
+                        + c4 * r * (1. - f))
+                        .round() as u8
+                })
+            } else {
+                NO_OCCLUSION
+            };
+
+            self.add_quad(
+                corners.map(|corner| voxel_min + corner),
+                occlusion,
+                VertexFace::from_direction(direction),
+                block.get_texture_id(block_face),
+                light_data
+                    .get(position)
+                    .max(light_data.get(position + direction)),
+                chunk_lod,
+            );
+        }
+    }
+
+    /// Adds two crossing quads that can be seen from both sides.
+    fn add_cross(
+        &mut self,
+        position: IVec3,
+        block: BlockType,
+        light: LightLevel,
+        chunk_lod: ChunkLod,
+    ) {
+        let voxel_min = position.as_vec3() - Vec3::splat(0.5);
+        let texture_id = block.get_texture_id(BlockFace::Front);
+
+        for (start, end) in
+            [(Vec3::ZERO, Vec3::new(1., 0., 1.)), (Vec3::Z, Vec3::X)]
+        {
+            let corners = [start, end, end + Vec3::Y, start + Vec3::Y]
+                .map(|corner| voxel_min + corner);
+            let [a, b, c, d] = corners;
+
+            self.add_quad(
+                corners,
+                NO_OCCLUSION,
+                VertexFace::Cross,
+                texture_id,
+                light,
+                chunk_lod,
+            );
+            self.add_quad(
+                [b, a, d, c],
+                NO_OCCLUSION,
+                VertexFace::Cross,
+                texture_id,
+                light,
+                chunk_lod,
+            );
+        }
     }
+}
 
-    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
-    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
-    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
-    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
-    mesh.insert_attribute(ATTRIBUTE_TEXTURE_ID, texture_ids);
+/// The corners of the face of a box of `size` pointing in `direction`,
+/// counter-clockwise seen from the front and relative to the box's minimum.
+fn face_corners(size: Vec3, direction: IVec3) -> [Vec3; 4] {
+    let (u, v) = face_axes(direction);
+    let base = if direction.max_element() > 0 {
+        direction.as_vec3() * size
+    } else {
+        Vec3::ZERO
+    };
 
-    mesh.insert_indices(Indices::U32(mesh_triangles));
+    [
+        base,
+        base + u * size,
+        base + (u + v) * size,
+        base + v * size,
+    ]
+}
 
-    Some(mesh)
+/// Two axes along a face pointing in `direction`, whose cross product is
+/// the direction.
+fn face_axes(direction: IVec3) -> (Vec3, Vec3) {
+    match direction {
+        IVec3::X => (Vec3::Y, Vec3::Z),
+        IVec3::NEG_X => (Vec3::Z, Vec3::Y),
+        IVec3::Y => (Vec3::Z, Vec3::X),
+        IVec3::NEG_Y => (Vec3::X, Vec3::Z),
+        IVec3::Z => (Vec3::X, Vec3::Y),
+        _ => (Vec3::Y, Vec3::X),
+    }
 }
 
 pub fn rotate_into_direction<T: Vec3Swizzles>(
@@ -1,11 +1,20 @@
 use fastnoise_lite::{CellularDistanceFunction, CellularReturnType, FastNoiseLite, NoiseType};
 use noise::{NoiseFn, Seedable};
+use serde::{Deserialize, Serialize};
+
+use crate::world_generation::chunk_generation::noise::noise_grid::GridNoiseFn;
 
 pub struct Cellular {
     fast_noise: FastNoiseLite,
 }
 
 impl Cellular {
+    pub const DEFAULT_FREQUENCY: f64 = 0.01;
+    pub const DEFAULT_JITTER: f64 = 1.;
+    pub const DEFAULT_DISTANCE_FUNCTION: CellularDistance =
+        CellularDistance::EuclideanSq;
+    pub const DEFAULT_RETURN_TYPE: CellularReturn = CellularReturn::Distance;
+
     pub fn new(seed: i32) -> Self {
         let mut noise = FastNoiseLite::with_seed(seed);
         noise.set_noise_type(Some(NoiseType::Cellular));
@@ -61,3 +70,72 @@ impl NoiseFn<f64, 2> for Cellular {
         ((noise + 1.) * 0.5).into()
     }
 }
+
+impl GridNoiseFn for Cellular {}
+
+/// Serializable mirror of [`CellularDistanceFunction`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CellularDistance {
+    Euclidean,
+    EuclideanSq,
+    Manhattan,
+    Hybrid,
+}
+
+impl CellularDistance {
+    pub const ALL: [Self; 4] = [
+        Self::Euclidean,
+        Self::EuclideanSq,
+        Self::Manhattan,
+        Self::Hybrid,
+    ];
+}
+
+impl From<CellularDistance> for CellularDistanceFunction {
+    fn from(value: CellularDistance) -> Self {
+        match value {
+            CellularDistance::Euclidean => Self::Euclidean,
+            CellularDistance::EuclideanSq => Self::EuclideanSq,
+            CellularDistance::Manhattan => Self::Manhattan,
+            CellularDistance::Hybrid => Self::Hybrid,
+        }
+    }
+}
+
+/// Serializable mirror of [`CellularReturnType`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CellularReturn {
+    CellValue,
+    Distance,
+    Distance2,
+    Distance2Add,
+    Distance2Sub,
+    Distance2Mul,
+    Distance2Div,
+}
+
+impl CellularReturn {
+    pub const ALL: [Self; 7] = [
+        Self::CellValue,
+        Self::Distance,
+        Self::Distance2,
+        Self::Distance2Add,
+        Self::Distance2Sub,
+        Self::Distance2Mul,
+        Self::Distance2Div,
+    ];
+}
+
+impl From<CellularReturn> for CellularReturnType {
+    fn from(value: CellularReturn) -> Self {
+        match value {
+            CellularReturn::CellValue => Self::CellValue,
+            CellularReturn::Distance => Self::Distance,
+            CellularReturn::Distance2 => Self::Distance2,
+            CellularReturn::Distance2Add => Self::Distance2Add,
+            CellularReturn::Distance2Sub => Self::Distance2Sub,
+            CellularReturn::Distance2Mul => Self::Distance2Mul,
+            CellularReturn::Distance2Div => Self::Distance2Div,
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use noise::NoiseFn;
+
+use crate::world_generation::chunk_generation::noise::noise_grid::{
+    GridNoiseFn, NoiseGrid, NoiseGridArea,
+};
+
+pub const DEFAULT_STRENGTH: f64 = 100.;
+
+/// Offsets the sampled coordinates of `source` by two other noise functions
+/// before sampling it.
+pub struct DomainWarp<T, X, Z> {
+    source: T,
+    warp_x: X,
+    warp_z: Z,
+    strength: f64,
+}
+
+impl<T, X, Z> DomainWarp<T, X, Z> {
+    pub fn new(source: T, warp_x: X, warp_z: Z) -> Self {
+        Self {
+            source,
+            warp_x,
+            warp_z,
+            strength: DEFAULT_STRENGTH,
+        }
+    }
+
+    pub fn set_strength(self, strength: f64) -> Self {
+        Self { strength, ..self }
+    }
+}
+
+impl<T, X, Z> NoiseFn<f64, 2usize> for DomainWarp<T, X, Z>
+where
+    T: NoiseFn<f64, 2usize>,
+    X: NoiseFn<f64, 2usize>,
+    Z: NoiseFn<f64, 2usize>,
+{
+    fn get(&self, point: [f64; 2usize]) -> f64 {
+        let offset_x = self.warp_x.get(point) * self.strength;
+        let offset_z = self.warp_z.get(point) * self.strength;
+
+        self.source.get([point[0] + offset_x, point[1] + offset_z])
+    }
+}
+
+impl<T, X, Z> GridNoiseFn for DomainWarp<T, X, Z>
+where
+    T: NoiseFn<f64, 2usize>,
+    X: GridNoiseFn,
+    Z: GridNoiseFn,
+{
+    /// The offsets come from the grids of the warp noise, but the warped
+    /// points are scattered, so the source is sampled one point at a time.
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        let offsets_x = self.warp_x.get_grid(area);
+        let offsets_z = self.warp_z.get_grid(area);
+
+        NoiseGrid::from_cells(|x, z| {
+            let point = area.point(x, z);
+            self.source.get([
+                point[0] + offsets_x.get(x, z) * self.strength,
+                point[1] + offsets_z.get(x, z) * self.strength,
+            ])
+        })
+    }
+}
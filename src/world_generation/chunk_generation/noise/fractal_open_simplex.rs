@@ -2,6 +2,10 @@ use noise::core::simplex::simplex_2d;
 use noise::permutationtable::PermutationTable;
 use noise::{NoiseFn, Seedable, Vector2};
 
+use crate::world_generation::chunk_generation::noise::noise_grid::{
+    GridNoiseFn, NoiseGrid, NoiseGridArea,
+};
+
 #[derive(Clone, Copy, Debug)]
 pub struct FractalOpenSimplex<R>
 where
@@ -89,6 +93,36 @@ where
     }
 }
 
+impl<R> GridNoiseFn for FractalOpenSimplex<R>
+where
+    R: NoiseFn<f64, 2usize>,
+{
+    /// Adds up one octave after the other over the whole grid, so the
+    /// frequency and amplitude of every octave are only computed once.
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        let mut grid = NoiseGrid::constant(0.);
+        for octave in 0..self.octaves {
+            let frequency = self.frequency * self.lacunarity.powi(octave);
+            let amplitude = self.amplitude * self.persistence.powi(octave);
+            grid = grid.zip(
+                &NoiseGrid::from_fn(area, |point| {
+                    noise(
+                        point[0],
+                        point[1],
+                        frequency,
+                        amplitude,
+                        &self.permutation_table,
+                    )
+                    .0
+                }),
+                |value, octave_value| value + octave_value,
+            );
+        }
+
+        grid.map(|value| value.max(2.))
+    }
+}
+
 pub fn fractal_noise(
     x: f64,
     z: f64,
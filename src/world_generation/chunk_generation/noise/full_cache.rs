@@ -2,6 +2,10 @@ use std::{cell::RefCell, collections::HashMap};
 
 use noise::{NoiseFn, Seedable};
 
+use crate::world_generation::chunk_generation::noise::noise_grid::{
+    GridNoiseFn, NOISE_GRID_SIZE, NoiseGrid, NoiseGridArea,
+};
+
 pub struct FullCache<T> {
     source: T,
     cache_map: RefCell<HashMap<[i64; 2], f64>>,
@@ -58,3 +62,20 @@ where
         return value;
     }
 }
+
+impl<T> GridNoiseFn for FullCache<T>
+where
+    T: GridNoiseFn,
+{
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        let grid = self.source.get_grid(area);
+        let mut map = self.cache_map.borrow_mut();
+        for x in 0..NOISE_GRID_SIZE {
+            for z in 0..NOISE_GRID_SIZE {
+                let point = area.point(x, z);
+                map.insert([point[0] as i64, point[1] as i64], grid.get(x, z));
+            }
+        }
+        grid
+    }
+}
@@ -1,7 +1,12 @@
 use std::f64::consts::E;
 
+use bevy::math::DVec2;
 use noise::{MultiFractal, NoiseFn, Seedable, math::vectors::Vector2};
 
+use crate::world_generation::chunk_generation::noise::noise_grid::{
+    GridNoiseFn, NoiseGrid, NoiseGridArea,
+};
+
 pub struct GFT<T> {
     pub octaves: usize,
     pub frequency: f64,
@@ -23,6 +28,9 @@ pub const DEFAULT_GRADIENT: f64 = 1.;
 pub const DEFAULT_AMPLITUDE: f64 = 1.;
 pub const DEFAULT_SEED: u32 = 0;
 
+/// How far apart the samples are that the slope of an octave is taken from.
+const DERIVATIVE_OFFSET: f64 = 0.001;
+
 impl<T> GFT<T>
 where
     T: NoiseFn<f64, 2>,
@@ -120,7 +128,7 @@ where
 {
     fn get(&self, point: [f64; 2]) -> f64 {
         let point = Vector2::from(point);
-        let derivative_offset = 0.001;
+        let derivative_offset = DERIVATIVE_OFFSET;
         let offset_x_point = Vector2::new(derivative_offset, 0.);
         let offset_y_point = Vector2::new(0., derivative_offset);
 
@@ -157,3 +165,52 @@ where
         result * self.scale_factor
     }
 }
+
+impl<T> GridNoiseFn for GFT<T>
+where
+    T: GridNoiseFn,
+{
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        let mut result = NoiseGrid::constant(0.);
+        let mut total_flatness = NoiseGrid::constant(0.);
+
+        for octave in 0..self.octaves as i32 {
+            let frequency = self.frequency * self.lacunarity.powi(octave);
+            let amplitude = self.amplitude * self.persistence.powi(octave);
+
+            // Every octave samples its source on a scaled grid, and on the
+            // same grid moved a little along both axes for the slope.
+            let octave_area = area.scaled(frequency);
+            let noise_values = self.source.get_grid(&octave_area);
+            let noise_values_offset_x = self.source.get_grid(
+                &octave_area.shifted(DVec2::new(DERIVATIVE_OFFSET, 0.)),
+            );
+            let noise_values_offset_y = self.source.get_grid(
+                &octave_area.shifted(DVec2::new(0., DERIVATIVE_OFFSET)),
+            );
+
+            total_flatness = total_flatness.zip(
+                &NoiseGrid::from_cells(|x, z| {
+                    let noise_value = noise_values.get(x, z);
+                    let derivative = Vector2::new(
+                        (noise_values_offset_x.get(x, z) - noise_value)
+                            / DERIVATIVE_OFFSET,
+                        (noise_values_offset_y.get(x, z) - noise_value)
+                            / DERIVATIVE_OFFSET,
+                    );
+                    derivative.magnitude()
+                }),
+                |total, flatness| total + flatness * (1. / (octave + 1) as f64),
+            );
+
+            result = NoiseGrid::from_cells(|x, z| {
+                let gradience =
+                    self.get_gradient_influence(total_flatness.get(x, z));
+                result.get(x, z)
+                    + noise_values.get(x, z) * gradience * amplitude
+            });
+        }
+
+        result.map(|value| value * self.scale_factor)
+    }
+}
@@ -1,6 +1,9 @@
 use noise::{NoiseFn, Seedable};
 
 use crate::world_generation::chunk_generation::chunk_lod::ChunkLod;
+use crate::world_generation::chunk_generation::noise::noise_grid::{
+    GridNoiseFn, NoiseGrid, NoiseGridArea,
+};
 
 pub struct LodHeightAdjuster<T> {
     noise: T,
@@ -58,3 +61,15 @@ where
             + 10. / self.lod.multiplier_i32() as f64
     }
 }
+
+impl<T> GridNoiseFn for LodHeightAdjuster<T>
+where
+    T: GridNoiseFn,
+{
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        let multiplier = self.lod.multiplier_i32() as f64;
+        self.noise
+            .get_grid(area)
+            .map(|value| value * (1. / multiplier) + 1. + 10. / multiplier)
+    }
+}
@@ -1,11 +1,15 @@
 pub mod cellular_noise;
+pub mod domain_warp;
 pub mod fractal_open_simplex;
 pub mod full_cache;
 pub mod gradient_fractal_noise;
 pub mod lod_height_adjuster;
+pub mod noise_grid;
 pub mod roughness;
+pub mod shared_noise;
 pub mod shift_n_scale;
 pub mod smooth_step;
+pub mod spline_remap;
 pub mod steepness;
 pub mod terrain_noise;
 pub mod terrain_noise_type;
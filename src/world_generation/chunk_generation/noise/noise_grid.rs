@@ -0,0 +1,200 @@
+use bevy::math::DVec2;
+use noise::{
+    Add, Constant, Max, Multiply, NoiseFn, ScalePoint, Seedable, Simplex,
+    core::simplex::simplex_2d, permutationtable::PermutationTable,
+};
+
+use crate::world_generation::chunk_generation::{
+    CHUNK_SIZE, chunk_lod::ChunkLod,
+};
+
+/// Width of a noise grid. One chunk plus the border the mesher needs.
+pub const NOISE_GRID_SIZE: usize = CHUNK_SIZE + 2;
+
+/// The noise positions covered by a grid: `NOISE_GRID_SIZE` columns in
+/// each direction, starting at `offset` and `step` apart.
+#[derive(Clone, Copy, PartialEq)]
+pub struct NoiseGridArea {
+    pub offset: DVec2,
+    pub step: f64,
+}
+
+impl NoiseGridArea {
+    pub fn from_chunk(position: [i32; 3], chunk_lod: ChunkLod) -> Self {
+        Self {
+            offset: DVec2::new(position[0] as f64, position[2] as f64)
+                * CHUNK_SIZE as f64,
+            step: chunk_lod.multiplier_i32() as f64,
+        }
+    }
+
+    pub fn point(&self, x: usize, z: usize) -> [f64; 2] {
+        [
+            self.offset.x + x as f64 * self.step,
+            self.offset.y + z as f64 * self.step,
+        ]
+    }
+
+    /// The area of the same points multiplied by `scale`.
+    pub fn scaled(&self, scale: f64) -> Self {
+        Self {
+            offset: self.offset * scale,
+            step: self.step * scale,
+        }
+    }
+
+    /// The area of the same points moved by `shift`.
+    pub fn shifted(&self, shift: DVec2) -> Self {
+        Self {
+            offset: self.offset + shift,
+            step: self.step,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NoiseGrid {
+    values: Vec<f64>,
+}
+
+impl NoiseGrid {
+    pub fn from_fn(
+        area: &NoiseGridArea,
+        mut noise_fn: impl FnMut([f64; 2]) -> f64,
+    ) -> Self {
+        Self::from_cells(|x, z| noise_fn(area.point(x, z)))
+    }
+
+    /// Builds a grid from the column indexes of its cells.
+    pub fn from_cells(mut cell_fn: impl FnMut(usize, usize) -> f64) -> Self {
+        let mut values = Vec::with_capacity(NOISE_GRID_SIZE * NOISE_GRID_SIZE);
+        for x in 0..NOISE_GRID_SIZE {
+            for z in 0..NOISE_GRID_SIZE {
+                values.push(cell_fn(x, z));
+            }
+        }
+        Self { values }
+    }
+
+    pub fn constant(value: f64) -> Self {
+        Self {
+            values: vec![value; NOISE_GRID_SIZE * NOISE_GRID_SIZE],
+        }
+    }
+
+    pub fn get(&self, x: usize, z: usize) -> f64 {
+        self.values[x * NOISE_GRID_SIZE + z]
+    }
+
+    pub fn map(mut self, map_fn: impl Fn(f64) -> f64) -> Self {
+        for value in &mut self.values {
+            *value = map_fn(*value);
+        }
+        self
+    }
+
+    pub fn zip(
+        mut self,
+        other: &NoiseGrid,
+        zip_fn: impl Fn(f64, f64) -> f64,
+    ) -> Self {
+        for (value, other) in self.values.iter_mut().zip(&other.values) {
+            *value = zip_fn(*value, *other);
+        }
+        self
+    }
+}
+
+/// Noise functions that can be sampled for a whole grid at once.
+/// The default samples every point on its own, noise functions that only
+/// transform or combine the values of their sources at the same point
+/// should override it to work on the grids of their sources instead.
+pub trait GridNoiseFn: NoiseFn<f64, 2> {
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        NoiseGrid::from_fn(area, |point| self.get(point))
+    }
+}
+
+impl<T> GridNoiseFn for &T
+where
+    T: GridNoiseFn + ?Sized,
+{
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        T::get_grid(*self, area)
+    }
+}
+
+impl<T> GridNoiseFn for Box<T>
+where
+    T: GridNoiseFn + ?Sized,
+{
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        T::get_grid(self, area)
+    }
+}
+
+impl GridNoiseFn for Simplex {
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        // The permutation table of `Simplex` is private, but it is built
+        // from the seed alone, so the grid builds its own once and samples
+        // without going through `get`.
+        let hasher = PermutationTable::new(self.seed());
+        NoiseGrid::from_fn(area, |point| simplex_2d(point.into(), &hasher).0)
+    }
+}
+
+impl<T> GridNoiseFn for ScalePoint<T>
+where
+    T: GridNoiseFn,
+{
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        // Scaling both axes alike keeps the points on a grid of the source.
+        if self.x_scale == self.y_scale {
+            self.source.get_grid(&area.scaled(self.x_scale))
+        } else {
+            NoiseGrid::from_fn(area, |point| self.get(point))
+        }
+    }
+}
+
+impl GridNoiseFn for Constant {
+    fn get_grid(&self, _area: &NoiseGridArea) -> NoiseGrid {
+        NoiseGrid::constant(self.value)
+    }
+}
+
+impl<A, B> GridNoiseFn for Add<f64, A, B, 2>
+where
+    A: GridNoiseFn,
+    B: GridNoiseFn,
+{
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        self.source1
+            .get_grid(area)
+            .zip(&self.source2.get_grid(area), |a, b| a + b)
+    }
+}
+
+impl<A, B> GridNoiseFn for Max<f64, A, B, 2>
+where
+    A: GridNoiseFn,
+    B: GridNoiseFn,
+{
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        self.source1
+            .get_grid(area)
+            .zip(&self.source2.get_grid(area), f64::max)
+    }
+}
+
+impl<A, B> GridNoiseFn for Multiply<f64, A, B, 2>
+where
+    A: GridNoiseFn,
+    B: GridNoiseFn,
+{
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        self.source1
+            .get_grid(area)
+            .zip(&self.source2.get_grid(area), |a, b| a * b)
+    }
+}
@@ -1,4 +1,5 @@
 use crate::world_generation::chunk_generation::noise::fractal_open_simplex::noise;
+use crate::world_generation::chunk_generation::noise::noise_grid::GridNoiseFn;
 use noise::permutationtable::PermutationTable;
 use noise::{NoiseFn, Seedable};
 
@@ -11,6 +12,9 @@ pub struct Roughness {
 }
 
 impl Roughness {
+    pub const DEFAULT_FREQUENCY: f64 = 0.0005;
+    pub const DEFAULT_AMPLITUDE: f64 = 0.3;
+
     pub fn new(seed: u32, frequency: f64, amplitude: f64) -> Self {
         Self {
             seed,
@@ -52,3 +56,5 @@ impl NoiseFn<f64, 2usize> for Roughness {
         .0 - 0.15
     }
 }
+
+impl GridNoiseFn for Roughness {}
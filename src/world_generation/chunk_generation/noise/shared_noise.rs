@@ -0,0 +1,60 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use noise::NoiseFn;
+
+use crate::world_generation::chunk_generation::noise::noise_grid::{
+    GridNoiseFn, NoiseGrid, NoiseGridArea,
+};
+
+/// A node of a compiled terrain noise graph, which can be sampled by
+/// multiple parents. The last sampled point and grid are remembered, so
+/// parents sampling the same point or grid only evaluate the node once.
+#[derive(Clone)]
+pub struct SharedNoise(Rc<SharedNoiseNode>);
+
+struct SharedNoiseNode {
+    noise: Box<dyn GridNoiseFn>,
+    last_sample: Cell<Option<([f64; 2], f64)>>,
+    last_grid: RefCell<Option<(NoiseGridArea, NoiseGrid)>>,
+}
+
+impl SharedNoise {
+    pub fn new(noise: Box<dyn GridNoiseFn>) -> Self {
+        Self(Rc::new(SharedNoiseNode {
+            noise,
+            last_sample: Cell::new(None),
+            last_grid: RefCell::new(None),
+        }))
+    }
+}
+
+impl NoiseFn<f64, 2usize> for SharedNoise {
+    fn get(&self, point: [f64; 2usize]) -> f64 {
+        if let Some((last_point, last_value)) = self.0.last_sample.get()
+            && last_point == point
+        {
+            return last_value;
+        }
+
+        let value = self.0.noise.get(point);
+        self.0.last_sample.set(Some((point, value)));
+        value
+    }
+}
+
+impl GridNoiseFn for SharedNoise {
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        if let Some((last_area, last_grid)) = &*self.0.last_grid.borrow()
+            && last_area == area
+        {
+            return last_grid.clone();
+        }
+
+        let grid = self.0.noise.get_grid(area);
+        *self.0.last_grid.borrow_mut() = Some((*area, grid.clone()));
+        grid
+    }
+}
@@ -1,35 +1,50 @@
 use noise::{NoiseFn, Seedable};
 
-pub struct ShiftNScale<T, const SCALE: i32, const SHIFT: i32> {
+use crate::world_generation::chunk_generation::noise::noise_grid::{
+    GridNoiseFn, NoiseGrid, NoiseGridArea,
+};
+
+pub struct ShiftNScale<T> {
     noise: T,
+    scale: f64,
+    shift: f64,
 }
 
-impl<T, const SCALE: i32, const SHIFT: i32> ShiftNScale<T, SCALE, SHIFT> {
+impl<T> ShiftNScale<T> {
+    pub const DEFAULT_SCALE: f64 = 1.;
+    pub const DEFAULT_SHIFT: f64 = 0.;
+
     pub fn new(source: T) -> Self {
-        Self { noise: source }
+        Self {
+            noise: source,
+            scale: Self::DEFAULT_SCALE,
+            shift: Self::DEFAULT_SHIFT,
+        }
+    }
+
+    pub fn set_scale(self, scale: f64) -> Self {
+        Self { scale, ..self }
+    }
+
+    pub fn set_shift(self, shift: f64) -> Self {
+        Self { shift, ..self }
     }
 }
 
-impl<
-    T, 
-    const SCALE: i32, 
-    const SHIFT: i32
-> Default for ShiftNScale<T, SCALE, SHIFT>
+impl<T> Default for ShiftNScale<T>
 where
     T: Default + Seedable,
 {
     fn default() -> Self {
         Self {
             noise: Default::default(),
+            scale: Self::DEFAULT_SCALE,
+            shift: Self::DEFAULT_SHIFT,
         }
     }
 }
 
-impl<
-    T, 
-    const SCALE: i32, 
-    const SHIFT: i32
-> Seedable for ShiftNScale<T, SCALE, SHIFT>
+impl<T> Seedable for ShiftNScale<T>
 where
     T: Default + Seedable,
 {
@@ -43,16 +58,22 @@ where
     }
 }
 
-impl<
-    T, 
-    const SCALE: i32, 
-    const SHIFT: i32, 
-    const D: usize
-> NoiseFn<f64, D> for ShiftNScale<T, SCALE, SHIFT>
+impl<T, const D: usize> NoiseFn<f64, D> for ShiftNScale<T>
 where
-    T: Default + Seedable + NoiseFn<f64, D>,
+    T: NoiseFn<f64, D>,
 {
     fn get(&self, point: [f64; D]) -> f64 {
-        (self.noise.get(point) + SHIFT as f64) / SCALE as f64
+        (self.noise.get(point) + self.shift) / self.scale
+    }
+}
+
+impl<T> GridNoiseFn for ShiftNScale<T>
+where
+    T: GridNoiseFn,
+{
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        self.noise
+            .get_grid(area)
+            .map(|value| (value + self.shift) / self.scale)
     }
 }
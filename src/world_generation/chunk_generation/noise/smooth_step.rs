@@ -1,6 +1,10 @@
 use noise::{NoiseFn, Seedable};
 use std::f64::consts::PI;
 
+use crate::world_generation::chunk_generation::noise::noise_grid::{
+    GridNoiseFn, NoiseGrid, NoiseGridArea,
+};
+
 pub struct SmoothStep<T> {
     noise: T,
     steps: f64,
@@ -67,6 +71,17 @@ where
     }
 }
 
+impl<T> GridNoiseFn for SmoothStep<T>
+where
+    T: GridNoiseFn,
+{
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        self.noise.get_grid(area).map(|value| {
+            smooth_floor(value * self.steps + 0.5, self.smoothness) / self.steps
+        })
+    }
+}
+
 // https://www.desmos.com/calculator/zyrixan1eo
 fn smooth_floor(x: f64, factor: f64) -> f64 {
     let sigmoid_value = sigmoid((PI * x).sin(), factor);
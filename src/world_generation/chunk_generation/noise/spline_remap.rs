@@ -0,0 +1,119 @@
+use noise::NoiseFn;
+
+use crate::world_generation::chunk_generation::noise::noise_grid::{
+    GridNoiseFn, NoiseGrid, NoiseGridArea,
+};
+
+pub const DEFAULT_POINTS: [[f64; 2]; 3] = [[-1., -1.], [0., 0.], [1., 1.]];
+
+/// Remaps the output of `source` through a monotone cubic spline.
+/// Each control point is `[input, output]`, inputs outside of the
+/// control points are clamped to the first or last output.
+pub struct SplineRemap<T> {
+    source: T,
+    points: Vec<[f64; 2]>,
+    tangents: Vec<f64>,
+}
+
+impl<T> SplineRemap<T> {
+    pub fn new(source: T, mut points: Vec<[f64; 2]>) -> Self {
+        points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        points.dedup_by(|a, b| a[0] == b[0]);
+
+        let tangents = monotone_tangents(&points);
+
+        Self {
+            source,
+            points,
+            tangents,
+        }
+    }
+
+    pub fn remap(&self, value: f64) -> f64 {
+        let (Some(first), Some(last)) =
+            (self.points.first(), self.points.last())
+        else {
+            return value;
+        };
+
+        if value <= first[0] {
+            return first[1];
+        }
+        if value >= last[0] {
+            return last[1];
+        }
+
+        let index = self.points.partition_point(|point| point[0] <= value) - 1;
+        let [x0, y0] = self.points[index];
+        let [x1, y1] = self.points[index + 1];
+        let width = x1 - x0;
+        let t = (value - x0) / width;
+        let t2 = t * t;
+        let t3 = t2 * t;
+
+        (2. * t3 - 3. * t2 + 1.) * y0
+            + (t3 - 2. * t2 + t) * width * self.tangents[index]
+            + (-2. * t3 + 3. * t2) * y1
+            + (t3 - t2) * width * self.tangents[index + 1]
+    }
+}
+
+impl<T> NoiseFn<f64, 2usize> for SplineRemap<T>
+where
+    T: NoiseFn<f64, 2usize>,
+{
+    fn get(&self, point: [f64; 2usize]) -> f64 {
+        self.remap(self.source.get(point))
+    }
+}
+
+impl<T> GridNoiseFn for SplineRemap<T>
+where
+    T: GridNoiseFn,
+{
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        self.source.get_grid(area).map(|value| self.remap(value))
+    }
+}
+
+// https://en.wikipedia.org/wiki/Monotone_cubic_interpolation
+fn monotone_tangents(points: &[[f64; 2]]) -> Vec<f64> {
+    if points.len() < 2 {
+        return vec![0.; points.len()];
+    }
+
+    let secants = points
+        .windows(2)
+        .map(|pair| (pair[1][1] - pair[0][1]) / (pair[1][0] - pair[0][0]))
+        .collect::<Vec<_>>();
+
+    let mut tangents = Vec::with_capacity(points.len());
+    tangents.push(secants[0]);
+    for pair in secants.windows(2) {
+        if pair[0] * pair[1] <= 0. {
+            tangents.push(0.);
+        } else {
+            tangents.push((pair[0] + pair[1]) / 2.);
+        }
+    }
+    tangents.push(secants[secants.len() - 1]);
+
+    for (index, secant) in secants.iter().enumerate() {
+        if *secant == 0. {
+            tangents[index] = 0.;
+            tangents[index + 1] = 0.;
+            continue;
+        }
+
+        let alpha = tangents[index] / secant;
+        let beta = tangents[index + 1] / secant;
+        let length = alpha * alpha + beta * beta;
+        if length > 9. {
+            let factor = 3. / length.sqrt();
+            tangents[index] = factor * alpha * secant;
+            tangents[index + 1] = factor * beta * secant;
+        }
+    }
+
+    tangents
+}
@@ -1,12 +1,17 @@
+use bevy::math::DVec2;
 use noise::{NoiseFn, Seedable};
 
+use crate::world_generation::chunk_generation::noise::noise_grid::{
+    GridNoiseFn, NOISE_GRID_SIZE, NoiseGrid, NoiseGridArea,
+};
+
 pub struct Steepness<T> {
     source: T,
     sample_offset: f64,
 }
 
 impl<T> Steepness<T> {
-    const DEFAULT_OFFSET: f64 = 1f64;
+    pub const DEFAULT_OFFSET: f64 = 1f64;
 
     pub fn new(source: T) -> Self {
         Self {
@@ -14,6 +19,13 @@ impl<T> Steepness<T> {
             sample_offset: Self::DEFAULT_OFFSET,
         }
     }
+
+    pub fn set_sample_offset(self, sample_offset: f64) -> Self {
+        Self {
+            sample_offset,
+            ..self
+        }
+    }
 }
 
 impl<T> Default for Steepness<T>
@@ -63,3 +75,66 @@ where
         (steepness_x + steepness_y) / 2.
     }
 }
+
+impl<T> Steepness<T>
+where
+    T: GridNoiseFn,
+{
+    /// The values of `values` moved by whole cells. Cells moved past the
+    /// edge of the grid are sampled on their own.
+    fn moved_grid(
+        &self,
+        area: &NoiseGridArea,
+        values: &NoiseGrid,
+        cells: [usize; 2],
+    ) -> NoiseGrid {
+        NoiseGrid::from_cells(|x, z| {
+            let [x, z] = [x + cells[0], z + cells[1]];
+            if x < NOISE_GRID_SIZE && z < NOISE_GRID_SIZE {
+                values.get(x, z)
+            } else {
+                self.source.get(area.point(x, z))
+            }
+        })
+    }
+}
+
+impl<T> GridNoiseFn for Steepness<T>
+where
+    T: GridNoiseFn,
+{
+    fn get_grid(&self, area: &NoiseGridArea) -> NoiseGrid {
+        let cell_offset = self.sample_offset / area.step;
+        let (values_main, values_offset_x, values_offset_y) =
+            if cell_offset.fract() == 0.
+                && (0. ..NOISE_GRID_SIZE as f64).contains(&cell_offset)
+            {
+                // The offset points lie on the grid itself.
+                let cells = cell_offset as usize;
+                let values_main = self.source.get_grid(area);
+                let values_offset_x =
+                    self.moved_grid(area, &values_main, [cells, 0]);
+                let values_offset_y =
+                    self.moved_grid(area, &values_main, [0, cells]);
+                (values_main, values_offset_x, values_offset_y)
+            } else {
+                // The main grid is sampled last, so a shared source keeps
+                // it for its other parents.
+                let values_offset_x = self.source.get_grid(
+                    &area.shifted(DVec2::new(self.sample_offset, 0.)),
+                );
+                let values_offset_y = self.source.get_grid(
+                    &area.shifted(DVec2::new(0., self.sample_offset)),
+                );
+                (self.source.get_grid(area), values_offset_x, values_offset_y)
+            };
+
+        NoiseGrid::from_cells(|x, z| {
+            let value_main = values_main.get(x, z);
+            let steepness_x = (value_main - values_offset_x.get(x, z)).abs();
+            let steepness_y = (value_main - values_offset_y.get(x, z)).abs();
+
+            (steepness_x + steepness_y) / 2.
+        })
+    }
+}
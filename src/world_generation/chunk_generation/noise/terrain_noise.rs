@@ -1,8 +1,11 @@
-use noise::NoiseFn;
-use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use crate::world_generation::chunk_generation::noise::terrain_noise_type::TerrainNoiseType;
+use crate::world_generation::{
+    chunk_generation::noise::{
+        shared_noise::SharedNoise, terrain_noise_type::TerrainNoiseType,
+    },
+    seed_derivation::{derive_seed, seeded_rng},
+};
 
 pub const TERRAIN_NOISE_FILE_PATH: &'static str = "assets/terrain_noise.ron";
 
@@ -20,7 +23,48 @@ impl TerrainNoise {
         }
     }
 
-    pub fn get_noise_fn(&self, rng: &mut impl Rng) -> Box<dyn NoiseFn<f64, 2>> {
-        self.noise_types[self.start_index].to_noise_fn(&self.noise_types, rng)
+    /// Builds every noise type reachable from the start index exactly once,
+    /// so noise types referenced by multiple parents are shared between them.
+    /// Each noise type is seeded from its index, so its output doesn't
+    /// depend on the order it is built in. Fails if the noise types form a
+    /// cycle or refer to noise types that don't exist.
+    pub fn get_noise_fn(
+        &self,
+        seed: u64,
+    ) -> Result<SharedNoise, anyhow::Error> {
+        let mut nodes = vec![None; self.noise_types.len()];
+        let mut visiting = vec![false; self.noise_types.len()];
+        self.compile_node(self.start_index, seed, &mut nodes, &mut visiting)
+    }
+
+    fn compile_node(
+        &self,
+        index: usize,
+        seed: u64,
+        nodes: &mut Vec<Option<SharedNoise>>,
+        visiting: &mut Vec<bool>,
+    ) -> Result<SharedNoise, anyhow::Error> {
+        let noise_type = self.noise_types.get(index).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Terrain noise refers to the missing index {index}!"
+            )
+        })?;
+        if let Some(node) = &nodes[index] {
+            return Ok(node.clone());
+        }
+        if visiting[index] {
+            anyhow::bail!("Terrain noise contains a cycle at index {index}!");
+        }
+
+        visiting[index] = true;
+        for input_index in noise_type.input_indexes() {
+            self.compile_node(input_index, seed, nodes, visiting)?;
+        }
+        visiting[index] = false;
+
+        let mut rng = seeded_rng(derive_seed(seed, index as u64));
+        let node = SharedNoise::new(noise_type.to_noise_fn(nodes, &mut rng));
+        nodes[index] = Some(node.clone());
+        Ok(node)
     }
 }
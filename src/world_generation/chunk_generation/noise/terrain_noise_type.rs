@@ -1,9 +1,19 @@
-use noise::{Add, Constant, Max, MultiFractal, Multiply, NoiseFn, ScalePoint, Simplex};
+use noise::{Add, Constant, Max, MultiFractal, Multiply, ScalePoint, Simplex};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::world_generation::chunk_generation::noise::{
-    gradient_fractal_noise::GFT, smooth_step::SmoothStep,
+    cellular_noise::{Cellular, CellularDistance, CellularReturn},
+    domain_warp::DomainWarp,
+    fractal_open_simplex::FractalOpenSimplex,
+    gradient_fractal_noise::GFT,
+    noise_grid::GridNoiseFn,
+    roughness::Roughness,
+    shared_noise::SharedNoise,
+    shift_n_scale::ShiftNScale,
+    smooth_step::SmoothStep,
+    spline_remap::SplineRemap,
+    steepness::Steepness,
 };
 
 #[derive(Serialize, Deserialize)]
@@ -42,42 +52,109 @@ pub enum TerrainNoiseType {
         gradient: f64,
         amplitude: f64,
     },
+    Cellular {
+        frequency: f64,
+        jitter: f64,
+        distance_function: CellularDistance,
+        return_type: CellularReturn,
+    },
+    Roughness {
+        frequency: f64,
+        amplitude: f64,
+    },
+    FractalOpenSimplex {
+        roughness_index: usize,
+        octaves: i32,
+        frequency: f64,
+        amplitude: f64,
+        lacunarity: f64,
+        persistence: f64,
+    },
+    ShiftNScale {
+        noise_index: usize,
+        shift: f64,
+        scale: f64,
+    },
+    Steepness {
+        noise_index: usize,
+        sample_offset: f64,
+    },
+    DomainWarp {
+        noise_index: usize,
+        x_index: usize,
+        z_index: usize,
+        strength: f64,
+    },
+    SplineRemap {
+        noise_index: usize,
+        points: Vec<[f64; 2]>,
+    },
 }
 
 impl TerrainNoiseType {
+    /// The indexes of the noise types this noise type samples from.
+    pub fn input_indexes(&self) -> Vec<usize> {
+        match self {
+            TerrainNoiseType::Simplex
+            | TerrainNoiseType::Constant { .. }
+            | TerrainNoiseType::Cellular { .. }
+            | TerrainNoiseType::Roughness { .. } => vec![],
+            TerrainNoiseType::Add { a_index, b_index }
+            | TerrainNoiseType::Max { a_index, b_index }
+            | TerrainNoiseType::Multiply { a_index, b_index } => vec![*a_index, *b_index],
+            TerrainNoiseType::SmoothStep { noise_index, .. }
+            | TerrainNoiseType::ScalePoint { noise_index, .. }
+            | TerrainNoiseType::GFT { noise_index, .. }
+            | TerrainNoiseType::ShiftNScale { noise_index, .. }
+            | TerrainNoiseType::Steepness { noise_index, .. }
+            | TerrainNoiseType::SplineRemap { noise_index, .. } => vec![*noise_index],
+            TerrainNoiseType::FractalOpenSimplex {
+                roughness_index, ..
+            } => vec![*roughness_index],
+            TerrainNoiseType::DomainWarp {
+                noise_index,
+                x_index,
+                z_index,
+                ..
+            } => vec![*noise_index, *x_index, *z_index],
+        }
+    }
+
     pub fn to_noise_fn(
         &self,
-        noise_types: &Vec<TerrainNoiseType>,
+        nodes: &[Option<SharedNoise>],
         rng: &mut impl Rng,
-    ) -> Box<dyn NoiseFn<f64, 2>> {
+    ) -> Box<dyn GridNoiseFn> {
+        let node = |index: usize| {
+            nodes[index]
+                .clone()
+                .expect("Noise inputs have to be compiled first!")
+        };
+
         match self {
             TerrainNoiseType::Simplex => Box::new(Simplex::new(rng.random())),
-            TerrainNoiseType::Add { a_index, b_index } => Box::new(Add::new(
-                noise_types[*a_index].to_noise_fn(noise_types, rng),
-                noise_types[*b_index].to_noise_fn(noise_types, rng),
-            )),
+            TerrainNoiseType::Add { a_index, b_index } => {
+                Box::new(Add::new(node(*a_index), node(*b_index)))
+            }
             TerrainNoiseType::Constant { value } => Box::new(Constant::new(*value)),
-            TerrainNoiseType::Max { a_index, b_index } => Box::new(Max::new(
-                noise_types[*a_index].to_noise_fn(noise_types, rng),
-                noise_types[*b_index].to_noise_fn(noise_types, rng),
-            )),
-            TerrainNoiseType::Multiply { a_index, b_index } => Box::new(Multiply::new(
-                noise_types[*a_index].to_noise_fn(noise_types, rng),
-                noise_types[*b_index].to_noise_fn(noise_types, rng),
-            )),
+            TerrainNoiseType::Max { a_index, b_index } => {
+                Box::new(Max::new(node(*a_index), node(*b_index)))
+            }
+            TerrainNoiseType::Multiply { a_index, b_index } => {
+                Box::new(Multiply::new(node(*a_index), node(*b_index)))
+            }
             TerrainNoiseType::SmoothStep {
                 noise_index,
                 steps,
                 smoothness,
             } => Box::new(
-                SmoothStep::new(noise_types[*noise_index].to_noise_fn(noise_types, rng))
+                SmoothStep::new(node(*noise_index))
                     .set_smoothness(*smoothness)
                     .set_steps(*steps),
             ),
-            TerrainNoiseType::ScalePoint { noise_index, scale } => Box::new(
-                ScalePoint::new(noise_types[*noise_index].to_noise_fn(noise_types, rng))
-                    .set_scale(*scale),
-            ),
+            TerrainNoiseType::ScalePoint { noise_index, scale } => {
+                Box::new(ScalePoint::new(node(*noise_index)).set_scale(*scale))
+            }
             TerrainNoiseType::GFT {
                 noise_index,
                 octaves,
@@ -87,7 +164,7 @@ impl TerrainNoiseType {
                 gradient,
                 amplitude,
             } => Box::new(
-                GFT::new_with_source(noise_types[*noise_index].to_noise_fn(noise_types, rng))
+                GFT::new_with_source(node(*noise_index))
                     .set_octaves(*octaves)
                     .set_frequency(*frequency)
                     .set_lacunarity(*lacunarity)
@@ -95,6 +172,64 @@ impl TerrainNoiseType {
                     .set_gradient(*gradient)
                     .set_amplitude(*amplitude),
             ),
+            TerrainNoiseType::Cellular {
+                frequency,
+                jitter,
+                distance_function,
+                return_type,
+            } => Box::new(
+                Cellular::new(rng.random())
+                    .set_frequency(*frequency as f32)
+                    .set_jitter(*jitter as f32)
+                    .set_distance_function((*distance_function).into())
+                    .set_return_type((*return_type).into()),
+            ),
+            TerrainNoiseType::Roughness {
+                frequency,
+                amplitude,
+            } => Box::new(Roughness::new(rng.random(), *frequency, *amplitude)),
+            TerrainNoiseType::FractalOpenSimplex {
+                roughness_index,
+                octaves,
+                frequency,
+                amplitude,
+                lacunarity,
+                persistence,
+            } => Box::new(FractalOpenSimplex::new(
+                rng.random(),
+                *frequency,
+                *amplitude,
+                *octaves,
+                *lacunarity,
+                *persistence,
+                node(*roughness_index),
+            )),
+            TerrainNoiseType::ShiftNScale {
+                noise_index,
+                shift,
+                scale,
+            } => Box::new(
+                ShiftNScale::new(node(*noise_index))
+                    .set_shift(*shift)
+                    .set_scale(*scale),
+            ),
+            TerrainNoiseType::Steepness {
+                noise_index,
+                sample_offset,
+            } => Box::new(Steepness::new(node(*noise_index)).set_sample_offset(*sample_offset)),
+            TerrainNoiseType::DomainWarp {
+                noise_index,
+                x_index,
+                z_index,
+                strength,
+            } => Box::new(
+                DomainWarp::new(node(*noise_index), node(*x_index), node(*z_index))
+                    .set_strength(*strength),
+            ),
+            TerrainNoiseType::SplineRemap {
+                noise_index,
+                points,
+            } => Box::new(SplineRemap::new(node(*noise_index), points.clone())),
         }
     }
 }
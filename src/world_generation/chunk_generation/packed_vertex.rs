@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+
+use crate::world_generation::chunk_generation::{
+    chunk_lod::ChunkLod, light_data::LightLevel,
+};
+
+/// Vertices are placed on a grid of sixteenths of a voxel.
+const POSITION_STEPS: f32 = 16.;
+const HORIZONTAL_MASK: u32 = (1 << 11) - 1;
+const VERTICAL_MASK: u32 = (1 << 16) - 1;
+
+/// Which way the face of a vertex looks. The shader takes the normal and
+/// the texture coordinates from it.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum VertexFace {
+    Right = 0,
+    Left = 1,
+    Top = 2,
+    Bottom = 3,
+    Front = 4,
+    Back = 5,
+    /// A quad of a plant, lit from above and textured like a side.
+    Cross = 6,
+}
+
+impl VertexFace {
+    pub fn from_direction(direction: IVec3) -> Self {
+        match direction {
+            IVec3::X => VertexFace::Right,
+            IVec3::NEG_X => VertexFace::Left,
+            IVec3::Y => VertexFace::Top,
+            IVec3::NEG_Y => VertexFace::Bottom,
+            IVec3::Z => VertexFace::Front,
+            _ => VertexFace::Back,
+        }
+    }
+}
+
+/// A vertex of a chunk mesh packed into two words, which
+/// `packed_vertex.wgsl` unpacks again.
+///
+/// The position is in voxels of the chunk, from 0 to 128 across and 4096
+/// up. The first word holds x and z with 11 bits each, then the face with
+/// 3, the ambient occlusion with 2 and the lod with 4 bits. The second one
+/// holds y with 16 bits, then the texture id with 8 and the sky and block
+/// light with 4 bits each.
+pub fn pack_vertex(
+    position: Vec3,
+    face: VertexFace,
+    ambient_occlusion: u8,
+    texture_id: u32,
+    light: LightLevel,
+    chunk_lod: ChunkLod,
+) -> [u32; 2] {
+    let [x, y, z] = (position * POSITION_STEPS).round().to_array();
+    let x = (x.max(0.) as u32).min(HORIZONTAL_MASK);
+    let y = (y.max(0.) as u32).min(VERTICAL_MASK);
+    let z = (z.max(0.) as u32).min(HORIZONTAL_MASK);
+
+    [
+        x | (z << 11)
+            | ((face as u32) << 22)
+            | ((ambient_occlusion as u32 & 3) << 25)
+            | ((chunk_lod.u32() - 1) << 27),
+        y | ((texture_id & 255) << 16)
+            | ((light.sky as u32 & 15) << 24)
+            | ((light.block as u32 & 15) << 28),
+    ]
+}
@@ -1,6 +1,8 @@
 use std::ops::Range;
 
-use rand::{rngs::StdRng, Rng};
+use rand::Rng;
+
+use crate::world_generation::seed_derivation::WorldRng;
 
 #[derive(Clone, Copy)]
 pub struct EntryRange {
@@ -32,7 +34,7 @@ impl EntryRange {
         )
     }
 
-    pub fn rng(&self, rng: &mut StdRng) -> f32 {
+    pub fn rng(&self, rng: &mut WorldRng) -> f32 {
         rng.random_range(self.start..self.end)
     }
 }
@@ -1,12 +1,17 @@
 use std::ops::Range;
 
 use bevy::math::Vec3;
-use rand::{Rng, rngs::StdRng};
+use rand::Rng;
 
-use crate::world_generation::chunk_generation::{
-    VOXEL_SIZE,
-    block_type::BlockType,
-    structures::foliage_generation::tree_l_system::{LSystem, LSystemEntry},
+use crate::world_generation::{
+    chunk_generation::{
+        VOXEL_SIZE,
+        block_type::BlockType,
+        structures::foliage_generation::tree_l_system::{
+            LSystem, LSystemEntry,
+        },
+    },
+    seed_derivation::WorldRng,
 };
 
 pub struct OakLSystem;
@@ -21,7 +26,7 @@ pub enum OakEntryType {
 impl LSystem<OakEntryType> for OakLSystem {
     fn get_start_state(
         position: Vec3,
-        rng: &mut StdRng,
+        rng: &mut WorldRng,
     ) -> Vec<LSystemEntry<OakEntryType>> {
         Self::create_straight_piece(
             &position,
@@ -39,7 +44,7 @@ impl LSystem<OakEntryType> for OakLSystem {
 
     fn process_tree(
         mut start_state: &mut Vec<LSystemEntry<OakEntryType>>,
-        rng: &mut StdRng,
+        rng: &mut WorldRng,
     ) {
         for _ in 0..3 {
             Self::recurse_l_system(&mut start_state, rng);
@@ -56,7 +61,7 @@ impl LSystem<OakEntryType> for OakLSystem {
 
     fn recurse_entry(
         entry: &LSystemEntry<OakEntryType>,
-        rng: &mut StdRng,
+        rng: &mut WorldRng,
         branches: &mut Vec<LSystemEntry<OakEntryType>>,
     ) {
         if let OakEntryType::Branch { angle_x, angle_z } = entry.entry_type {
@@ -1,17 +1,20 @@
 use std::f32::consts::PI;
 
 use bevy::math::{Quat, Vec3};
-use rand::{Rng, rngs::StdRng};
+use rand::Rng;
 
 use crate::{
     utils::rotation::{RotationDirection, rotate_around},
-    world_generation::chunk_generation::{
-        VOXEL_SIZE,
-        block_type::BlockType,
-        structures::foliage_generation::{
-            entry_range::EntryRange,
-            tree_l_system::{LSystem, LSystemEntry},
+    world_generation::{
+        chunk_generation::{
+            VOXEL_SIZE,
+            block_type::BlockType,
+            structures::foliage_generation::{
+                entry_range::EntryRange,
+                tree_l_system::{LSystem, LSystemEntry},
+            },
         },
+        seed_derivation::WorldRng,
     },
 };
 
@@ -39,7 +42,7 @@ pub enum PineEntryType {
 impl LSystem<PineEntryType> for PineLSystem {
     fn get_start_state(
         position: Vec3,
-        rng: &mut StdRng,
+        rng: &mut WorldRng,
     ) -> Vec<LSystemEntry<PineEntryType>> {
         let mut entries = vec![];
 
@@ -94,7 +97,7 @@ impl LSystem<PineEntryType> for PineLSystem {
 
     fn process_tree(
         mut start_state: &mut Vec<LSystemEntry<PineEntryType>>,
-        rng: &mut StdRng,
+        rng: &mut WorldRng,
     ) {
         while Self::recurse_l_system(&mut start_state, rng) {}
     }
@@ -108,7 +111,7 @@ impl LSystem<PineEntryType> for PineLSystem {
 
     fn recurse_entry(
         entry: &LSystemEntry<PineEntryType>,
-        rng: &mut StdRng,
+        rng: &mut WorldRng,
         branches: &mut Vec<LSystemEntry<PineEntryType>>,
     ) {
         match entry.entry_type {
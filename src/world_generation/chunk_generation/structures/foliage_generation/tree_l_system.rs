@@ -4,13 +4,16 @@ use crate::{
         rotation::{RotationDirection, rotate_around},
         vec_utils::vec_round_to_int,
     },
-    world_generation::chunk_generation::{
-        VOXEL_SIZE, block_type::BlockType,
-        structures::foliage_generation::entry_range::EntryRange,
+    world_generation::{
+        chunk_generation::{
+            VOXEL_SIZE, block_type::BlockType,
+            structures::foliage_generation::entry_range::EntryRange,
+        },
+        seed_derivation::WorldRng,
     },
 };
 use bevy::math::Vec3;
-use rand::{Rng, rngs::StdRng};
+use rand::Rng;
 
 pub struct LSystemEntry<EntryEnum> {
     pub pos: Vec3,
@@ -23,7 +26,7 @@ pub struct LSystemEntry<EntryEnum> {
 /// See [L-System](https://en.wikipedia.org/wiki/L-system) for more information.
 pub trait LSystem<EntryEnum: Clone + Copy> {
     fn grow_new<const XSIZE: usize, const YSIZE: usize, const ZSIZE: usize>(
-        rng: &mut StdRng,
+        rng: &mut WorldRng,
     ) -> Vec<Vec<Vec<BlockType>>> {
         let pos = Vec3::new(XSIZE as f32 / 2., 0., ZSIZE as f32 / 2.);
         let pos_offset = Vec3 {
@@ -87,7 +90,7 @@ pub trait LSystem<EntryEnum: Clone + Copy> {
 
     fn recurse_l_system(
         data: &mut Vec<LSystemEntry<EntryEnum>>,
-        rng: &mut StdRng,
+        rng: &mut WorldRng,
     ) -> bool {
         let mut i = 0usize;
         let mut changed = false;
@@ -187,16 +190,16 @@ pub trait LSystem<EntryEnum: Clone + Copy> {
 
     fn get_start_state(
         position: Vec3,
-        rng: &mut StdRng,
+        rng: &mut WorldRng,
     ) -> Vec<LSystemEntry<EntryEnum>>;
     fn process_tree(
         start_state: &mut Vec<LSystemEntry<EntryEnum>>,
-        rng: &mut StdRng,
+        rng: &mut WorldRng,
     );
     fn get_block_from_entry(entry: &LSystemEntry<EntryEnum>) -> BlockType;
     fn recurse_entry(
         entry: &LSystemEntry<EntryEnum>,
-        rng: &mut StdRng,
+        rng: &mut WorldRng,
         branches: &mut Vec<LSystemEntry<EntryEnum>>,
     );
 }
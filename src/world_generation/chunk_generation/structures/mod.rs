@@ -3,4 +3,5 @@ pub mod oak_structure_generator;
 pub mod pine_structure_generator;
 pub mod structure_generator;
 pub mod structure_model;
+pub mod structure_placement;
 pub mod tree_structure_generator;
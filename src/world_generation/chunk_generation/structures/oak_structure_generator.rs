@@ -1,15 +1,16 @@
-use rand::rngs::StdRng;
-
-use crate::world_generation::chunk_generation::{
-    VOXEL_SIZE,
-    block_type::BlockType,
-    structures::{
-        foliage_generation::{
-            oak_l_system::OakLSystem, tree_l_system::LSystem,
+use crate::world_generation::{
+    chunk_generation::{
+        VOXEL_SIZE,
+        block_type::BlockType,
+        structures::{
+            foliage_generation::{
+                oak_l_system::OakLSystem, tree_l_system::LSystem,
+            },
+            structure_generator::VoxelStructureMetadata,
+            tree_structure_generator::TreeStructureGenerator,
         },
-        structure_generator::VoxelStructureMetadata,
-        tree_structure_generator::TreeStructureGenerator,
     },
+    seed_derivation::WorldRng,
 };
 
 pub struct OakStructureGenerator {
@@ -31,7 +32,7 @@ impl TreeStructureGenerator for OakStructureGenerator {
         &self.fixed_structure_metadata
     }
 
-    fn grow(&self, rng: &mut StdRng) -> Vec<Vec<Vec<BlockType>>> {
+    fn grow(&self, rng: &mut WorldRng) -> Vec<Vec<Vec<BlockType>>> {
         OakLSystem::grow_new::<OAK_VOXEL_SIZE, OAK_VOXEL_SIZE, OAK_VOXEL_SIZE>(
             rng,
         )
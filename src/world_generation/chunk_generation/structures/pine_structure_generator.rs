@@ -1,15 +1,16 @@
-use rand::rngs::StdRng;
-
-use crate::world_generation::chunk_generation::{
-    VOXEL_SIZE,
-    block_type::BlockType,
-    structures::{
-        foliage_generation::{
-            pine_l_system::PineLSystem, tree_l_system::LSystem,
+use crate::world_generation::{
+    chunk_generation::{
+        VOXEL_SIZE,
+        block_type::BlockType,
+        structures::{
+            foliage_generation::{
+                pine_l_system::PineLSystem, tree_l_system::LSystem,
+            },
+            structure_generator::VoxelStructureMetadata,
+            tree_structure_generator::TreeStructureGenerator,
         },
-        structure_generator::VoxelStructureMetadata,
-        tree_structure_generator::TreeStructureGenerator,
     },
+    seed_derivation::WorldRng,
 };
 
 pub struct PineStructureGenerator {
@@ -32,7 +33,7 @@ impl TreeStructureGenerator for PineStructureGenerator {
         &self.fixed_structure_metadata
     }
 
-    fn grow(&self, rng: &mut StdRng) -> Vec<Vec<Vec<BlockType>>> {
+    fn grow(&self, rng: &mut WorldRng) -> Vec<Vec<Vec<BlockType>>> {
         PineLSystem::grow_new::<
             PINE_VOXEL_SIZE,
             PINE_VOXEL_HEIGHT,
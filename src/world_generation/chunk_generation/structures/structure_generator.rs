@@ -1,18 +1,18 @@
 use bevy::math::IVec2;
-use fastnoise_lite::FastNoiseLite;
 use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::Arc};
 
 use crate::world_generation::chunk_generation::{
     block_type::BlockType, chunk_lod::ChunkLod,
+    structures::structure_placement::PlacementSettings,
 };
 
 pub struct VoxelStructureMetadata {
     pub model_size: [i32; 3],
-    pub generation_size: [i32; 2],
-    pub grid_offset: [i32; 2],
+    pub placement: PlacementSettings,
     pub generate_debug_blocks: bool,
     pub debug_rgb_multiplier: [f32; 3],
-    pub noise: FastNoiseLite,
+    /// Seeds where the structures are placed and how their models are grown.
+    pub seed: u64,
 }
 
 pub trait StructureGenerator {
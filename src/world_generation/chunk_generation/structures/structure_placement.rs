@@ -0,0 +1,455 @@
+use std::{
+    cell::RefCell, collections::HashMap, f32::consts::SQRT_2, sync::Arc,
+};
+
+use bevy::math::{IVec2, Vec2};
+use fastnoise_lite::{FastNoiseLite, NoiseType};
+use itertools::Itertools;
+use noise::NoiseFn;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::world_generation::{
+    chunk_generation::{
+        VOXEL_SIZE,
+        country::{
+            country_cache_position::CountryPosition, country_data::CountryData,
+        },
+        structures::structure_generator::{
+            StructureGenerator, VoxelStructureMetadata,
+        },
+        voxel_generation::get_min_distance_to_path,
+    },
+    generation_options::{Biome, BiomeSettings, WaterSettings},
+    seed_derivation::{
+        STRUCTURE_DENSITY_SALT, STRUCTURE_PLACEMENT_SALT, derive_seed,
+        positional_rng,
+    },
+};
+
+/// How the structures of one generator are scattered over the world.
+///
+/// Every cell of a grid with a cell size of `spacing / √2` gets one
+/// candidate, which is thinned out by the density and the rules. Of the
+/// candidates that are left, only the one with the highest priority is
+/// placed within `spacing` of each other. This only depends on the cells
+/// around a candidate, so every chunk places the same structures.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlacementSettings {
+    /// Minimum distance in meters between two structures of the generator.
+    pub spacing: f32,
+    #[serde(default)]
+    pub density: DensityMap,
+    #[serde(default)]
+    pub rules: Vec<PlacementRule>,
+}
+
+impl Default for PlacementSettings {
+    fn default() -> Self {
+        Self {
+            spacing: 64.,
+            density: DensityMap::default(),
+            rules: vec![],
+        }
+    }
+}
+
+impl PlacementSettings {
+    /// Checks that the rules of the structure at `index` of a preset only
+    /// refer to structures before it.
+    pub fn validate(&self, index: usize) -> Result<(), anyhow::Error> {
+        for rule in &self.rules {
+            if let PlacementRule::MinDistanceTo { structure, .. } = rule
+                && *structure >= index
+            {
+                anyhow::bail!(
+                    "Structure {index} can only keep its distance to \
+                     structures before it, not to structure {structure}!"
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The chance of a candidate to be kept.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum DensityMap {
+    Constant(f64),
+    /// Simplex noise mapped from `min` to `max`.
+    Noise {
+        frequency: f32,
+        min: f64,
+        max: f64,
+    },
+}
+
+impl Default for DensityMap {
+    fn default() -> Self {
+        Self::Constant(1.)
+    }
+}
+
+/// A condition the center of a structure has to meet to be placed.
+/// Heights and distances are in meters.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum PlacementRule {
+    MaxSteepness(f64),
+    MinAltitude(f32),
+    MaxAltitude(f32),
+    Biomes(Vec<Biome>),
+    MinPathDistance(f32),
+    MaxPathDistance(f32),
+    /// Keeps the distance to the structures of an earlier generator of the
+    /// same preset.
+    MinDistanceTo {
+        structure: usize,
+        distance: f32,
+    },
+}
+
+/// The terrain the placement rules are checked against.
+pub struct PlacementContext<'a> {
+    /// The terrain height in voxels, without the lod adjustment.
+    pub terrain_height: &'a dyn NoiseFn<f64, 2>,
+    pub terrain_steepness: &'a dyn NoiseFn<f64, 2>,
+    /// Every country within `placement_reach` of the placed area. A
+    /// candidate is checked against the paths and lakes of the country it
+    /// lies in, so chunks on both sides of a border agree.
+    pub countries: &'a [CountryData],
+    pub biome_settings: &'a BiomeSettings,
+    pub water_settings: &'a WaterSettings,
+}
+
+impl PlacementContext<'_> {
+    fn country(&self, column: IVec2) -> Option<&CountryData> {
+        let country_pos = CountryPosition::from_column(column);
+        self.countries
+            .iter()
+            .find(|country| country.country_pos == country_pos)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct StructurePlacement {
+    /// The candidate cell of the structure, which also seeds its model.
+    pub cell: IVec2,
+    /// The column the model is centered on.
+    pub center: IVec2,
+}
+
+#[derive(Clone, Copy)]
+struct Candidate {
+    center: IVec2,
+    priority: f64,
+}
+
+/// Places the structures of one generator, caching the candidates of the
+/// cells it has looked at.
+pub struct StructurePlacer<'a> {
+    metadata: &'a VoxelStructureMetadata,
+    spacing: f32,
+    cell_size: f32,
+    density_noise: Option<FastNoiseLite>,
+    candidates: RefCell<HashMap<IVec2, Option<Candidate>>>,
+    placements: RefCell<HashMap<IVec2, Option<IVec2>>>,
+}
+
+impl<'a> StructurePlacer<'a> {
+    pub fn new(metadata: &'a VoxelStructureMetadata) -> Self {
+        let placement = &metadata.placement;
+        let density_noise = match placement.density {
+            DensityMap::Constant(_) => None,
+            DensityMap::Noise { frequency, .. } => {
+                let mut noise = FastNoiseLite::with_seed(derive_seed(
+                    metadata.seed,
+                    STRUCTURE_DENSITY_SALT,
+                )
+                    as i32);
+                noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+                noise.set_frequency(Some(frequency));
+                Some(noise)
+            }
+        };
+        let spacing = (placement.spacing / VOXEL_SIZE).max(1.);
+
+        Self {
+            metadata,
+            spacing,
+            cell_size: spacing / SQRT_2,
+            density_noise,
+            candidates: RefCell::new(HashMap::new()),
+            placements: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// The structures whose models reach into the area from `min` to `max`.
+    /// `earlier` are the placers of the structures before this one in the
+    /// preset.
+    pub fn placements_in(
+        &self,
+        min: IVec2,
+        max: IVec2,
+        context: &PlacementContext,
+        earlier: &[StructurePlacer],
+    ) -> Vec<StructurePlacement> {
+        let half_size = self.half_model_size();
+        let model_size = IVec2::new(
+            self.metadata.model_size[0],
+            self.metadata.model_size[2],
+        );
+
+        self.cells_between(
+            (min - half_size).as_vec2(),
+            (max + half_size).as_vec2(),
+        )
+        .filter_map(|cell| {
+            let center = self.placed(cell, context, earlier)?;
+            let corner = center - half_size;
+            (corner.cmple(max).all() && (corner + model_size).cmpgt(min).all())
+                .then_some(StructurePlacement { cell, center })
+        })
+        .collect()
+    }
+
+    fn half_model_size(&self) -> IVec2 {
+        IVec2::new(
+            self.metadata.model_size[0] / 2,
+            self.metadata.model_size[2] / 2,
+        )
+    }
+
+    fn cells_between(
+        &self,
+        min: Vec2,
+        max: Vec2,
+    ) -> impl Iterator<Item = IVec2> {
+        let min = (min / self.cell_size).floor().as_ivec2();
+        let max = (max / self.cell_size).floor().as_ivec2();
+        (min.x..=max.x)
+            .cartesian_product(min.y..=max.y)
+            .map(|(x, y)| IVec2::new(x, y))
+    }
+
+    /// The center of the structure placed in the cell, if any.
+    fn placed(
+        &self,
+        cell: IVec2,
+        context: &PlacementContext,
+        earlier: &[StructurePlacer],
+    ) -> Option<IVec2> {
+        if let Some(placed) = self.placements.borrow().get(&cell) {
+            return *placed;
+        }
+
+        let placed = self
+            .candidate(cell, context, earlier)
+            .filter(|candidate| {
+                let position = candidate.center.as_vec2();
+                self.cells_between(
+                    position - self.spacing,
+                    position + self.spacing,
+                )
+                .filter(|other_cell| *other_cell != cell)
+                .filter_map(|other_cell| {
+                    self.candidate(other_cell, context, earlier)
+                })
+                .all(|other| {
+                    other.center.as_vec2().distance(position) >= self.spacing
+                        || other.priority < candidate.priority
+                })
+            })
+            .map(|candidate| candidate.center);
+
+        self.placements.borrow_mut().insert(cell, placed);
+        placed
+    }
+
+    fn candidate(
+        &self,
+        cell: IVec2,
+        context: &PlacementContext,
+        earlier: &[StructurePlacer],
+    ) -> Option<Candidate> {
+        if let Some(candidate) = self.candidates.borrow().get(&cell) {
+            return *candidate;
+        }
+
+        let mut rng = positional_rng(
+            derive_seed(self.metadata.seed, STRUCTURE_PLACEMENT_SALT),
+            cell,
+        );
+        let offset = Vec2::new(rng.random(), rng.random());
+        let center = ((cell.as_vec2() + offset) * self.cell_size)
+            .floor()
+            .as_ivec2();
+        let priority = rng.random::<f64>();
+
+        let candidate = (rng.random::<f64>() < self.density(center)
+            && self.follows_rules(center, context, earlier))
+        .then_some(Candidate { center, priority });
+
+        self.candidates.borrow_mut().insert(cell, candidate);
+        candidate
+    }
+
+    fn density(&self, center: IVec2) -> f64 {
+        match self.metadata.placement.density {
+            DensityMap::Constant(density) => density,
+            DensityMap::Noise { min, max, .. } => {
+                let value = self.density_noise.as_ref().map_or(0., |noise| {
+                    noise.get_noise_2d(center.x as f32, center.y as f32) as f64
+                        * 0.5
+                        + 0.5
+                });
+                min + (max - min) * value
+            }
+        }
+    }
+
+    fn follows_rules(
+        &self,
+        center: IVec2,
+        context: &PlacementContext,
+        earlier: &[StructurePlacer],
+    ) -> bool {
+        let Some(country) = context.country(center) else {
+            return false;
+        };
+
+        let position = center.as_dvec2().to_array();
+        let height = context.terrain_height.get(position) as f32 * VOXEL_SIZE;
+        let steepness = context.terrain_steepness.get(position);
+
+        if steepness > context.biome_settings.max_structure_steepness
+            || height / VOXEL_SIZE
+                < country
+                    .lake_cache
+                    .water_level(center, context.water_settings)
+        {
+            return false;
+        }
+
+        // Structures never stand on a path, the rules can only keep them
+        // further away.
+        let half_size = self.half_model_size();
+        let paths = [
+            &country.this_path_cache.paths,
+            &country.bottom_path_cache.paths,
+            &country.left_path_cache.paths,
+        ];
+        let (path_distance, _, _, _) = get_min_distance_to_path(
+            center,
+            paths.into_iter().flatten(),
+            (half_size + IVec2::ONE * 10).max(IVec2::splat(self.path_margin())),
+        );
+        if (path_distance as i32) < half_size.x + half_size.y {
+            return false;
+        }
+        let path_distance = path_distance * VOXEL_SIZE;
+
+        self.metadata.placement.rules.iter().all(|rule| match rule {
+            PlacementRule::MaxSteepness(max) => steepness <= *max,
+            PlacementRule::MinAltitude(min) => height >= *min,
+            PlacementRule::MaxAltitude(max) => height <= *max,
+            PlacementRule::Biomes(biomes) => biomes
+                .contains(&context.biome_settings.biome_at(height, steepness)),
+            PlacementRule::MinPathDistance(min) => path_distance >= *min,
+            PlacementRule::MaxPathDistance(max) => path_distance <= *max,
+            PlacementRule::MinDistanceTo {
+                structure,
+                distance,
+            } => earlier.get(*structure).is_none_or(|other| {
+                !other.is_placed_within(
+                    center,
+                    distance / VOXEL_SIZE,
+                    context,
+                    &earlier[..*structure],
+                )
+            }),
+        })
+    }
+
+    /// How far around a structure paths have to be looked for, so the path
+    /// rules can see them.
+    fn path_margin(&self) -> i32 {
+        self.metadata
+            .placement
+            .rules
+            .iter()
+            .filter_map(|rule| match rule {
+                PlacementRule::MinPathDistance(distance)
+                | PlacementRule::MaxPathDistance(distance) => {
+                    Some((distance / VOXEL_SIZE).ceil() as i32)
+                }
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// How far from the area it places structures in the placer looks at
+    /// candidates, in voxels.
+    fn reach(&self, earlier: &[StructurePlacer]) -> f32 {
+        self.half_model_size().max_element() as f32
+            + self.cell_size
+            + self.candidate_reach(earlier)
+    }
+
+    /// How far from a candidate the candidates it is checked against can
+    /// lie, including the ones the distance rules look at.
+    fn candidate_reach(&self, earlier: &[StructurePlacer]) -> f32 {
+        let rule_reach = self
+            .metadata
+            .placement
+            .rules
+            .iter()
+            .filter_map(|rule| match rule {
+                PlacementRule::MinDistanceTo {
+                    structure,
+                    distance,
+                } => earlier.get(*structure).map(|other| {
+                    distance / VOXEL_SIZE
+                        + other.cell_size
+                        + other.candidate_reach(&earlier[..*structure])
+                }),
+                _ => None,
+            })
+            .fold(0., f32::max);
+
+        self.spacing + self.cell_size + rule_reach
+    }
+
+    fn is_placed_within(
+        &self,
+        position: IVec2,
+        distance: f32,
+        context: &PlacementContext,
+        earlier: &[StructurePlacer],
+    ) -> bool {
+        let position = position.as_vec2();
+        self.cells_between(position - distance, position + distance)
+            .filter_map(|cell| self.placed(cell, context, earlier))
+            .any(|center| center.as_vec2().distance(position) < distance)
+    }
+}
+
+/// How far in voxels past an area the structures placed in it look at
+/// the terrain, so every country they can be checked against is known.
+pub fn placement_reach(
+    structure_generators: &[Arc<Box<dyn StructureGenerator + Send + Sync>>],
+) -> i32 {
+    let placers = structure_generators
+        .iter()
+        .map(|structure_generator| {
+            StructurePlacer::new(structure_generator.get_structure_metadata())
+        })
+        .collect_vec();
+
+    placers
+        .iter()
+        .enumerate()
+        .map(|(index, placer)| placer.reach(&placers[..index]))
+        .fold(0., f32::max)
+        .ceil() as i32
+}
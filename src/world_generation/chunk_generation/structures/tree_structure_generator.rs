@@ -1,14 +1,18 @@
 use std::rc::Rc;
 
 use bevy::math::IVec2;
-use rand::{SeedableRng, rngs::StdRng};
 
-use crate::world_generation::chunk_generation::{
-    VOXEL_SIZE,
-    block_type::BlockType,
-    chunk_lod::ChunkLod,
-    structures::structure_generator::{
-        StructureGenerator, VoxelStructureMetadata,
+use crate::world_generation::{
+    chunk_generation::{
+        VOXEL_SIZE,
+        block_type::BlockType,
+        chunk_lod::ChunkLod,
+        structures::structure_generator::{
+            StructureGenerator, VoxelStructureMetadata,
+        },
+    },
+    seed_derivation::{
+        STRUCTURE_MODEL_SALT, WorldRng, derive_seed, positional_rng,
     },
 };
 
@@ -20,24 +24,12 @@ pub trait TreeStructureGenerator {
             (metadata.model_size[2] as f32 / VOXEL_SIZE) as i32,
         ];
 
-        let generation_size = [
-            (metadata.generation_size[0] as f32 / VOXEL_SIZE) as i32,
-            (metadata.generation_size[1] as f32 / VOXEL_SIZE) as i32,
-        ];
-
-        let grid_offset = [
-            (metadata.grid_offset[0] as f32 / VOXEL_SIZE) as i32,
-            (metadata.grid_offset[1] as f32 / VOXEL_SIZE) as i32,
-        ];
-
         metadata.model_size = model_size;
-        metadata.generation_size = generation_size;
-        metadata.grid_offset = grid_offset;
     }
 
     fn new(metadata: VoxelStructureMetadata) -> Self;
     fn get_structure_metadata(&self) -> &VoxelStructureMetadata;
-    fn grow(&self, rng: &mut StdRng) -> Vec<Vec<Vec<BlockType>>>;
+    fn grow(&self, rng: &mut WorldRng) -> Vec<Vec<Vec<BlockType>>>;
 }
 
 impl<T: TreeStructureGenerator> StructureGenerator for T {
@@ -50,13 +42,13 @@ impl<T: TreeStructureGenerator> StructureGenerator for T {
         structure_position: IVec2,
         _: ChunkLod,
     ) -> Rc<Vec<Vec<Vec<BlockType>>>> {
-        let noise_value = self.get_structure_metadata().noise.get_noise_2d(
-            structure_position.x as f32,
-            structure_position.y as f32,
-        ) * 0.5
-            + 0.5;
-        let mut rng =
-            StdRng::seed_from_u64((noise_value.abs() * 10000.) as u64);
+        let mut rng = positional_rng(
+            derive_seed(
+                self.get_structure_metadata().seed,
+                STRUCTURE_MODEL_SALT,
+            ),
+            structure_position,
+        );
 
         let voxel_grid = Self::grow(&self, &mut rng);
 
@@ -0,0 +1,86 @@
+use bevy::math::IVec3;
+use noise::{NoiseFn, Simplex};
+
+use crate::world_generation::{
+    chunk_generation::{VOXEL_SIZE, block_type::BlockType},
+    generation_options::UndergroundSettings,
+    seed_derivation::{ORE_SALT, STRATA_SALT, derive_seed},
+};
+
+/// Picks the blocks below the ground of a chunk.
+pub struct Underground<'a> {
+    settings: &'a UndergroundSettings,
+    strata_warp: Simplex,
+    ore_noises: Vec<Simplex>,
+    /// The height in meters after which the strata repeat.
+    strata_height: f32,
+}
+
+impl<'a> Underground<'a> {
+    pub fn new(settings: &'a UndergroundSettings, seed: u64) -> Self {
+        let ore_seed = derive_seed(seed, ORE_SALT);
+        Self {
+            settings,
+            strata_warp: Simplex::new(derive_seed(seed, STRATA_SALT) as u32),
+            ore_noises: (0..settings.ores.len())
+                .map(|index| {
+                    Simplex::new(derive_seed(ore_seed, index as u64) as u32)
+                })
+                .collect(),
+            strata_height: settings
+                .strata
+                .iter()
+                .map(|stratum| stratum.thickness)
+                .sum(),
+        }
+    }
+
+    /// The block at a voxel `depth` meters below the ground of a column
+    /// with `steepness`. `position` is in voxels, not lod units.
+    pub fn block_at(
+        &self,
+        position: IVec3,
+        depth: f32,
+        steepness: f64,
+    ) -> BlockType {
+        let dirt_depth = self.settings.dirt_depth
+            * (1. - steepness / self.settings.max_dirt_steepness).max(0.)
+                as f32;
+        if depth < dirt_depth {
+            return BlockType::Dirt;
+        }
+
+        let point = position.as_dvec3() * VOXEL_SIZE as f64;
+        for (ore, noise) in self.settings.ores.iter().zip(&self.ore_noises) {
+            if (ore.min_depth..ore.max_depth).contains(&depth)
+                && noise.get((point * ore.frequency).to_array()) > ore.threshold
+            {
+                return ore.block;
+            }
+        }
+
+        if self.strata_height <= 0. {
+            return BlockType::Stone;
+        }
+
+        let warp = self
+            .strata_warp
+            .get((point * self.settings.strata_warp_frequency).to_array())
+            as f32
+            * self.settings.strata_warp;
+        let mut layer_height =
+            (point.y as f32 + warp).rem_euclid(self.strata_height);
+        for stratum in &self.settings.strata {
+            if layer_height < stratum.thickness {
+                return stratum.block;
+            }
+            layer_height -= stratum.thickness;
+        }
+
+        // Only reached through rounding at the top of the last layer.
+        self.settings
+            .strata
+            .last()
+            .map_or(BlockType::Stone, |stratum| stratum.block)
+    }
+}
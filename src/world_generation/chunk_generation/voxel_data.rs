@@ -2,39 +2,158 @@ use bevy::math::IVec3;
 
 use crate::world_generation::chunk_generation::{
     ambient_occlusion::AmbiantOcclusion, block_type::BlockType,
-    mesh_generation::rotate_into_direction,
+    chunk_lod::ChunkLod, mesh_generation::rotate_into_direction,
 };
 
 use super::CHUNK_SIZE;
 
-pub type VoxelArray =
-    [BlockType; (CHUNK_SIZE + 2) * (CHUNK_SIZE + 2) * (CHUNK_SIZE + 2)];
+/// How many voxels a chunk stores, including its border.
+pub const VOXEL_COUNT: usize =
+    (CHUNK_SIZE + 2) * (CHUNK_SIZE + 2) * (CHUNK_SIZE + 2);
 
+/// The blocks of a chunk including its border.
+///
+/// Every voxel stores an index into a palette of the blocks the chunk
+/// contains, packed into as few bits as the palette needs. A chunk made of
+/// a single block, like the air above the terrain, stores no voxels at
+/// all. The chunk knows where it lies in the world, so it can also be
+/// read and written with world coordinates.
+#[derive(Clone)]
 pub struct VoxelData {
-    pub array: VoxelArray,
+    storage: VoxelStorage,
+    /// The world voxel at the corner of voxel `(0, 0, 0)`.
+    origin: IVec3,
+    lod: ChunkLod,
+}
+
+#[derive(Clone)]
+enum VoxelStorage {
+    Single(BlockType),
+    Paletted(PalettedVoxels),
+}
+
+#[derive(Clone)]
+struct PalettedVoxels {
+    palette: Vec<BlockType>,
+    bits: u32,
+    words: Vec<u64>,
 }
 
 impl Default for VoxelData {
     fn default() -> Self {
-        Self {
-            array: [BlockType::Air;
-                (CHUNK_SIZE + 2) * (CHUNK_SIZE + 2) * (CHUNK_SIZE + 2)],
-        }
+        Self::new(IVec3::ZERO, ChunkLod::Full)
     }
 }
 
 impl VoxelData {
+    /// An empty chunk whose voxel `(0, 0, 0)` starts at the world voxel
+    /// `origin`, and whose voxels each cover `lod.multiplier_i32()` world
+    /// voxels along every axis.
+    pub fn new(origin: IVec3, lod: ChunkLod) -> Self {
+        Self {
+            storage: VoxelStorage::Single(BlockType::Air),
+            origin,
+            lod,
+        }
+    }
+
     pub fn get_block<T: Into<IVec3>>(&self, position: T) -> BlockType {
-        let index = Self::position_to_indexes(position);
-        self.array[index]
+        match &self.storage {
+            VoxelStorage::Single(block) => *block,
+            VoxelStorage::Paletted(voxels) => {
+                voxels.get(Self::position_to_indexes(position))
+            }
+        }
     }
 
     pub fn set_block<T: Into<IVec3>>(&mut self, position: T, block: BlockType) {
         let index = Self::position_to_indexes(position);
-        self.array[index] = block;
+        match &mut self.storage {
+            VoxelStorage::Single(current) if *current == block => {}
+            VoxelStorage::Single(current) => {
+                let mut voxels = PalettedVoxels::filled(*current);
+                voxels.set(index, block);
+                self.storage = VoxelStorage::Paletted(voxels);
+            }
+            VoxelStorage::Paletted(voxels) => voxels.set(index, block),
+        }
+    }
+
+    /// The block covering the world voxel `world_position`, `None` if it
+    /// lies outside of this chunk and its border.
+    pub fn get_world_block(&self, world_position: IVec3) -> Option<BlockType> {
+        self.world_to_local(world_position)
+            .map(|position| self.get_block(position))
+    }
+
+    /// Sets the block covering the world voxel `world_position`. Returns
+    /// false if it lies outside of this chunk and its border.
+    pub fn set_world_block(
+        &mut self,
+        world_position: IVec3,
+        block: BlockType,
+    ) -> bool {
+        let Some(position) = self.world_to_local(world_position) else {
+            return false;
+        };
+        self.set_block(position, block);
+        true
+    }
+
+    /// The voxel of this chunk covering a world voxel.
+    pub fn world_to_local(&self, world_position: IVec3) -> Option<IVec3> {
+        let position = (world_position - self.origin)
+            .div_euclid(IVec3::splat(self.lod.multiplier_i32()));
+        (position.min_element() >= 0
+            && position.max_element() < CHUNK_SIZE as i32 + 2)
+            .then_some(position)
     }
 
-    fn position_to_indexes<T: Into<IVec3>>(position: T) -> usize {
+    /// The world voxel at the corner of a voxel of this chunk.
+    pub fn local_to_world(&self, position: IVec3) -> IVec3 {
+        self.origin + position * self.lod.multiplier_i32()
+    }
+
+    pub fn lod(&self) -> ChunkLod {
+        self.lod
+    }
+
+    /// Drops the blocks from the palette no voxel uses anymore, and packs
+    /// the voxels into fewer bits if that lets them. A chunk left with a
+    /// single block stops storing voxels.
+    pub fn compact(&mut self) {
+        let VoxelStorage::Paletted(voxels) = &self.storage else {
+            return;
+        };
+
+        let mut used = vec![false; voxels.palette.len()];
+        for index in 0..VOXEL_COUNT {
+            used[voxels.palette_index(index)] = true;
+        }
+        if used.iter().all(|used| *used) {
+            return;
+        }
+
+        let palette = voxels
+            .palette
+            .iter()
+            .zip(&used)
+            .filter(|(_, used)| **used)
+            .map(|(block, _)| *block)
+            .collect::<Vec<_>>();
+        if let [block] = palette[..] {
+            self.storage = VoxelStorage::Single(block);
+            return;
+        }
+
+        let mut compacted = PalettedVoxels::with_palette(palette);
+        for index in 0..VOXEL_COUNT {
+            compacted.set(index, voxels.get(index));
+        }
+        self.storage = VoxelStorage::Paletted(compacted);
+    }
+
+    pub fn position_to_indexes<T: Into<IVec3>>(position: T) -> usize {
         let position: IVec3 = position.into();
         let index = position.x as usize
             + (position.y as usize * (CHUNK_SIZE + 2))
@@ -52,10 +171,18 @@ impl VoxelData {
         let front = rotate_into_direction(IVec3::Z, direction);
 
         let get_corner_value = |right: IVec3, front: IVec3| -> u8 {
-            let side_1 = self.get_block(position + right) != BlockType::Air;
-            let side_2 = self.get_block(position + front) != BlockType::Air;
-            let corner =
-                self.get_block(position + right + front) != BlockType::Air;
+            let side_1 = self
+                .get_block(position + right)
+                .shape()
+                .casts_ambient_occlusion();
+            let side_2 = self
+                .get_block(position + front)
+                .shape()
+                .casts_ambient_occlusion();
+            let corner = self
+                .get_block(position + right + front)
+                .shape()
+                .casts_ambient_occlusion();
 
             if side_1 && side_2 {
                 return 0;
@@ -89,3 +216,182 @@ impl VoxelData {
         }
     }
 }
+
+impl PalettedVoxels {
+    /// Every voxel set to `block`, with room for a second block.
+    fn filled(block: BlockType) -> Self {
+        Self::with_palette(vec![block])
+    }
+
+    /// Every voxel set to the first block of `palette`.
+    fn with_palette(palette: Vec<BlockType>) -> Self {
+        let bits = Self::bits_for(palette.len());
+        Self {
+            palette,
+            bits,
+            words: vec![0; VOXEL_COUNT.div_ceil(Self::per_word(bits))],
+        }
+    }
+
+    /// The bits needed to index a palette of `length` blocks, at least one.
+    fn bits_for(length: usize) -> u32 {
+        usize::BITS - (length.max(2) - 1).leading_zeros()
+    }
+
+    /// How many voxels fit into a word. Voxels never straddle two words.
+    fn per_word(bits: u32) -> usize {
+        (u64::BITS / bits) as usize
+    }
+
+    fn get(&self, index: usize) -> BlockType {
+        self.palette[self.palette_index(index)]
+    }
+
+    fn palette_index(&self, index: usize) -> usize {
+        let per_word = Self::per_word(self.bits);
+        let shift = (index % per_word) as u32 * self.bits;
+        ((self.words[index / per_word] >> shift) & self.mask()) as usize
+    }
+
+    fn set(&mut self, index: usize, block: BlockType) {
+        let palette_index = match self
+            .palette
+            .iter()
+            .position(|palette_block| *palette_block == block)
+        {
+            Some(palette_index) => palette_index,
+            None => {
+                self.palette.push(block);
+                if Self::bits_for(self.palette.len()) > self.bits {
+                    self.repack(self.bits + 1);
+                }
+                self.palette.len() - 1
+            }
+        };
+
+        let per_word = Self::per_word(self.bits);
+        let shift = (index % per_word) as u32 * self.bits;
+        let mask = self.mask();
+        let word = &mut self.words[index / per_word];
+        *word = (*word & !(mask << shift)) | ((palette_index as u64) << shift);
+    }
+
+    /// Moves the voxels into words of `bits` per voxel.
+    fn repack(&mut self, bits: u32) {
+        let per_word = Self::per_word(bits);
+        let mut words = vec![0; VOXEL_COUNT.div_ceil(per_word)];
+        for index in 0..VOXEL_COUNT {
+            let shift = (index % per_word) as u32 * bits;
+            words[index / per_word] |=
+                (self.palette_index(index) as u64) << shift;
+        }
+        self.bits = bits;
+        self.words = words;
+    }
+
+    fn mask(&self) -> u64 {
+        (1 << self.bits) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Enough distinct blocks to need five bits per voxel.
+    const BLOCKS: [BlockType; 17] = [
+        BlockType::Air,
+        BlockType::Stone,
+        BlockType::Grass,
+        BlockType::Log,
+        BlockType::Snow,
+        BlockType::Leaf,
+        BlockType::TallGrass,
+        BlockType::Flower,
+        BlockType::StoneSlab,
+        BlockType::LogFence,
+        BlockType::GlassPane,
+        BlockType::Lantern,
+        BlockType::Water,
+        BlockType::Dirt,
+        BlockType::Slate,
+        BlockType::Sandstone,
+        BlockType::CoalOre,
+    ];
+
+    /// The inverse of `VoxelData::position_to_indexes`.
+    fn position(index: usize) -> IVec3 {
+        let size = CHUNK_SIZE + 2;
+        IVec3::new(
+            (index % size) as i32,
+            (index / size % size) as i32,
+            (index / (size * size)) as i32,
+        )
+    }
+
+    fn assert_blocks(voxels: &VoxelData, expected: &[BlockType]) {
+        for (index, block) in expected.iter().enumerate() {
+            assert_eq!(voxels.get_block(position(index)), *block, "{index}");
+        }
+    }
+
+    fn paletted(voxels: &VoxelData) -> &PalettedVoxels {
+        match &voxels.storage {
+            VoxelStorage::Paletted(paletted) => paletted,
+            VoxelStorage::Single(_) => panic!("the voxels aren't paletted"),
+        }
+    }
+
+    #[test]
+    fn position_to_indexes_round_trips() {
+        for index in 0..VOXEL_COUNT {
+            assert_eq!(VoxelData::position_to_indexes(position(index)), index);
+        }
+    }
+
+    #[test]
+    fn palette_grows_and_compacts() {
+        let mut voxels = VoxelData::default();
+        let mut expected = vec![BlockType::Air; VOXEL_COUNT];
+        assert!(matches!(
+            voxels.storage,
+            VoxelStorage::Single(BlockType::Air)
+        ));
+
+        // Every new block takes over every `count`th voxel, so the palette
+        // grows by one block at a time and repacks at 2, 3, 5 and 17.
+        for count in 2..=BLOCKS.len() {
+            let block = BLOCKS[count - 1];
+            for index in (count - 1..VOXEL_COUNT).step_by(count) {
+                voxels.set_block(position(index), block);
+                expected[index] = block;
+            }
+
+            let paletted = paletted(&voxels);
+            assert_eq!(paletted.palette.len(), count);
+            assert_eq!(paletted.bits, PalettedVoxels::bits_for(count));
+            assert_blocks(&voxels, &expected);
+        }
+        assert_eq!(paletted(&voxels).bits, 5);
+
+        // Leaving two blocks packs the voxels into a single bit again.
+        for (index, block) in expected.iter_mut().enumerate() {
+            *block = BLOCKS[index % 2];
+            voxels.set_block(position(index), *block);
+        }
+        voxels.compact();
+        assert_eq!(paletted(&voxels).palette, BLOCKS[..2]);
+        assert_eq!(paletted(&voxels).bits, 1);
+        assert_blocks(&voxels, &expected);
+
+        for index in 0..VOXEL_COUNT {
+            voxels.set_block(position(index), BlockType::Stone);
+        }
+        voxels.compact();
+        assert!(matches!(
+            voxels.storage,
+            VoxelStorage::Single(BlockType::Stone)
+        ));
+        assert_blocks(&voxels, &[BlockType::Stone; VOXEL_COUNT]);
+    }
+}
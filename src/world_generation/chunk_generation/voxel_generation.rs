@@ -1,95 +1,176 @@
-use crate::utils::div_floor::div_floor;
 use crate::world_generation::chunk_generation::block_type::BlockType;
 use crate::world_generation::chunk_generation::chunk_lod::ChunkLod;
 use crate::world_generation::chunk_generation::country::country_data::CountryData;
 use crate::world_generation::chunk_generation::country::path_data::{Path, PathLine};
+use crate::world_generation::chunk_generation::country::river_data::River;
+use crate::world_generation::chunk_generation::decoration::{
+    DECORATION_REACH, Surface, decorate,
+};
 use crate::world_generation::chunk_generation::structures::structure_generator::{
     StructureGenerator, StructureGeneratorCache,
 };
+use crate::world_generation::chunk_generation::structures::structure_placement::{
+    PlacementContext, StructurePlacer,
+};
+use crate::world_generation::chunk_generation::underground::Underground;
 use crate::world_generation::chunk_generation::voxel_data::VoxelData;
 use crate::world_generation::chunk_generation::{CHUNK_SIZE, VOXEL_SIZE};
-use crate::world_generation::generation_options::GenerationOptions;
-use bevy::math::{DVec2, IVec2};
+use crate::world_generation::generation_options::{
+    Biome, GenerationOptions, RiverSettings,
+};
+use bevy::math::{IVec2, IVec3};
 use bevy::prelude::Vec2;
+use itertools::Itertools;
 use noise::NoiseFn;
-use rand::prelude::StdRng;
-use rand::{Rng, SeedableRng};
 use std::usize;
 
 use super::noise::full_cache::FullCache;
 use super::noise::lod_height_adjuster::LodHeightAdjuster;
+use super::noise::noise_grid::{
+    GridNoiseFn, NOISE_GRID_SIZE, NoiseGrid, NoiseGridArea,
+};
 use super::noise::steepness::Steepness;
 
-pub fn generate_voxels(
-    position: [i32; 3],
+/// The terrain of a column, the same for every chunk stacked on it.
+#[derive(Clone, Copy)]
+pub struct ColumnData {
+    /// Height of the ground in lod units, with paths and rivers cut in.
+    pub height: f32,
+    pub biome: Biome,
+    pub steepness: f64,
+    pub is_path: bool,
+    /// Height in lod units water fills the column up to.
+    pub water_level: f32,
+}
+
+impl ColumnData {
+    /// The block on top of the ground.
+    pub fn surface_block(&self) -> BlockType {
+        if self.is_path {
+            return BlockType::Log;
+        }
+
+        match self.biome {
+            Biome::Grass => BlockType::Grass,
+            Biome::Snow => BlockType::Snow,
+            Biome::Rock => BlockType::Stone,
+        }
+    }
+}
+
+/// How many columns past the border of a chunk are generated, so the
+/// decorations standing there can still reach into the chunk.
+const COLUMN_MARGIN: i32 = DECORATION_REACH;
+/// Width of the generated columns of a chunk.
+const COLUMNS_SIZE: i32 = CHUNK_SIZE as i32 + 2 + 2 * COLUMN_MARGIN;
+
+/// The columns of a chunk including its border and `COLUMN_MARGIN`
+/// columns around it.
+pub struct ChunkColumns {
+    columns: Vec<ColumnData>,
+    /// The lowest terrain height in lod units inside of the chunk, before
+    /// paths and rivers are cut in.
+    pub min_terrain_height: f64,
+}
+
+impl ChunkColumns {
+    pub fn get(&self, x: usize, z: usize) -> &ColumnData {
+        self.get_around(x as i32, z as i32)
+    }
+
+    /// A column in the same coordinates as `get`, which can also lie in
+    /// the margin around the border.
+    pub fn get_around(&self, x: i32, z: i32) -> &ColumnData {
+        &self.columns
+            [((x + COLUMN_MARGIN) * COLUMNS_SIZE + z + COLUMN_MARGIN) as usize]
+    }
+}
+
+pub fn generate_columns(
+    chunk_position: IVec2,
     generation_options: &GenerationOptions,
     chunk_lod: ChunkLod,
     country_data: &CountryData,
-) -> (VoxelData, i32, bool) {
-    let mut blocks = VoxelData::default();
-
+) -> ChunkColumns {
     let terrain_noise = FullCache::new(LodHeightAdjuster::new(
-        generation_options.get_terrain_noise(),
+        country_data.terrain_height(generation_options),
         chunk_lod,
     ));
     let terrain_steepness = FullCache::new(Steepness::new(FullCache::new(
-        generation_options.get_terrain_noise(),
+        country_data.terrain_height(generation_options),
     )));
 
-    let chunk_noise_offset =
-        DVec2::new(position[0] as f64, position[2] as f64) * CHUNK_SIZE as f64;
-
-    let min_height =
-        (get_min_in_noise_map(&terrain_noise, chunk_noise_offset, chunk_lod)
-            as i32)
-            - 2
-            + position[1] * CHUNK_SIZE as i32
-            - 10 / chunk_lod.multiplier_i32();
+    let grid_area = NoiseGridArea::from_chunk(
+        [chunk_position.x, 0, chunk_position.y],
+        chunk_lod,
+    );
+    let terrain_grid = terrain_noise.get_grid(&grid_area);
+    let steepness_grid = terrain_steepness.get_grid(&grid_area);
 
-    let mut generate_more: bool = false;
+    let biome_settings = &generation_options.biome_settings;
 
-    let all_paths = vec![
+    let all_paths = [
         &country_data.this_path_cache.paths,
         &country_data.bottom_path_cache.paths,
         &country_data.left_path_cache.paths,
     ];
 
-    let structure_generators: Vec<StructureGeneratorCache> = generation_options
-        .structure_generators
+    let margin = IVec2::splat(COLUMN_MARGIN * chunk_lod.multiplier_i32());
+    let area_min = chunk_position * CHUNK_SIZE as i32 - margin;
+    let area_max = chunk_position * CHUNK_SIZE as i32
+        + IVec2::ONE * (CHUNK_SIZE as i32 + 1) * chunk_lod.multiplier_i32()
+        + margin;
+    let area_center = (area_min + area_max) / 2;
+    let area_half_size = (area_max - area_min) / 2;
+    let rivers = country_data
+        .river_caches
         .iter()
-        .map(|structure_generator| {
-            StructureGeneratorCache::new(structure_generator)
+        .flat_map(|river_cache| &river_cache.rivers)
+        .filter(|river| {
+            river
+                .path
+                .is_in_box(area_center, area_half_size + river_margin(river))
         })
-        .collect();
+        .collect_vec();
 
-    for x in 0..CHUNK_SIZE + 2 {
-        for z in 0..CHUNK_SIZE + 2 {
-            let total_x = position[0] * CHUNK_SIZE as i32
-                + x as i32 * chunk_lod.multiplier_i32();
-            let total_z = position[2] * CHUNK_SIZE as i32
-                + z as i32 * chunk_lod.multiplier_i32();
+    let mut columns =
+        Vec::with_capacity((COLUMNS_SIZE * COLUMNS_SIZE) as usize);
+    let column_range = -COLUMN_MARGIN..COLUMNS_SIZE - COLUMN_MARGIN;
+    let in_grid = |index: i32| (0..NOISE_GRID_SIZE as i32).contains(&index);
 
-            let noise_position = [total_x as f64, total_z as f64];
+    for x in column_range.clone() {
+        for z in column_range.clone() {
+            let total_x = chunk_position.x * CHUNK_SIZE as i32
+                + x * chunk_lod.multiplier_i32();
+            let total_z = chunk_position.y * CHUNK_SIZE as i32
+                + z * chunk_lod.multiplier_i32();
 
             //let dryness = value_noise.get([total_x as f64, total_z as f64]);
             //let mountain = mountain_noise.get([total_x as f64, total_z as f64]);
 
-            let steepness = terrain_steepness.get(noise_position);
-
-            let mut noise_height = terrain_noise.get(noise_position) as f32;
-
-            let is_snow =
-                noise_height * chunk_lod.multiplier_f32() > 3500. / VOXEL_SIZE;
-            let is_grass_steep = if is_snow {
-                steepness < 1.2
+            // The margin lies outside of the grids and is sampled on its own.
+            let (steepness, mut noise_height) = if in_grid(x) && in_grid(z) {
+                (
+                    steepness_grid.get(x as usize, z as usize),
+                    terrain_grid.get(x as usize, z as usize) as f32,
+                )
             } else {
-                steepness < 0.8
+                let point = [total_x as f64, total_z as f64];
+                (
+                    terrain_steepness.get(point),
+                    terrain_noise.get(point) as f32,
+                )
             };
 
+            let biome = biome_settings.biome_at(
+                noise_height * chunk_lod.multiplier_f32() * VOXEL_SIZE,
+                steepness,
+            );
+
             let (mut path_distance, closest_point_on_path, _, line) =
                 get_min_distance_to_path(
                     IVec2::new(total_x, total_z),
-                    &all_paths,
+                    all_paths.iter().copied().flatten(),
                     IVec2::ONE * 15,
                 );
             let is_path = path_distance <= 8.75;
@@ -125,6 +206,124 @@ pub fn generate_voxels(
                 .max(noise_height - 10.);
             }
 
+            let (carved_height, river_level) = carve_rivers(
+                IVec2::new(total_x, total_z),
+                noise_height,
+                &rivers,
+                &terrain_noise,
+                &generation_options.river_settings,
+                chunk_lod,
+            );
+
+            let water_level = (country_data.lake_cache.water_level(
+                IVec2::new(total_x, total_z),
+                &generation_options.water_settings,
+            ) / chunk_lod.multiplier_f32())
+            .max(river_level.unwrap_or(f32::MIN));
+
+            columns.push(ColumnData {
+                height: carved_height,
+                biome,
+                steepness,
+                is_path,
+                water_level,
+            });
+        }
+    }
+
+    ChunkColumns {
+        columns,
+        min_terrain_height: get_min_in_noise_map(&terrain_grid),
+    }
+}
+
+pub fn generate_voxels(
+    position: [i32; 3],
+    columns: &ChunkColumns,
+    generation_options: &GenerationOptions,
+    chunk_lod: ChunkLod,
+    country_data: &CountryData,
+    countries: &[CountryData],
+) -> (VoxelData, i32, bool) {
+    let terrain_noise = FullCache::new(LodHeightAdjuster::new(
+        country_data.terrain_height(generation_options),
+        chunk_lod,
+    ));
+    let terrain_steepness = FullCache::new(Steepness::new(FullCache::new(
+        country_data.terrain_height(generation_options),
+    )));
+
+    let min_height = (columns.min_terrain_height as i32) - 2
+        + position[1] * CHUNK_SIZE as i32
+        - 10 / chunk_lod.multiplier_i32();
+
+    let mut blocks = VoxelData::new(
+        IVec3::new(
+            position[0] * CHUNK_SIZE as i32,
+            min_height * chunk_lod.multiplier_i32(),
+            position[2] * CHUNK_SIZE as i32,
+        ),
+        chunk_lod,
+    );
+
+    let mut generate_more: bool = false;
+
+    let biome_settings = &generation_options.biome_settings;
+
+    let underground = Underground::new(
+        &generation_options.underground_settings,
+        generation_options.seed,
+    );
+
+    let structure_generators: Vec<StructureGeneratorCache> = generation_options
+        .structure_generators
+        .iter()
+        .map(|structure_generator| {
+            StructureGeneratorCache::new(structure_generator)
+        })
+        .collect();
+
+    let structure_height =
+        FullCache::new(country_data.terrain_height(generation_options));
+    let placement_context = PlacementContext {
+        terrain_height: &structure_height,
+        terrain_steepness: &terrain_steepness,
+        countries,
+        biome_settings,
+        water_settings: &generation_options.water_settings,
+    };
+    let structure_placers = structure_generators
+        .iter()
+        .map(|structure_generator| {
+            StructurePlacer::new(structure_generator.get_structure_metadata())
+        })
+        .collect_vec();
+    let area_min = IVec2::new(position[0], position[2]) * CHUNK_SIZE as i32;
+    let area_max = area_min
+        + IVec2::ONE * (CHUNK_SIZE as i32 + 1) * chunk_lod.multiplier_i32();
+    let structure_placements = structure_placers
+        .iter()
+        .enumerate()
+        .map(|(index, placer)| {
+            placer.placements_in(
+                area_min,
+                area_max,
+                &placement_context,
+                &structure_placers[..index],
+            )
+        })
+        .collect_vec();
+
+    for x in 0..CHUNK_SIZE + 2 {
+        for z in 0..CHUNK_SIZE + 2 {
+            let total_x = position[0] * CHUNK_SIZE as i32
+                + x as i32 * chunk_lod.multiplier_i32();
+            let total_z = position[2] * CHUNK_SIZE as i32
+                + z as i32 * chunk_lod.multiplier_i32();
+
+            let column = columns.get(x, z);
+            let noise_height = column.height;
+
             for y in min_height
                 ..noise_height.min((CHUNK_SIZE as i32 + 2 + min_height) as f32)
                     as i32
@@ -135,78 +334,34 @@ pub fn generate_voxels(
                 blocks.set_block(
                     [x as i32, y as i32 - min_height, z as i32],
                     // BlockType::Gray((biome_noise.get([total_x as f64, total_z as f64]) * 255.) as u8)
-                    if is_path {
-                        BlockType::Log
+                    if y + 1 == noise_height.floor() as i32 || column.is_path {
+                        column.surface_block()
                     } else {
-                        if is_grass_steep
-                            && y + 1 == noise_height.floor() as i32
-                        {
-                            if is_snow {
-                                BlockType::Snow
-                            } else {
-                                BlockType::Grass
-                            }
-                        } else {
-                            BlockType::Stone
-                        }
+                        underground.block_at(
+                            IVec3::new(
+                                total_x,
+                                y * chunk_lod.multiplier_i32(),
+                                total_z,
+                            ),
+                            (noise_height.floor() - 1. - y as f32)
+                                * chunk_lod.multiplier_f32()
+                                * VOXEL_SIZE,
+                            column.steepness,
+                        )
                     },
                 );
             }
 
-            for structure_generator in &structure_generators {
+            for (structure_generator, placements) in
+                structure_generators.iter().zip(&structure_placements)
+            {
                 let structure_metadata =
                     structure_generator.get_structure_metadata();
-                let structure_offset_x = div_floor(
-                    total_x + structure_metadata.grid_offset[0],
-                    structure_metadata.generation_size[0],
-                );
-                let structure_offset_z = div_floor(
-                    total_z + structure_metadata.grid_offset[1],
-                    structure_metadata.generation_size[1],
-                );
-                let structure_value = structure_metadata.noise.get_noise_2d(
-                    structure_offset_x as f32,
-                    structure_offset_z as f32,
-                ) * 0.5
-                    + 0.5;
-                if structure_metadata.generate_debug_blocks {
-                    let top_terrain = (noise_height
-                        .min(CHUNK_SIZE as f32 + min_height as f32)
-                        as i32
-                        - min_height.min(noise_height as i32))
-                    .max(1) as usize
-                        - 1;
-                    blocks.set_block(
-                        [x as i32, top_terrain as i32, z as i32],
-                        BlockType::Stone,
-                    );
-                }
-                let mut rand = StdRng::seed_from_u64(
-                    (structure_value.abs() * 10000.) as u64,
-                );
-
-                if structure_value > 0. {
-                    let random_x = rand.random_range(
-                        0..=structure_metadata.generation_size[0]
-                            - structure_metadata.model_size[0],
-                    );
-                    let random_z = rand.random_range(
-                        0..=structure_metadata.generation_size[1]
-                            - structure_metadata.model_size[2],
-                    );
-
-                    let structure_x: i32 = (total_x
-                        + structure_metadata.grid_offset[0]
-                        - structure_offset_x
-                            * structure_metadata.generation_size[0])
-                        .abs()
-                        - random_x;
-                    let structure_z: i32 = (total_z
-                        + structure_metadata.grid_offset[1]
-                        - structure_offset_z
-                            * structure_metadata.generation_size[1])
-                        .abs()
-                        - random_z;
+                for placement in placements {
+                    let structure_x = total_x - placement.center.x
+                        + structure_metadata.model_size[0] / 2;
+                    let structure_z = total_z - placement.center.y
+                        + structure_metadata.model_size[2] / 2;
 
                     if structure_x < 0
                         || structure_z < 0
@@ -216,59 +371,26 @@ pub fn generate_voxels(
                         continue;
                     }
 
-                    let structure_noise_height_x = structure_offset_x
-                        * structure_metadata.generation_size[0]
-                        + (structure_metadata.model_size[0] / 2)
-                        - structure_metadata.grid_offset[0]
-                        + random_x;
-                    let structure_noise_height_z = structure_offset_z
-                        * structure_metadata.generation_size[1]
-                        + (structure_metadata.model_size[2] / 2)
-                        - structure_metadata.grid_offset[1]
-                        + random_z;
-
-                    let structure_steepness = terrain_steepness.get([
-                        structure_noise_height_x as f64,
-                        structure_noise_height_z as f64,
-                    ]);
-
-                    if structure_steepness > 0.8 {
-                        continue;
-                    }
-
-                    let structure_center: IVec2 =
-                        [structure_noise_height_x, structure_noise_height_z]
-                            .into();
-
-                    let (a, _, _, _) = get_min_distance_to_path(
-                        structure_center,
-                        &all_paths,
-                        IVec2::new(
-                            structure_metadata.model_size[0] / 2,
-                            structure_metadata.model_size[2] / 2,
-                        ) + IVec2::ONE * 10,
-                    );
-
-                    if (a as i32)
-                        < structure_metadata.model_size[0] / 2
-                            + structure_metadata.model_size[1] / 2
-                    {
-                        continue;
+                    if structure_metadata.generate_debug_blocks {
+                        let top_terrain = (noise_height
+                            .min(CHUNK_SIZE as f32 + min_height as f32)
+                            as i32
+                            - min_height.min(noise_height as i32))
+                        .max(1)
+                            as usize
+                            - 1;
+                        blocks.set_block(
+                            [x as i32, top_terrain as i32, z as i32],
+                            BlockType::Stone,
+                        );
                     }
 
-                    let noise_height = terrain_noise.get([
-                        structure_noise_height_x as f64,
-                        structure_noise_height_z as f64,
-                    ]);
+                    let center = placement.center.as_dvec2().to_array();
+                    let noise_height = terrain_noise.get(center);
 
                     for (index, sub_structure) in structure_generator
-                        .get_structure_model(
-                            IVec2 {
-                                x: structure_offset_x,
-                                y: structure_offset_z,
-                            },
-                            chunk_lod,
-                        )[structure_x as usize]
+                        .get_structure_model(placement.cell, chunk_lod)
+                        [structure_x as usize]
                         .iter()
                         .enumerate()
                     {
@@ -311,25 +433,59 @@ pub fn generate_voxels(
                     }
                 }
             }
+
+            for y in (noise_height as i32).max(min_height)
+                ..(column.water_level as i32)
+                    .min(CHUNK_SIZE as i32 + 2 + min_height)
+            {
+                let position = [x as i32, y - min_height, z as i32];
+                if blocks.get_block(position) != BlockType::Air {
+                    continue;
+                }
+                if y == CHUNK_SIZE as i32 + 1 + min_height {
+                    generate_more = true;
+                }
+                blocks.set_block(position, BlockType::Water);
+            }
         }
     }
 
+    let surface_range =
+        -DECORATION_REACH..CHUNK_SIZE as i32 + 2 + DECORATION_REACH;
+    let surfaces = surface_range
+        .clone()
+        .cartesian_product(surface_range)
+        .map(|(x, z)| {
+            let column = columns.get_around(x, z);
+            Surface {
+                y: column.height as i32 - min_height,
+                biome: column.biome,
+                steepness: column.steepness,
+                is_path: column.is_path,
+            }
+        })
+        .collect_vec();
+
+    generate_more |= decorate(
+        &mut blocks,
+        &surfaces,
+        position,
+        chunk_lod,
+        &generation_options.decoration_settings,
+        generation_options.seed,
+    );
+
+    blocks.compact();
+
     (blocks, min_height, generate_more)
 }
 
-fn get_min_in_noise_map(
-    noise: &impl NoiseFn<f64, 2usize>,
-    chunk_offset: DVec2,
-    chunk_lod: ChunkLod,
-) -> f64 {
-    let mut min = noise.get(chunk_offset.to_array());
+fn get_min_in_noise_map(noise_grid: &NoiseGrid) -> f64 {
+    let mut min = noise_grid.get(0, 0);
 
     for x in 0..CHUNK_SIZE {
         for z in 0..CHUNK_SIZE {
-            let current = noise.get([
-                x as f64 * chunk_lod.multiplier_i32() as f64 + chunk_offset.x,
-                z as f64 * chunk_lod.multiplier_i32() as f64 + chunk_offset.y,
-            ]);
+            let current = noise_grid.get(x, z);
             if current < min {
                 min = current;
             }
@@ -339,9 +495,9 @@ fn get_min_in_noise_map(
     min
 }
 
-fn get_min_distance_to_path<'a>(
+pub fn get_min_distance_to_path<'a>(
     pos: IVec2,
-    paths_list: &'a Vec<&'a Vec<Path>>,
+    paths: impl IntoIterator<Item = &'a Path>,
     margin: IVec2,
 ) -> (f32, IVec2, Vec2, Option<&'a PathLine>) {
     let mut min: Option<f32> = None;
@@ -349,36 +505,34 @@ fn get_min_distance_to_path<'a>(
     let mut path_dir = Vec2::ZERO;
     let mut end_path = None;
 
-    for paths in paths_list {
-        for path in *paths {
-            if !path.is_in_box(pos, margin) {
+    for path in paths {
+        if !path.is_in_box(pos, margin) {
+            continue;
+        }
+
+        for line in &path.lines {
+            if !line.is_in_box(pos, margin) {
                 continue;
             }
 
-            for line in &path.lines {
-                if !line.is_in_box(pos, margin) {
-                    continue;
-                }
-
-                if let Some((closest_point, closest_path_dir)) =
-                    line.closest_point_on_path(pos, margin)
-                {
-                    let distance = closest_point.distance(pos.as_vec2());
-                    match min {
-                        None => {
+            if let Some((closest_point, closest_path_dir)) =
+                line.closest_point_on_path(pos, margin)
+            {
+                let distance = closest_point.distance(pos.as_vec2());
+                match min {
+                    None => {
+                        min = Some(distance);
+                        closest_point_total = closest_point.as_ivec2();
+                        path_dir = closest_path_dir;
+                        end_path = Some(line);
+                    }
+                    Some(current_min) => {
+                        if distance < current_min {
                             min = Some(distance);
                             closest_point_total = closest_point.as_ivec2();
                             path_dir = closest_path_dir;
                             end_path = Some(line);
                         }
-                        Some(current_min) => {
-                            if distance < current_min {
-                                min = Some(distance);
-                                closest_point_total = closest_point.as_ivec2();
-                                path_dir = closest_path_dir;
-                                end_path = Some(line);
-                            }
-                        }
                     }
                 }
             }
@@ -393,6 +547,71 @@ fn get_min_distance_to_path<'a>(
     )
 }
 
+/// Lowers the height of a column, in lod units, into the channels of the
+/// rivers around it. The banks slope down over another half width of the
+/// river. Also returns the water surface of the river the column lies in.
+fn carve_rivers(
+    column: IVec2,
+    height: f32,
+    rivers: &[&River],
+    terrain_noise: &impl NoiseFn<f64, 2>,
+    river_settings: &RiverSettings,
+    chunk_lod: ChunkLod,
+) -> (f32, Option<f32>) {
+    let mut carved_height = height;
+    let mut river_level: Option<f32> = None;
+
+    for river in rivers {
+        let (distance, closest_point_on_river, _, line) =
+            get_min_distance_to_path(
+                column,
+                [&river.path],
+                river_margin(river),
+            );
+        let Some(line) = line else {
+            continue;
+        };
+        let distance = distance / river.half_width;
+        if distance >= 2. {
+            continue;
+        }
+
+        // The water follows the line between the grid nodes, which runs
+        // downhill, but never stands above the ground it flows through.
+        let line_height = lerp(
+            terrain_noise.get(line.start.as_dvec2().to_array()) as f32,
+            terrain_noise.get(line.end.as_dvec2().to_array()) as f32,
+            line.get_progress_on_line(closest_point_on_river),
+        );
+        let surface = line_height
+            .min(
+                terrain_noise.get(closest_point_on_river.as_dvec2().to_array())
+                    as f32,
+            )
+            .min(height)
+            - 1. / chunk_lod.multiplier_f32();
+
+        if distance < 1. {
+            let depth =
+                river_settings.depth / VOXEL_SIZE / chunk_lod.multiplier_f32();
+            carved_height =
+                carved_height.min(surface - depth * (1. - distance.powi(2)));
+            river_level =
+                Some(river_level.map_or(surface, |level| level.max(surface)));
+        } else {
+            carved_height =
+                carved_height.min(lerp(surface, height, distance - 1.));
+        }
+    }
+
+    (carved_height, river_level)
+}
+
+/// How far around its path a river changes the terrain.
+fn river_margin(river: &River) -> IVec2 {
+    IVec2::splat((river.half_width * 2.).ceil() as i32 + 1)
+}
+
 fn lerp(a: f32, b: f32, f: f32) -> f32 {
     a + f * (b - a)
 }
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::world_generation::chunk_generation::{
+    CHUNK_SIZE, VOXEL_SIZE, block_shape::BlockShape, block_type::BlockType,
+    chunk::Chunk, chunk_lod::ChunkLod, voxel_data::VoxelData,
+};
+
+/// The voxels of every loaded chunk at full detail, so systems can ask
+/// which block is at a position in the world.
+///
+/// Chunks are added once they are generated and forgotten when their
+/// entity despawns, however the chunk tree changes. Every chunk answers
+/// for its own voxels only, not for its border, so stacked chunks and
+/// neighbours never disagree. Positions are in world voxels.
+#[derive(Resource, Default)]
+pub struct WorldVoxels {
+    /// The stacked chunks of every chunk column, with their entities.
+    columns: HashMap<IVec2, Vec<(Entity, VoxelData)>>,
+}
+
+/// Where a ray hit a block.
+#[derive(Clone, Copy, Debug)]
+pub struct VoxelHit {
+    pub position: IVec3,
+    /// The side of the voxel the ray entered through, zero if it started
+    /// inside of it.
+    pub normal: IVec3,
+    pub block: BlockType,
+    pub distance: f32,
+}
+
+impl WorldVoxels {
+    pub fn insert(
+        &mut self,
+        entity: Entity,
+        chunk_column: IVec2,
+        voxel_data: VoxelData,
+    ) {
+        self.columns
+            .entry(chunk_column)
+            .or_default()
+            .push((entity, voxel_data));
+    }
+
+    pub fn remove(&mut self, entity: Entity, chunk_column: IVec2) {
+        let Some(chunks) = self.columns.get_mut(&chunk_column) else {
+            return;
+        };
+
+        chunks.retain(|(chunk_entity, _)| *chunk_entity != entity);
+        if chunks.is_empty() {
+            self.columns.remove(&chunk_column);
+        }
+    }
+
+    /// The block at a world voxel, `None` if no loaded chunk covers it.
+    pub fn get_block(&self, position: IVec3) -> Option<BlockType> {
+        let chunk_column = (position.xz() - IVec2::ONE)
+            .div_euclid(IVec2::splat(CHUNK_SIZE as i32));
+
+        self.columns
+            .get(&chunk_column)?
+            .iter()
+            .find_map(|(_, voxel_data)| {
+                let local = voxel_data.world_to_local(position)?;
+                (local.min_element() >= 1
+                    && local.max_element() <= CHUNK_SIZE as i32)
+                    .then(|| voxel_data.get_block(local))
+            })
+    }
+
+    /// The loaded blocks between two world voxels, both included.
+    pub fn blocks_in_area(
+        &self,
+        min: IVec3,
+        max: IVec3,
+    ) -> impl Iterator<Item = (IVec3, BlockType)> + '_ {
+        (min.x..=max.x).flat_map(move |x| {
+            (min.y..=max.y).flat_map(move |y| {
+                (min.z..=max.z).filter_map(move |z| {
+                    let position = IVec3::new(x, y, z);
+                    self.get_block(position).map(|block| (position, block))
+                })
+            })
+        })
+    }
+
+    /// The first solid block along `ray` within `max_distance`, in world
+    /// units. Fluids and voxels of chunks that aren't loaded are passed
+    /// through.
+    pub fn raycast(&self, ray: Ray3d, max_distance: f32) -> Option<VoxelHit> {
+        raycast_voxels(ray, max_distance, |position| self.get_block(position))
+    }
+}
+
+/// The first solid block along `ray` within `max_distance`, in world
+/// units, with `get_block` telling which block is at a world voxel. Fluids
+/// and voxels it has no block for are passed through.
+pub fn raycast_voxels(
+    ray: Ray3d,
+    max_distance: f32,
+    get_block: impl Fn(IVec3) -> Option<BlockType>,
+) -> Option<VoxelHit> {
+    let origin = ray.origin / VOXEL_SIZE;
+    let direction = *ray.direction;
+    let max_distance = max_distance / VOXEL_SIZE;
+
+    // Walks the voxels along the ray one boundary at a time. `t_max`
+    // is the distance at which the ray crosses the next boundary on
+    // every axis, `t_delta` the distance between two boundaries.
+    let mut position = origin.floor().as_ivec3();
+    let step = IVec3::new(
+        if direction.x < 0. { -1 } else { 1 },
+        if direction.y < 0. { -1 } else { 1 },
+        if direction.z < 0. { -1 } else { 1 },
+    );
+    let next_boundary = position.as_vec3() + step.max(IVec3::ZERO).as_vec3();
+    let parallel = direction.cmpeq(Vec3::ZERO);
+    let mut t_max = Vec3::select(
+        parallel,
+        Vec3::INFINITY,
+        (next_boundary - origin) / direction,
+    );
+    let t_delta =
+        Vec3::select(parallel, Vec3::INFINITY, (1. / direction).abs());
+
+    let mut normal = IVec3::ZERO;
+    let mut distance = 0.;
+    loop {
+        if let Some(block) = get_block(position)
+            && !matches!(block.shape(), BlockShape::Empty | BlockShape::Fluid)
+        {
+            return Some(VoxelHit {
+                position,
+                normal,
+                block,
+                distance: distance * VOXEL_SIZE,
+            });
+        }
+
+        let axis = if t_max.x < t_max.y && t_max.x < t_max.z {
+            0
+        } else if t_max.y < t_max.z {
+            1
+        } else {
+            2
+        };
+        distance = t_max[axis];
+        if distance > max_distance {
+            return None;
+        }
+
+        position[axis] += step[axis];
+        t_max[axis] += t_delta[axis];
+        normal = IVec3::ZERO;
+        normal[axis] = -step[axis];
+    }
+}
+
+/// The voxels of a generated chunk on its way into `WorldVoxels`. They are
+/// inserted together with the `Chunk`, so they are remembered exactly when
+/// the chunk is added and forgotten when it is removed.
+#[derive(Component)]
+pub struct GeneratedVoxels(pub Option<VoxelData>);
+
+/// Remembers the voxels of a chunk when it is added.
+pub fn remember_chunk_voxels(
+    trigger: Trigger<OnAdd, Chunk>,
+    mut commands: Commands,
+    mut chunks: Query<(&Chunk, &mut GeneratedVoxels)>,
+    mut world_voxels: ResMut<WorldVoxels>,
+) {
+    let Ok((chunk, mut generated_voxels)) = chunks.get_mut(trigger.target())
+    else {
+        return;
+    };
+
+    if let Some(voxel_data) = generated_voxels.0.take() {
+        let chunk_column = chunk
+            .lod_position
+            .get_absolute_chunk_pos(chunk.tree_position);
+        world_voxels.insert(trigger.target(), *chunk_column, voxel_data);
+    }
+    commands
+        .entity(trigger.target())
+        .remove::<GeneratedVoxels>();
+}
+
+/// Forgets the voxels of a chunk when its entity despawns.
+pub fn forget_chunk_voxels(
+    trigger: Trigger<OnRemove, Chunk>,
+    chunks: Query<&Chunk>,
+    mut world_voxels: ResMut<WorldVoxels>,
+) {
+    let Ok(chunk) = chunks.get(trigger.target()) else {
+        return;
+    };
+    if chunk.lod_position.lod != ChunkLod::Full {
+        return;
+    }
+
+    let chunk_column = chunk
+        .lod_position
+        .get_absolute_chunk_pos(chunk.tree_position);
+    world_voxels.remove(trigger.target(), *chunk_column);
+}
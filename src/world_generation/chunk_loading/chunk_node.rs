@@ -2,7 +2,10 @@ use bevy::prelude::*;
 use itertools::Itertools;
 
 use crate::world_generation::{
-    chunk_generation::{chunk::Chunk, chunk_start::ChunkStart},
+    chunk_generation::{
+        chunk::{Chunk, StackColumns},
+        chunk_start::ChunkStart,
+    },
     chunk_loading::{
         chunk_loader::ChunkLoader, chunk_node_children::ChunkNodeChildren,
         chunk_tree::ChunkTreePos, lod_position::LodPosition,
@@ -19,6 +22,9 @@ pub struct ChunkNode {
     state: NodeState,
     is_dead: bool,
     chunk_children: Vec<Entity>,
+    /// Chunks generated with outdated options, which stay until the new
+    /// chunks of the node replace them.
+    outdated_chunks: Vec<Entity>,
 }
 
 impl Default for ChunkNode {
@@ -32,6 +38,7 @@ impl Default for ChunkNode {
             },
             is_dead: false,
             chunk_children: Vec::new(),
+            outdated_chunks: Vec::new(),
         }
     }
 }
@@ -59,6 +66,38 @@ impl ChunkNode {
         }
     }
 
+    pub fn position(&self) -> LodPosition {
+        self.position
+    }
+
+    pub fn parent(&self) -> Option<Entity> {
+        self.parent
+    }
+
+    /// Whether the node waits for its four children before it drops its
+    /// own chunks.
+    pub fn is_dividing(&self) -> bool {
+        matches!(self.state, NodeState::LeafToBranch { .. })
+    }
+
+    /// Whether the chunks of the node are generated again and wait to
+    /// replace the outdated ones.
+    pub fn is_regenerating(&self) -> bool {
+        !self.outdated_chunks.is_empty()
+    }
+
+    /// Generates the chunks of the node again, for example after the
+    /// terrain noise changed. The current chunks stay until the new ones
+    /// replace them.
+    pub fn regenerate(&mut self) {
+        if let NodeState::Leaf { spawned_task }
+        | NodeState::BranchToLeaf { spawned_task, .. } = &mut self.state
+        {
+            *spawned_task = false;
+            self.outdated_chunks.append(&mut self.chunk_children);
+        }
+    }
+
     pub fn to_branch(&mut self, node_children: ChunkNodeChildren) {
         self.state = NodeState::LeafToBranch {
             children: node_children,
@@ -138,6 +177,7 @@ pub fn check_for_task_spawning(
                 chunk_lod_pos: chunk_node.position,
                 chunk_tree_pos: chunk_node.tree_pos,
                 chunk_stack_offset: 0,
+                columns: None,
             },
             Visibility::Visible,
         ));
@@ -298,14 +338,21 @@ fn devide_chunk_node(
 
 pub fn stack_chunks(
     mut commands: Commands,
-    added_stacked_chunks: Query<(&Chunk, &ChildOf), Added<Chunk>>,
+    added_stacked_chunks: Query<
+        (Entity, &Chunk, &ChildOf, Option<&StackColumns>),
+        Added<Chunk>,
+    >,
     mut chunk_nodes: Query<(&mut ChunkNode, Entity)>,
 ) {
-    for (chunk, ChildOf(chunk_node_parent)) in added_stacked_chunks {
+    for (chunk_entity, chunk, ChildOf(chunk_node_parent), stack_columns) in
+        added_stacked_chunks
+    {
         if !chunk.generate_above {
             continue;
         }
 
+        commands.entity(chunk_entity).remove::<StackColumns>();
+
         let (mut parent_node, _) = chunk_nodes
             .iter_mut()
             .find(|node| node.1 == *chunk_node_parent)
@@ -317,6 +364,8 @@ pub fn stack_chunks(
                 chunk_lod_pos: chunk.lod_position,
                 chunk_tree_pos: chunk.tree_position,
                 chunk_stack_offset: chunk.chunk_height + 1,
+                columns: stack_columns
+                    .map(|stack_columns| stack_columns.0.clone()),
             },
             Visibility::Visible,
         ));
@@ -367,10 +416,34 @@ pub fn update_added_chunks(
             chunk_node.to_leaf_done();
         }
 
-        if let Some(chunk_node_parent) = chunk_node.parent {
+        let regenerated_chunks = chunk_node.is_regenerating().then(|| {
+            for outdated_chunk in chunk_node.outdated_chunks.drain(..) {
+                commands.entity(outdated_chunk).try_despawn();
+            }
+            chunk_node.chunk_children.clone()
+        });
+        let chunk_node_entity = *entity;
+        let chunk_node_parent = chunk_node.parent;
+
+        // The regenerated chunks were hidden until now, so they replace the
+        // outdated chunks in the same frame. Below a dividing node they stay
+        // hidden until it swaps in all of its children.
+        if let Some(regenerated_chunks) = regenerated_chunks
+            && !chunk_node_parent.is_some_and(|parent| {
+                all_nodes.iter().any(|(node, node_entity)| {
+                    *node_entity == parent && node.is_dividing()
+                })
+            })
+        {
+            for chunk in regenerated_chunks {
+                commands.entity(chunk).try_insert(Visibility::Visible);
+            }
+        }
+
+        if let Some(chunk_node_parent) = chunk_node_parent {
             update_parent_count(
                 chunk_node_parent,
-                *entity,
+                chunk_node_entity,
                 &mut all_nodes,
                 &mut commands,
             );
@@ -415,6 +488,7 @@ fn update_parent_count(
                 && *bottom_left_done
                 && *bottom_right_done
             {
+                let node_children = children.get_all().collect_vec();
                 parent_node.to_branch_done();
 
                 for chunk_child in &parent_node.chunk_children {
@@ -422,9 +496,28 @@ fn update_parent_count(
                     commands.entity(*chunk_child).try_despawn();
                 }
 
+                for outdated_chunk in &parent_node.outdated_chunks {
+                    commands.entity(*outdated_chunk).try_despawn();
+                }
+
                 parent_node.chunk_children.clear();
+                parent_node.outdated_chunks.clear();
+                let parent_parent = parent_node.parent;
+
+                // The chunks of the children were hidden until now, so they
+                // replace the old chunks in the same frame.
+                for (child_node, _) in chunk_nodes
+                    .iter()
+                    .filter(|node| node_children.contains(&node.1))
+                {
+                    for chunk_child in &child_node.chunk_children {
+                        commands
+                            .entity(*chunk_child)
+                            .try_insert(Visibility::Visible);
+                    }
+                }
 
-                let Some(parent_parent) = parent_node.parent else {
+                let Some(parent_parent) = parent_parent else {
                     return;
                 };
 
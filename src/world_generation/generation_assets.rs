@@ -29,6 +29,8 @@ pub struct GenerationAssets {
         Handle<ExtendedMaterial<StandardMaterial, ArrayTextureMaterial>>,
     pub transparent_material:
         Handle<ExtendedMaterial<StandardMaterial, ArrayTextureMaterial>>,
+    pub fluid_material:
+        Handle<ExtendedMaterial<StandardMaterial, ArrayTextureMaterial>>,
     pub texture_handle: Handle<Image>,
 }
 
@@ -55,6 +57,16 @@ pub fn load_block_texture_assets(
     block_textures.push(asset_server.load("default_leaves.png"));
     block_textures.push(asset_server.load("grass_side.png"));
     block_textures.push(asset_server.load("default_dirt.png"));
+    block_textures.push(asset_server.load("tall_grass.png"));
+    block_textures.push(asset_server.load("flower.png"));
+    block_textures.push(asset_server.load("glass.png"));
+    block_textures.push(asset_server.load("lantern.png"));
+    block_textures.push(asset_server.load("water.png"));
+    block_textures.push(asset_server.load("slate.png"));
+    block_textures.push(asset_server.load("sandstone.png"));
+    block_textures.push(asset_server.load("coal_ore.png"));
+    block_textures.push(asset_server.load("iron_ore.png"));
+    block_textures.push(asset_server.load("gold_ore.png"));
 
     commands.insert_resource(BlockTextureAssets { block_textures });
 
@@ -136,6 +148,7 @@ pub fn setup_array_texture(
             },
             extension: ArrayTextureMaterial {
                 array_texture: texture_handle.clone(),
+                wave_height: 0.,
             },
         }),
         transparent_material: materials.add(ExtendedMaterial {
@@ -147,6 +160,21 @@ pub fn setup_array_texture(
             },
             extension: ArrayTextureMaterial {
                 array_texture: texture_handle.clone(),
+                wave_height: 0.,
+            },
+        }),
+        fluid_material: materials.add(ExtendedMaterial {
+            base: StandardMaterial {
+                base_color: Color::NONE,
+                alpha_mode: AlphaMode::Blend,
+                perceptual_roughness: 0.1,
+                cull_mode: None,
+                double_sided: true,
+                ..Default::default()
+            },
+            extension: ArrayTextureMaterial {
+                array_texture: texture_handle.clone(),
+                wave_height: 0.08,
             },
         }),
         texture_handle,
@@ -1,103 +1,337 @@
 use crate::{
     utils::file_utils::read_ron_from_file,
-    world_generation::chunk_generation::{
-        block_type::BlockType,
-        noise::terrain_noise::{TERRAIN_NOISE_FILE_PATH, TerrainNoise},
-        structures::{
-            oak_structure_generator::OakStructureGenerator,
-            structure_generator::{StructureGenerator, VoxelStructureMetadata},
-            tree_structure_generator::TreeStructureGenerator,
+    world_generation::{
+        chunk_generation::{
+            block_type::BlockType,
+            noise::{noise_grid::GridNoiseFn, terrain_noise::TerrainNoise},
+            structures::structure_generator::StructureGenerator,
         },
+        seed_derivation::{
+            STRUCTURE_GENERATOR_SALT, TERRAIN_NOISE_SALT, derive_seed,
+        },
+        world_preset::WorldPreset,
     },
 };
 use bevy::prelude::*;
-use fastnoise_lite::FastNoiseLite;
-use noise::NoiseFn;
-use rand::prelude::StdRng;
-use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 #[derive(Resource)]
 pub struct GenerationOptionsResource(pub Arc<GenerationOptions>);
 
 impl GenerationOptionsResource {
-    pub fn from_seed(seed: u64) -> Self {
-        // let tree_house = vox_data_to_structure_data(
-        //     &from_file("assets/tree_house.vox").unwrap(),
-        // );
-        // let box_structure =
-        //     vox_data_to_structure_data(&from_file("assets/box.vox").unwrap());
+    pub fn from_preset(
+        seed: u64,
+        preset: &WorldPreset,
+    ) -> Result<Self, anyhow::Error> {
         let terrain_noise: TerrainNoise =
-            read_ron_from_file(TERRAIN_NOISE_FILE_PATH)
-                .expect("Failed loading terrain noise config.");
-
-        // let tree_model: StructureModel =
-        //     read_ron_from_file("assets/tree_test.ron")
-        //         .expect("Failed to load tree model.");
+            read_ron_from_file(&preset.terrain_noise_path)?;
+        terrain_noise.get_noise_fn(derive_seed(seed, TERRAIN_NOISE_SALT))?;
 
-        let mut rng = StdRng::seed_from_u64(seed);
+        let structures_seed = derive_seed(seed, STRUCTURE_GENERATOR_SALT);
+        let structure_generators = preset
+            .structures
+            .iter()
+            .enumerate()
+            .map(|(index, structure)| {
+                structure.placement.validate(index)?;
+                structure.build(derive_seed(structures_seed, index as u64))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-        Self(Arc::new(GenerationOptions {
+        Ok(Self(Arc::new(GenerationOptions {
             seed,
             terrain_noise,
-            generate_paths: false,
-            structure_generators: vec![
-                Arc::new(Box::new(OakStructureGenerator::new(
-                    VoxelStructureMetadata {
-                        model_size: [27, 27, 27],
-                        generation_size: [64, 64],
-                        grid_offset: [24, 16],
-                        generate_debug_blocks: false,
-                        debug_rgb_multiplier: [1., 1., 1.],
-                        noise: get_seeded_white_noise(rng.random()),
-                    },
-                ))),
-                Arc::new(Box::new(OakStructureGenerator::new(
-                    VoxelStructureMetadata {
-                        model_size: [27, 27, 27],
-                        generation_size: [64, 64],
-                        grid_offset: [43, 52],
-                        generate_debug_blocks: false,
-                        debug_rgb_multiplier: [1., 1., 1.],
-                        noise: get_seeded_white_noise(rng.random()),
-                    },
-                ))),
-                Arc::new(Box::new(OakStructureGenerator::new(
-                    VoxelStructureMetadata {
-                        model_size: [27, 27, 27],
-                        generation_size: [64, 64],
-                        grid_offset: [10, 4],
-                        generate_debug_blocks: false,
-                        debug_rgb_multiplier: [1., 1., 1.],
-                        noise: get_seeded_white_noise(rng.random()),
-                    },
-                ))),
-                // Arc::new(Box::new(FixedStructureGenerator {
-                //     fixed_structure_model: Arc::new(tree_model.blocks),
-                //     fixed_structure_metadata: VoxelStructureMetadata {
-                //         model_size: tree_model.model_size.to_array(),
-                //         generation_size: [10, 10],
-                //         grid_offset: [7, 4],
-                //         generate_debug_blocks: false,
-                //         debug_rgb_multiplier: [1., 1., 1.],
-                //         noise: get_seeded_white_noise(rng.random()),
-                //     },
-                // })),
+            path_settings: preset.paths.clone(),
+            biome_settings: preset.biome.clone(),
+            decoration_settings: preset.decorations.clone(),
+            water_settings: preset.water.clone(),
+            river_settings: preset.rivers.clone(),
+            erosion_settings: preset.erosion.clone(),
+            underground_settings: preset.underground.clone(),
+            structure_generators,
+            structure_assets: vec![],
+        })))
+    }
+}
+
+/// How paths between the cities of neighbouring countries are laid out.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PathSettings {
+    pub enabled: bool,
+    /// Steps of the path finding that climb more than this are not taken.
+    pub max_height_difference: f64,
+    pub height_difference_cost: f64,
+    pub steepness_cost: f64,
+}
+
+impl Default for PathSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_height_difference: 0.65,
+            height_difference_cost: 30.,
+            steepness_cost: 20.,
+        }
+    }
+}
+
+/// Decides which blocks cover the terrain.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BiomeSettings {
+    /// Height in meters above which snow covers the ground instead of grass.
+    pub snow_height: f32,
+    /// Slopes steeper than this show bare stone instead of grass.
+    pub max_grass_steepness: f64,
+    /// Slopes steeper than this show bare stone instead of snow.
+    pub max_snow_steepness: f64,
+    /// Structures are not placed on slopes steeper than this.
+    pub max_structure_steepness: f64,
+}
+
+impl Default for BiomeSettings {
+    fn default() -> Self {
+        Self {
+            snow_height: 3500.,
+            max_grass_steepness: 0.8,
+            max_snow_steepness: 1.2,
+            max_structure_steepness: 0.8,
+        }
+    }
+}
+
+/// How often a column of ground gets a decoration. The chances add up, so
+/// they should stay below one together.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DecorationSettings {
+    pub grass_tuft_chance: f64,
+    pub flower_chance: f64,
+    pub bush_chance: f64,
+    pub boulder_chance: f64,
+    /// Grass tufts, flowers and bushes don't grow on slopes steeper than
+    /// this.
+    pub max_plant_steepness: f64,
+}
+
+impl Default for DecorationSettings {
+    fn default() -> Self {
+        Self {
+            grass_tuft_chance: 0.2,
+            flower_chance: 0.015,
+            bush_chance: 0.004,
+            boulder_chance: 0.001,
+            max_plant_steepness: 0.6,
+        }
+    }
+}
+
+/// Where water fills the terrain.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WaterSettings {
+    /// Height in meters below which the terrain lies under the sea.
+    pub sea_level: f32,
+    /// Whether basins above the sea are filled with lakes.
+    pub lakes: bool,
+    /// Basins shallower than this in meters are left dry.
+    pub min_lake_depth: f32,
+}
+
+impl Default for WaterSettings {
+    fn default() -> Self {
+        Self {
+            sea_level: 16.,
+            lakes: true,
+            min_lake_depth: 4.,
+        }
+    }
+}
+
+/// How rivers run from the high ground down to the sea.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RiverSettings {
+    pub enabled: bool,
+    /// Height in meters above which rivers can spring.
+    pub source_height: f32,
+    /// The chance of a node of the river grid above the source height to
+    /// be a spring.
+    pub source_chance: f64,
+    /// Width in meters of a river fed by a single spring.
+    pub width: f32,
+    /// Width in meters every further spring upstream adds to a river.
+    pub width_per_source: f32,
+    pub max_width: f32,
+    /// Depth in meters of the middle of the channel.
+    pub depth: f32,
+}
+
+impl Default for RiverSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            source_height: 800.,
+            source_chance: 0.01,
+            width: 4.,
+            width_per_source: 2.,
+            max_width: 40.,
+            depth: 3.,
+        }
+    }
+}
+
+/// The erosion simulated on the terrain of every country. Water droplets
+/// wash material down the slopes and settle it in the valleys, then steep
+/// slopes crumble until they are no steeper than `talus`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ErosionSettings {
+    pub enabled: bool,
+    /// How many droplets run over the terrain per node of the erosion grid.
+    pub droplets_per_node: f32,
+    /// How much of its direction a droplet keeps, from 0 to 1.
+    pub inertia: f32,
+    /// How much sediment a droplet can carry for its speed and slope.
+    pub sediment_capacity: f32,
+    /// The share of the free capacity a droplet takes from the ground.
+    pub erosion_rate: f32,
+    /// The share of the sediment over capacity a droplet drops.
+    pub deposition_rate: f32,
+    /// The share of its water a droplet loses every step.
+    pub evaporation_rate: f32,
+    pub thermal_iterations: u32,
+    /// The slope from which the terrain starts to crumble.
+    pub talus: f32,
+}
+
+impl Default for ErosionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            droplets_per_node: 0.5,
+            inertia: 0.05,
+            sediment_capacity: 4.,
+            erosion_rate: 0.3,
+            deposition_rate: 0.3,
+            evaporation_rate: 0.02,
+            thermal_iterations: 4,
+            talus: 0.8,
+        }
+    }
+}
+
+/// What lies below the ground: dirt, then layers of rock bent up and down
+/// by 3D noise, with veins of ore at their depths.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UndergroundSettings {
+    /// Depth in meters of the dirt below flat ground.
+    pub dirt_depth: f32,
+    /// The dirt thins out towards this steepness and is gone above it.
+    pub max_dirt_steepness: f64,
+    /// The layers of rock from the bottom up. They repeat over the whole
+    /// height of the world, no layers means plain stone.
+    pub strata: Vec<Stratum>,
+    /// How far in meters the noise bends the layers up and down.
+    pub strata_warp: f32,
+    pub strata_warp_frequency: f64,
+    /// Checked in order, the first vein at a voxel wins.
+    pub ores: Vec<OreVein>,
+}
+
+impl Default for UndergroundSettings {
+    fn default() -> Self {
+        Self {
+            dirt_depth: 3.,
+            max_dirt_steepness: 1.,
+            strata: vec![
+                Stratum {
+                    block: BlockType::Stone,
+                    thickness: 14.,
+                },
+                Stratum {
+                    block: BlockType::Slate,
+                    thickness: 6.,
+                },
+                Stratum {
+                    block: BlockType::Stone,
+                    thickness: 9.,
+                },
+                Stratum {
+                    block: BlockType::Sandstone,
+                    thickness: 4.,
+                },
             ],
-            structure_assets: vec![
-                // StructureAsset {
-                //     _blocks: (*box_structure.0).clone(),
-                // }
+            strata_warp: 12.,
+            strata_warp_frequency: 0.01,
+            ores: vec![
+                OreVein {
+                    block: BlockType::CoalOre,
+                    min_depth: 2.,
+                    max_depth: 120.,
+                    frequency: 0.08,
+                    threshold: 0.75,
+                },
+                OreVein {
+                    block: BlockType::IronOre,
+                    min_depth: 12.,
+                    max_depth: 400.,
+                    frequency: 0.1,
+                    threshold: 0.8,
+                },
+                OreVein {
+                    block: BlockType::GoldOre,
+                    min_depth: 60.,
+                    max_depth: 2000.,
+                    frequency: 0.12,
+                    threshold: 0.88,
+                },
             ],
-        }))
+        }
     }
 }
 
-fn get_seeded_white_noise(seed: u64) -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(seed as i32);
-    noise.set_noise_type(Some(fastnoise_lite::NoiseType::Value));
-    noise.set_frequency(Some(100.));
-    noise
+/// A layer of rock.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Stratum {
+    pub block: BlockType,
+    /// Thickness in meters.
+    pub thickness: f32,
+}
+
+/// Where a kind of ore is found.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OreVein {
+    pub block: BlockType,
+    /// The depths in meters below the ground the veins lie between.
+    pub min_depth: f32,
+    pub max_depth: f32,
+    /// The frequency of the noise shaping the veins, higher makes them
+    /// smaller.
+    pub frequency: f64,
+    /// Noise values above this are ore, closer to 1 is rarer.
+    pub threshold: f64,
+}
+
+/// What covers the ground of a column.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Biome {
+    Grass,
+    Snow,
+    Rock,
+}
+
+impl BiomeSettings {
+    /// The biome at a height in meters.
+    pub fn biome_at(&self, height: f32, steepness: f64) -> Biome {
+        if height > self.snow_height {
+            if steepness < self.max_snow_steepness {
+                Biome::Snow
+            } else {
+                Biome::Rock
+            }
+        } else if steepness < self.max_grass_steepness {
+            Biome::Grass
+        } else {
+            Biome::Rock
+        }
+    }
 }
 
 pub struct GenerationOptions {
@@ -105,14 +339,43 @@ pub struct GenerationOptions {
     pub structure_generators:
         Vec<Arc<Box<dyn StructureGenerator + Send + Sync>>>,
     pub structure_assets: Vec<StructureAsset>,
-    pub generate_paths: bool,
+    pub path_settings: PathSettings,
+    pub biome_settings: BiomeSettings,
+    pub decoration_settings: DecorationSettings,
+    pub water_settings: WaterSettings,
+    pub river_settings: RiverSettings,
+    pub erosion_settings: ErosionSettings,
+    pub underground_settings: UndergroundSettings,
     pub terrain_noise: TerrainNoise,
 }
 
 impl GenerationOptions {
-    pub fn get_terrain_noise(&self) -> impl NoiseFn<f64, 2> {
+    /// Options that only generate terrain, without structures or paths.
+    pub fn from_terrain_noise(
+        seed: u64,
+        terrain_noise: TerrainNoise,
+    ) -> Result<Self, anyhow::Error> {
+        terrain_noise.get_noise_fn(derive_seed(seed, TERRAIN_NOISE_SALT))?;
+
+        Ok(Self {
+            seed,
+            structure_generators: vec![],
+            structure_assets: vec![],
+            path_settings: PathSettings::default(),
+            biome_settings: BiomeSettings::default(),
+            decoration_settings: DecorationSettings::default(),
+            water_settings: WaterSettings::default(),
+            river_settings: RiverSettings::default(),
+            erosion_settings: ErosionSettings::default(),
+            underground_settings: UndergroundSettings::default(),
+            terrain_noise,
+        })
+    }
+
+    pub fn get_terrain_noise(&self) -> impl GridNoiseFn {
         self.terrain_noise
-            .get_noise_fn(&mut StdRng::seed_from_u64(self.seed + 1))
+            .get_noise_fn(derive_seed(self.seed, TERRAIN_NOISE_SALT))
+            .expect("The terrain noise is checked when the options are built!")
     }
 }
 
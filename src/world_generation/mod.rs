@@ -3,5 +3,8 @@ pub mod chunk_generation;
 pub mod chunk_loading;
 pub mod generation_assets;
 pub mod generation_options;
+pub mod seed_derivation;
+pub mod terrain_noise_watcher;
 pub mod world_generation_plugin;
 pub mod world_generation_state;
+pub mod world_preset;
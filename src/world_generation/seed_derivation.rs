@@ -0,0 +1,129 @@
+use bevy::math::IVec2;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+// Every generator derives its seed from the world seed with its own salt,
+// so no two generators share random values:
+//
+// - terrain noise: `derive_seed(world, TERRAIN_NOISE_SALT)`, and each node of
+//   it `derive_seed(terrain, node_index)`
+// - structure generator `i`: `derive_seed(derive_seed(world,
+//   STRUCTURE_GENERATOR_SALT), i)`, which also seeds its placement noise
+// - structure candidate of a placement cell: `positional_rng(derive_seed(
+//   generator, STRUCTURE_PLACEMENT_SALT), cell)`
+// - structure density noise: `derive_seed(generator, STRUCTURE_DENSITY_SALT)`
+// - structure model of a placement cell: `positional_rng(derive_seed(generator,
+//   STRUCTURE_MODEL_SALT), cell)`
+// - decoration of a column: `positional_seed(derive_seed(world,
+//   DECORATION_SALT), column)`
+// - city of a country: `positional_rng(derive_seed(world,
+//   CITY_LOCATION_SALT), country)`
+// - river spring at a node of the river grid: `positional_rng(derive_seed(
+//   world, RIVER_SOURCE_SALT), column)`
+// - erosion droplets of a country: `positional_rng(derive_seed(world,
+//   EROSION_SALT), country)`
+// - strata noise: `derive_seed(world, STRATA_SALT)`
+// - ore vein `i` noise: `derive_seed(derive_seed(world, ORE_SALT), i)`
+//
+// The salts, the hashing and the random number generator are part of the
+// world format, changing them changes every generated world.
+
+pub const TERRAIN_NOISE_SALT: u64 = 1;
+pub const STRUCTURE_GENERATOR_SALT: u64 = 2;
+pub const STRUCTURE_PLACEMENT_SALT: u64 = 3;
+pub const STRUCTURE_MODEL_SALT: u64 = 4;
+pub const CITY_LOCATION_SALT: u64 = 5;
+pub const STRUCTURE_DENSITY_SALT: u64 = 6;
+pub const DECORATION_SALT: u64 = 7;
+pub const RIVER_SOURCE_SALT: u64 = 8;
+pub const EROSION_SALT: u64 = 9;
+pub const STRATA_SALT: u64 = 10;
+pub const ORE_SALT: u64 = 11;
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// The world seed for the text entered when creating a world. Whole
+/// numbers are used as they are, any other text is hashed with FNV-1a.
+pub fn seed_from_text(text: &str) -> u64 {
+    let text = text.trim();
+    if let Ok(seed) = text.parse::<u64>() {
+        return seed;
+    }
+    if let Ok(seed) = text.parse::<i64>() {
+        return seed as u64;
+    }
+
+    text.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
+
+/// Mixes the salt into the seed (splitmix64), so neighbouring salts get
+/// unrelated seeds.
+pub fn derive_seed(seed: u64, salt: u64) -> u64 {
+    let mut z =
+        seed.wrapping_add(salt.wrapping_add(1).wrapping_mul(GOLDEN_GAMMA));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A seed for one position, hashed from the bits of both coordinates so
+/// mirrored positions don't collide.
+pub fn positional_seed(seed: u64, position: IVec2) -> u64 {
+    derive_seed(
+        derive_seed(seed, position.x as u32 as u64),
+        position.y as u32 as u64,
+    )
+}
+
+/// The random number generator of the world generation. Unlike `StdRng`,
+/// its algorithm is pinned, so a seed gives the same values on every
+/// platform and with every version of `rand`.
+pub type WorldRng = ChaCha8Rng;
+
+/// A random number generator for a seed. The key is spread from the seed
+/// with `derive_seed` rather than `seed_from_u64`, so no library decides
+/// how the seed becomes the key.
+pub fn seeded_rng(seed: u64) -> WorldRng {
+    let mut key = [0; 32];
+    for (index, chunk) in key.chunks_exact_mut(8).enumerate() {
+        chunk.copy_from_slice(&derive_seed(seed, index as u64).to_le_bytes());
+    }
+    WorldRng::from_seed(key)
+}
+
+pub fn positional_rng(seed: u64, position: IVec2) -> WorldRng {
+    seeded_rng(positional_seed(seed, position))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::*;
+
+    // Changing any of these values changes every generated world.
+
+    #[test]
+    fn seed_from_text_keeps_numbers() {
+        assert_eq!(seed_from_text("12345"), 12345);
+        assert_eq!(seed_from_text(" -1 "), u64::MAX);
+    }
+
+    #[test]
+    fn seed_from_text_hashes_with_fnv_1a() {
+        assert_eq!(seed_from_text("hello world"), 0x779A_65E7_023C_D2E7);
+    }
+
+    #[test]
+    fn derive_seed_is_pinned() {
+        assert_eq!(derive_seed(0, TERRAIN_NOISE_SALT), 7960286522194355700);
+    }
+
+    #[test]
+    fn seeded_rng_is_pinned() {
+        let mut rng = seeded_rng(derive_seed(0, TERRAIN_NOISE_SALT));
+        assert_eq!(rng.next_u64(), 8187297555550158464);
+    }
+}
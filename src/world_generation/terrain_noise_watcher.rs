@@ -0,0 +1,97 @@
+use std::{fs, time::SystemTime};
+
+use bevy::prelude::*;
+
+use crate::world_generation::{
+    chunk_generation::{
+        chunk_start::ChunkStart,
+        chunk_task::ChunkTask,
+        country::{
+            cache_generation_task::CacheGenerationTask,
+            country_cache::CountryCache,
+        },
+    },
+    chunk_loading::chunk_node::ChunkNode,
+    generation_options::GenerationOptionsResource,
+    world_preset::WorldPreset,
+};
+
+const WATCH_INTERVAL_SECONDS: f32 = 1.;
+
+/// Watches the terrain noise file of the world preset, so changes saved in
+/// the terrain editor show up in a running game without restarting it.
+#[derive(Resource)]
+pub struct TerrainNoiseWatcher {
+    timer: Timer,
+    preset: WorldPreset,
+    last_modified: Option<SystemTime>,
+}
+
+impl TerrainNoiseWatcher {
+    /// Starts watching from the version of the file the world is created
+    /// with.
+    pub fn new(preset: WorldPreset) -> Self {
+        Self {
+            timer: Timer::from_seconds(
+                WATCH_INTERVAL_SECONDS,
+                TimerMode::Repeating,
+            ),
+            last_modified: file_modified(&preset.terrain_noise_path),
+            preset,
+        }
+    }
+}
+
+fn file_modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Rebuilds the generation options from the changed terrain noise file,
+/// throws away all cached countries and generates the loaded chunks again
+/// with the new noise. The old chunks stay until the new ones replace them,
+/// so the ground never disappears below the player.
+pub fn reload_terrain_noise(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut watcher: ResMut<TerrainNoiseWatcher>,
+    mut generation_options: ResMut<GenerationOptionsResource>,
+    mut country_cache: ResMut<CountryCache>,
+    mut chunk_nodes: Query<&mut ChunkNode>,
+    outdated_tasks: Query<
+        Entity,
+        Or<(With<CacheGenerationTask>, With<ChunkStart>, With<ChunkTask>)>,
+    >,
+) {
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let modified = file_modified(&watcher.preset.terrain_noise_path);
+    if modified == watcher.last_modified {
+        return;
+    }
+    watcher.last_modified = modified;
+
+    let seed = generation_options.0.seed;
+    match GenerationOptionsResource::from_preset(seed, &watcher.preset) {
+        Ok(reloaded_options) => *generation_options = reloaded_options,
+        Err(error) => {
+            warn!("Could not reload terrain noise: {error}");
+            return;
+        }
+    }
+    info!("Reloaded terrain noise.");
+
+    // Countries and chunks that are still generating would be stored with
+    // the old noise, the chunk nodes start new tasks for them.
+    for entity in &outdated_tasks {
+        commands.entity(entity).despawn();
+    }
+    *country_cache = CountryCache::default();
+
+    for mut chunk_node in &mut chunk_nodes {
+        chunk_node.regenerate();
+    }
+}
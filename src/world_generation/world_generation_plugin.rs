@@ -5,6 +5,7 @@ use crate::world_generation::{
     generation_assets::{
         GenerationAssetState, load_block_texture_assets, setup_array_texture,
     },
+    terrain_noise_watcher::{TerrainNoiseWatcher, reload_terrain_noise},
     world_generation_state::{
         WorldGenerationState, check_world_done_initializing,
         check_world_gen_started,
@@ -31,6 +32,8 @@ impl Plugin for WorldGenerationPlugin {
                     check_world_done_initializing.run_if(in_state(
                         WorldGenerationState::InitialGeneration,
                     )),
+                    reload_terrain_noise
+                        .run_if(resource_exists::<TerrainNoiseWatcher>),
                 ),
             )
             .add_plugins(ChunkGenerationPlugin);
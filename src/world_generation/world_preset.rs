@@ -0,0 +1,145 @@
+use std::{fs, sync::Arc};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    utils::file_utils::read_ron_from_file,
+    world_generation::{
+        chunk_generation::structures::{
+            oak_structure_generator::OakStructureGenerator,
+            pine_structure_generator::PineStructureGenerator,
+            structure_generator::{
+                FixedStructureGenerator, StructureGenerator,
+                VoxelStructureMetadata,
+            },
+            structure_model::StructureModel,
+            structure_placement::PlacementSettings,
+            tree_structure_generator::TreeStructureGenerator,
+        },
+        generation_options::{
+            BiomeSettings, DecorationSettings, ErosionSettings, PathSettings,
+            RiverSettings, UndergroundSettings, WaterSettings,
+        },
+    },
+};
+
+pub const WORLD_PRESETS_DIRECTORY: &str = "assets/world_presets";
+
+/// Everything a new world is generated from, except for its seed.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WorldPreset {
+    pub name: String,
+    pub terrain_noise_path: String,
+    pub structures: Vec<StructurePreset>,
+    #[serde(default)]
+    pub paths: PathSettings,
+    #[serde(default)]
+    pub biome: BiomeSettings,
+    #[serde(default)]
+    pub decorations: DecorationSettings,
+    #[serde(default)]
+    pub water: WaterSettings,
+    #[serde(default)]
+    pub rivers: RiverSettings,
+    #[serde(default)]
+    pub erosion: ErosionSettings,
+    #[serde(default)]
+    pub underground: UndergroundSettings,
+}
+
+impl WorldPreset {
+    /// Reads all presets of the preset directory, ordered by file name.
+    /// Presets that can't be read are skipped with a warning.
+    pub fn load_all() -> Vec<WorldPreset> {
+        let entries = match fs::read_dir(WORLD_PRESETS_DIRECTORY) {
+            Ok(entries) => entries,
+            Err(error) => {
+                warn!("Could not read {WORLD_PRESETS_DIRECTORY}: {error}");
+                return vec![];
+            }
+        };
+
+        let mut paths = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        paths
+            .iter()
+            .filter_map(|path| {
+                let path = path.to_string_lossy();
+                read_ron_from_file(&path)
+                    .inspect_err(|error| {
+                        warn!("Could not read world preset {path}: {error}")
+                    })
+                    .ok()
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum StructureKind {
+    Oak,
+    Pine,
+    /// A structure model saved by the structure builder.
+    Fixed {
+        model_path: String,
+    },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StructurePreset {
+    pub kind: StructureKind,
+    /// Fixed structures take the size of their model instead.
+    #[serde(default)]
+    pub model_size: [i32; 3],
+    pub placement: PlacementSettings,
+    #[serde(default)]
+    pub generate_debug_blocks: bool,
+    #[serde(default = "default_debug_rgb_multiplier")]
+    pub debug_rgb_multiplier: [f32; 3],
+}
+
+fn default_debug_rgb_multiplier() -> [f32; 3] {
+    [1., 1., 1.]
+}
+
+impl StructurePreset {
+    pub fn build(
+        &self,
+        seed: u64,
+    ) -> Result<Arc<Box<dyn StructureGenerator + Send + Sync>>, anyhow::Error>
+    {
+        let metadata = |model_size| VoxelStructureMetadata {
+            model_size,
+            placement: self.placement.clone(),
+            generate_debug_blocks: self.generate_debug_blocks,
+            debug_rgb_multiplier: self.debug_rgb_multiplier,
+            seed,
+        };
+
+        let structure_generator: Box<dyn StructureGenerator + Send + Sync> =
+            match &self.kind {
+                StructureKind::Oak => Box::new(OakStructureGenerator::new(
+                    metadata(self.model_size),
+                )),
+                StructureKind::Pine => Box::new(PineStructureGenerator::new(
+                    metadata(self.model_size),
+                )),
+                StructureKind::Fixed { model_path } => {
+                    let model: StructureModel = read_ron_from_file(model_path)?;
+                    Box::new(FixedStructureGenerator {
+                        fixed_structure_metadata: metadata(
+                            model.model_size.to_array(),
+                        ),
+                        fixed_structure_model: Arc::new(model.blocks),
+                    })
+                }
+            };
+
+        Ok(Arc::new(structure_generator))
+    }
+}